@@ -5,8 +5,8 @@ use once_cell::sync::Lazy;
 use radix_trie::Trie;
 use trie_hard::TrieHard;
 
-/// This is a rip off of the benchmark suite for for
-/// [`radix_trie`](https://github.com/michaelsproul/rust_radix_trie/blob/master/Cargo.toml)
+// This is a rip off of the benchmark suite for for
+// [`radix_trie`](https://github.com/michaelsproul/rust_radix_trie/blob/master/Cargo.toml)
 
 const OW_1984: &str = include_str!("../data/1984.txt");
 const SUN_RISING: &str = include_str!("../data/sun-rising.txt");
@@ -0,0 +1,96 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Support for tries generated at compile time. Generated code calls the
+//! `const` constructors in here so that a trie can be stored in a `static`.
+//! Nothing in this module is part of the public API.
+
+use std::borrow::Cow;
+
+pub use crate::render::{render, skeleton, MaskParts, Skeleton, SkeletonNode};
+use crate::{
+    u256::U256, MasksByByteSized, NodeStorage, Normalization, SearchNode,
+    TrieHardSized, TrieState,
+};
+
+/// Generated tries have no normalization, so every byte in `entries` has a
+/// bit of its own and the entries, which are in byte order, are also in bit
+/// order
+pub const fn masks<I: Copy>(
+    zero: I,
+    entries: &[(u8, I)],
+) -> MasksByByteSized<I> {
+    let mut by_byte = [zero; 256];
+    let mut edge_bytes = [0; 256];
+    let mut i = 0;
+    while i < entries.len() {
+        by_byte[entries[i].0 as usize] = entries[i].1;
+        edge_bytes[i] = entries[i].0;
+        i += 1;
+    }
+    MasksByByteSized {
+        by_byte,
+        edge_bytes,
+        edge_count: entries.len(),
+    }
+}
+
+pub const fn u256(parts: [u64; 4]) -> U256 {
+    U256::from_parts(parts)
+}
+
+pub const fn sized<'a, T, I>(
+    masks: MasksByByteSized<I>,
+    nodes: &'a [TrieState<'a, T, I>],
+    below: &'a [usize],
+) -> TrieHardSized<'a, T, I>
+where
+    T: Clone,
+    I: Clone,
+{
+    TrieHardSized {
+        masks,
+        nodes: NodeStorage::Static(nodes, <[TrieState<'a, T, I>]>::to_vec),
+        dead: 0,
+        normalization: Normalization::None,
+        below: Cow::Borrowed(below),
+    }
+}
+
+pub const fn leaf<'a, T, I>(key: &'a [u8], value: T) -> TrieState<'a, T, I> {
+    TrieState::Leaf(key, value)
+}
+
+pub const fn search<'a, T, I>(
+    mask: I,
+    edge_start: usize,
+) -> TrieState<'a, T, I> {
+    TrieState::Search(SearchNode { mask, edge_start })
+}
+
+pub const fn search_or_leaf<'a, T, I>(
+    key: &'a [u8],
+    value: T,
+    mask: I,
+    edge_start: usize,
+) -> TrieState<'a, T, I> {
+    TrieState::SearchOrLeaf(key, value, SearchNode { mask, edge_start })
+}
+
+pub const fn run<'a, T, I>(
+    bytes: &'a [u8],
+    next: usize,
+) -> TrieState<'a, T, I> {
+    TrieState::Run(bytes, next)
+}
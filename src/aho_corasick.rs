@@ -0,0 +1,653 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+use crate::{u256::U256, TrieHard, TrieHardSized, TrieState};
+
+/// Which match to report when several keys match at the leftmost position of
+/// a haystack. Matches never overlap, and scanning resumes at the end of the
+/// reported match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Report the key that comes first in key order. Keys matching at the same
+    /// position are prefixes of each other, so this is the shortest one.
+    #[default]
+    LeftmostFirst,
+    /// Report the longest key
+    LeftmostLongest,
+}
+
+/// Automaton for finding every occurrence of the keys of a trie inside a
+/// larger haystack. It attaches an Aho-Corasick failure link and the longest
+/// key ending there to each node of the trie, so transitions still go
+/// through the bitmask edges of the trie and the automaton takes a fixed
+/// number of words per node.
+///
+/// Positions partway through the tail of a leaf key or through a run have no
+/// stored link. A scan works theirs out from the link of their node the
+/// first time it reaches them and keeps it until the scan is dropped.
+///
+/// Matches are reported from the scan itself as soon as no earlier partial
+/// match is still alive, and scanning resumes at the end of the match.
+///
+/// ```
+/// # use trie_hard::{MatchKind, TrieHard};
+/// let trie = ["cat", "category", "dog"]
+///     .into_iter()
+///     .collect::<TrieHard<'_, _>>();
+///
+/// let automaton = trie.aho_corasick(MatchKind::LeftmostLongest);
+///
+/// assert_eq!(
+///     automaton.find_iter("hotdog category").collect::<Vec<_>>(),
+///     [(3, 6, "dog"), (7, 15, "category")]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub enum AhoCorasick<'b, 'a, T> {
+    /// Variant for automata over trie-hard tries built on u8
+    U8(AhoCorasickSized<'b, 'a, T, u8>),
+    /// Variant for automata over trie-hard tries built on u16
+    U16(AhoCorasickSized<'b, 'a, T, u16>),
+    /// Variant for automata over trie-hard tries built on u32
+    U32(AhoCorasickSized<'b, 'a, T, u32>),
+    /// Variant for automata over trie-hard tries built on u64
+    U64(AhoCorasickSized<'b, 'a, T, u64>),
+    /// Variant for automata over trie-hard tries built on u128
+    U128(AhoCorasickSized<'b, 'a, T, u128>),
+    /// Variant for automata over trie-hard tries built on u256
+    U256(AhoCorasickSized<'b, 'a, T, U256>),
+}
+
+/// Aho-Corasick automaton over a trie-hard trie built on a specific integer
+/// size
+#[derive(Debug, Clone)]
+pub struct AhoCorasickSized<'b, 'a, T, I> {
+    trie: &'b TrieHardSized<'a, T, I>,
+    kind: MatchKind,
+    /// Depth and link of each node, left at their default for slots that
+    /// are not reachable from the root
+    links: Vec<NodeLinks>,
+}
+
+/// One position in the trie, which may be partway through the key of a leaf
+/// or through a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Position {
+    node: usize,
+    /// Number of bytes from the root to this position
+    depth: usize,
+}
+
+const ROOT: Position = Position { node: 0, depth: 0 };
+
+/// A key ending at some position
+#[derive(Debug, Clone, Copy)]
+struct Output {
+    /// Length of the key
+    len: usize,
+    /// Node holding the entry of the key
+    node: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    /// Position for the longest proper suffix of this position that is also
+    /// a prefix of some key
+    fail: Position,
+    /// Longest key that is a suffix of this position
+    output: Option<Output>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NodeLinks {
+    /// Number of bytes from the root to the node
+    depth: usize,
+    /// Link of the position where the node is entered
+    link: Link,
+}
+
+impl Default for NodeLinks {
+    fn default() -> Self {
+        NodeLinks {
+            depth: 0,
+            link: Link {
+                fail: ROOT,
+                output: None,
+            },
+        }
+    }
+}
+
+impl<'b, 'a, T> AhoCorasick<'b, 'a, T>
+where
+    T: Copy,
+{
+    /// Create an iterator over the non-overlapping occurrences of keys in
+    /// `haystack`, as `(start, end, value)` with `haystack[start..end]` being
+    /// the matched key
+    ///
+    /// ```
+    /// # use trie_hard::{MatchKind, TrieHard};
+    /// let trie = ["he", "hers", "she"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let automaton = trie.aho_corasick(MatchKind::LeftmostFirst);
+    ///
+    /// assert_eq!(
+    ///     automaton.find_iter("ushers").collect::<Vec<_>>(),
+    ///     [(1, 4, "she")]
+    /// );
+    /// ```
+    pub fn find_iter<'h, H: AsRef<[u8]> + ?Sized>(
+        &self,
+        haystack: &'h H,
+    ) -> FindIter<'_, 'b, 'a, 'h, T> {
+        match self {
+            AhoCorasick::U8(ac) => FindIter::U8(ac.find_iter(haystack)),
+            AhoCorasick::U16(ac) => FindIter::U16(ac.find_iter(haystack)),
+            AhoCorasick::U32(ac) => FindIter::U32(ac.find_iter(haystack)),
+            AhoCorasick::U64(ac) => FindIter::U64(ac.find_iter(haystack)),
+            AhoCorasick::U128(ac) => FindIter::U128(ac.find_iter(haystack)),
+            AhoCorasick::U256(ac) => FindIter::U256(ac.find_iter(haystack)),
+        }
+    }
+}
+
+impl<'a, T> TrieHard<'a, T> {
+    /// Build an automaton for finding the keys of this trie inside larger
+    /// inputs. See [`AhoCorasick`].
+    ///
+    /// ```
+    /// # use trie_hard::{MatchKind, TrieHard};
+    /// let trie = ["do", "dot"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let first = trie.aho_corasick(MatchKind::LeftmostFirst);
+    /// let longest = trie.aho_corasick(MatchKind::LeftmostLongest);
+    ///
+    /// assert_eq!(first.find_iter("dots").next(), Some((0, 2, "do")));
+    /// assert_eq!(longest.find_iter("dots").next(), Some((0, 3, "dot")));
+    /// ```
+    pub fn aho_corasick(&self, kind: MatchKind) -> AhoCorasick<'_, 'a, T> {
+        match self {
+            TrieHard::U8(trie) => AhoCorasick::U8(trie.aho_corasick(kind)),
+            TrieHard::U16(trie) => AhoCorasick::U16(trie.aho_corasick(kind)),
+            TrieHard::U32(trie) => AhoCorasick::U32(trie.aho_corasick(kind)),
+            TrieHard::U64(trie) => AhoCorasick::U64(trie.aho_corasick(kind)),
+            TrieHard::U128(trie) => AhoCorasick::U128(trie.aho_corasick(kind)),
+            TrieHard::U256(trie) => AhoCorasick::U256(trie.aho_corasick(kind)),
+        }
+    }
+}
+
+/// Structure used for iterating over the occurrences of keys in a haystack
+#[derive(Debug)]
+pub enum FindIter<'s, 'b, 'a, 'h, T> {
+    /// Variant for automata over trie-hard tries built on u8
+    U8(FindIterSized<'s, 'b, 'a, 'h, T, u8>),
+    /// Variant for automata over trie-hard tries built on u16
+    U16(FindIterSized<'s, 'b, 'a, 'h, T, u16>),
+    /// Variant for automata over trie-hard tries built on u32
+    U32(FindIterSized<'s, 'b, 'a, 'h, T, u32>),
+    /// Variant for automata over trie-hard tries built on u64
+    U64(FindIterSized<'s, 'b, 'a, 'h, T, u64>),
+    /// Variant for automata over trie-hard tries built on u128
+    U128(FindIterSized<'s, 'b, 'a, 'h, T, u128>),
+    /// Variant for automata over trie-hard tries built on u256
+    U256(FindIterSized<'s, 'b, 'a, 'h, T, U256>),
+}
+
+/// Structure for iterating over the occurrences of keys in a haystack with an
+/// automaton built on a specific integer size
+#[derive(Debug)]
+pub struct FindIterSized<'s, 'b, 'a, 'h, T, I> {
+    automaton: &'s AhoCorasickSized<'b, 'a, T, I>,
+    haystack: &'h [u8],
+    /// Position to resume scanning from, or `None` once the haystack is done
+    pos: Option<usize>,
+    /// Links of the positions partway through leaves and runs that this scan
+    /// has needed so far
+    inner: HashMap<Position, Link>,
+}
+
+impl<'s, 'b, 'a, 'h, T> Iterator for FindIter<'s, 'b, 'a, 'h, T>
+where
+    T: Copy,
+{
+    type Item = (usize, usize, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            FindIter::U8(iter) => iter.next(),
+            FindIter::U16(iter) => iter.next(),
+            FindIter::U32(iter) => iter.next(),
+            FindIter::U64(iter) => iter.next(),
+            FindIter::U128(iter) => iter.next(),
+            FindIter::U256(iter) => iter.next(),
+        }
+    }
+}
+
+macro_rules! aho_corasick_impls {
+    ($($int_type:ty),+) => {
+        $(
+            aho_corasick_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            /// Build an automaton for finding the keys of this trie inside
+            /// larger inputs
+            ///
+            /// ```
+            /// # use trie_hard::{MatchKind, TrieHard};
+            /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// let automaton = sized_trie.aho_corasick(MatchKind::LeftmostFirst);
+            /// assert_eq!(
+            ///     automaton.find_iter("pants").collect::<Vec<_>>(),
+            ///     [(1, 4, "ant")]
+            /// );
+            /// ```
+            pub fn aho_corasick(
+                &self,
+                kind: MatchKind,
+            ) -> AhoCorasickSized<'_, 'a, T, $int_type> {
+                let mut automaton = AhoCorasickSized {
+                    trie: self,
+                    kind,
+                    links: vec![NodeLinks::default(); self.nodes.len()],
+                };
+                if self.nodes.is_empty() {
+                    return automaton;
+                }
+
+                let edge_bytes = self.masks.edge_bytes();
+                let mut inner = HashMap::new();
+
+                // Nodes are linked in order of depth, so that every position
+                // a node can fail to already has its link. Each pending node
+                // comes with the position before it and the byte leading
+                // from there.
+                let mut pending = BinaryHeap::new();
+                let push_children = |here: Position,
+                                         pending: &mut BinaryHeap<_>| {
+                    match &self.nodes[here.node] {
+                        TrieState::Leaf(..) => {}
+                        TrieState::Run(run, next) => {
+                            let last = run.len() - 1;
+                            let before = Position {
+                                node: here.node,
+                                depth: here.depth + last,
+                            };
+                            pending.push(Reverse((
+                                here.depth + run.len(),
+                                *next,
+                                before,
+                                run[last],
+                            )));
+                        }
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => {
+                            for c in edge_bytes {
+                                if let Some(child) =
+                                    search.evaluate(*c, &self.masks)
+                                {
+                                    pending.push(Reverse((
+                                        here.depth + 1,
+                                        child,
+                                        here,
+                                        *c,
+                                    )));
+                                }
+                            }
+                        }
+                    }
+                };
+
+                automaton.links[0].link.output = automaton.terminal(ROOT);
+                push_children(ROOT, &mut pending);
+
+                while let Some(Reverse((depth, node, before, c))) =
+                    pending.pop()
+                {
+                    let fail = if before.depth == 0 {
+                        ROOT
+                    } else {
+                        let fail = automaton.link(before, &mut inner).fail;
+                        automaton.next_position(fail, c, &mut inner)
+                    };
+                    let here = Position { node, depth };
+                    let output = automaton.terminal(here).or_else(|| {
+                        automaton.link(fail, &mut inner).output
+                    });
+
+                    automaton.links[node] = NodeLinks {
+                        depth,
+                        link: Link { fail, output },
+                    };
+                    push_children(here, &mut pending);
+                }
+
+                automaton
+            }
+        }
+
+        impl<'b, 'a, T> AhoCorasickSized<'b, 'a, T, $int_type> {
+            /// The key ending exactly at a position, if there is one
+            fn terminal(&self, here: Position) -> Option<Output> {
+                match &self.trie.nodes[here.node] {
+                    TrieState::Leaf(k, _)
+                    | TrieState::SearchOrLeaf(k, _, _)
+                        if k.len() == here.depth =>
+                    {
+                        Some(Output {
+                            len: here.depth,
+                            node: here.node,
+                        })
+                    }
+                    _ => None,
+                }
+            }
+
+            /// Byte leading out of a position partway through a leaf or a
+            /// run, on the way to the next position of the same node
+            fn inner_byte(&self, here: Position) -> u8 {
+                match &self.trie.nodes[here.node] {
+                    TrieState::Leaf(k, _) => k[here.depth],
+                    TrieState::Run(run, _) => {
+                        run[here.depth - self.links[here.node].depth]
+                    }
+                    TrieState::Search(_) | TrieState::SearchOrLeaf(..) => {
+                        unreachable!("search nodes have a single position")
+                    }
+                }
+            }
+
+            /// Link of a position. Positions partway through a leaf or a run
+            /// are worked out from the closest earlier position of the same
+            /// node whose link is known, and kept in `inner`.
+            fn link(
+                &self,
+                here: Position,
+                inner: &mut HashMap<Position, Link>,
+            ) -> Link {
+                let entry = self.links[here.node];
+                if here.depth == entry.depth {
+                    return entry.link;
+                }
+                if let Some(link) = inner.get(&here) {
+                    return *link;
+                }
+
+                let mut at = Position {
+                    node: here.node,
+                    depth: here.depth - 1,
+                };
+                let mut link = loop {
+                    if at.depth == entry.depth {
+                        break entry.link;
+                    }
+                    if let Some(link) = inner.get(&at) {
+                        break *link;
+                    }
+                    at.depth -= 1;
+                };
+
+                while at.depth < here.depth {
+                    let c = self.inner_byte(at);
+                    let fail = if at.depth == 0 {
+                        ROOT
+                    } else {
+                        self.next_position(link.fail, c, inner)
+                    };
+                    at.depth += 1;
+                    let output = self
+                        .terminal(at)
+                        .or_else(|| self.link(fail, inner).output);
+
+                    link = Link { fail, output };
+                    inner.insert(at, link);
+                }
+                link
+            }
+
+            /// Follow the trie edge for `c` out of a position, if there is
+            /// one
+            fn step(&self, here: Position, c: u8) -> Option<Position> {
+                let normalization = &self.trie.normalization;
+                let matches = |k: u8| normalization.byte(k) == normalization.byte(c);
+                let Position { node, depth } = here;
+                match &self.trie.nodes[node] {
+                    TrieState::Leaf(k, _) => k
+                        .get(depth)
+                        .filter(|k| matches(**k))
+                        .map(|_| Position { node, depth: depth + 1 }),
+                    TrieState::Run(run, next) => {
+                        let offset = depth - self.links[node].depth;
+                        if !matches(run[offset]) {
+                            None
+                        } else if offset + 1 < run.len() {
+                            Some(Position { node, depth: depth + 1 })
+                        } else {
+                            Some(Position { node: *next, depth: depth + 1 })
+                        }
+                    }
+                    TrieState::Search(search)
+                    | TrieState::SearchOrLeaf(_, _, search) => search
+                        .evaluate(c, &self.trie.masks)
+                        .map(|child| Position { node: child, depth: depth + 1 }),
+                }
+            }
+
+            /// Follow the transition for `c` out of a position, going through
+            /// failure links until some position accepts it
+            fn next_position(
+                &self,
+                mut here: Position,
+                c: u8,
+                inner: &mut HashMap<Position, Link>,
+            ) -> Position {
+                loop {
+                    if let Some(next) = self.step(here, c) {
+                        return next;
+                    }
+                    if here.depth == 0 {
+                        return ROOT;
+                    }
+                    here = self.link(here, inner).fail;
+                }
+            }
+
+            /// Create an iterator over the non-overlapping occurrences of
+            /// keys in `haystack`. See [`AhoCorasick`] for how matches are
+            /// found.
+            pub fn find_iter<'h, H: AsRef<[u8]> + ?Sized>(
+                &self,
+                haystack: &'h H,
+            ) -> FindIterSized<'_, 'b, 'a, 'h, T, $int_type> {
+                FindIterSized {
+                    automaton: self,
+                    haystack: haystack.as_ref(),
+                    pos: (!self.links.is_empty()).then_some(0),
+                    inner: HashMap::new(),
+                }
+            }
+        }
+
+        impl<'s, 'b, 'a, 'h, T> Iterator for FindIterSized<'s, 'b, 'a, 'h, T, $int_type>
+        where
+            T: Copy
+        {
+            type Item = (usize, usize, T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let pos = self.pos?;
+                let automaton = self.automaton;
+                let kind = automaton.kind;
+
+                // Scan until no partial match that could beat the best match
+                // so far is still alive. The earliest partial match that is
+                // alive starts at `i - here.depth`.
+                let mut here = ROOT;
+                let mut best = automaton.links[0].link.output.map(|out| (pos, out));
+                for (i, c) in self.haystack.iter().enumerate().skip(pos) {
+                    if let Some((start, _)) = best {
+                        let alive = i - here.depth;
+                        let done = match kind {
+                            MatchKind::LeftmostFirst => alive >= start,
+                            MatchKind::LeftmostLongest => alive > start,
+                        };
+                        if done {
+                            break;
+                        }
+                    }
+
+                    here = automaton.next_position(here, *c, &mut self.inner);
+                    let Some(out) = automaton.link(here, &mut self.inner).output
+                    else {
+                        continue;
+                    };
+                    let start = i + 1 - out.len;
+                    let better = match best {
+                        None => true,
+                        Some((best_start, best_out)) => {
+                            start < best_start
+                                || (start == best_start
+                                    && kind == MatchKind::LeftmostLongest
+                                    && out.len > best_out.len)
+                        }
+                    };
+                    if better {
+                        best = Some((start, out));
+                    }
+                }
+
+                let Some((start, out)) = best else {
+                    self.pos = None;
+                    return None;
+                };
+
+                // Safety: Outputs always point at a node holding an entry
+                let (_, value) = automaton.trie.entry(out.node).unwrap();
+                let end = start + out.len;
+
+                // An empty match must not be reported twice at one position
+                let next = if out.len == 0 { end + 1 } else { end };
+                self.pos = (next <= self.haystack.len()).then_some(next);
+                Some((start, end, value))
+            }
+        }
+    };
+}
+
+aho_corasick_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::TrieHardBuilder;
+
+    /// Find matches by trying every start position in turn
+    fn naive<'a>(
+        trie: &TrieHard<'a, &'a str>,
+        kind: MatchKind,
+        haystack: &[u8],
+    ) -> Vec<(usize, usize, &'a str)> {
+        let mut found = Vec::new();
+        let mut pos = 0;
+        while pos <= haystack.len() {
+            let rest = &haystack[pos..];
+            let matched = match kind {
+                MatchKind::LeftmostFirst => trie.prefixes_of(rest).next(),
+                MatchKind::LeftmostLongest => trie.longest_prefix(rest),
+            };
+            match matched {
+                Some((key, value)) => {
+                    found.push((pos, pos + key.len(), value));
+                    pos += key.len().max(1);
+                }
+                None => pos += 1,
+            }
+        }
+        found
+    }
+
+    #[rstest]
+    #[case(&["he", "she", "his", "hers"], "ushers")]
+    #[case(&["he", "she", "his", "hers"], "ahishershe")]
+    #[case(&["a", "ab", "abc", "bcd", "c"], "abcd")]
+    #[case(&["abcd", "bc", "b"], "abcabcd")]
+    #[case(&["aaa", "aa", "a"], "aaaaaaa")]
+    #[case(&["xabcdy", "xabcdz", "bc"], "xabcdq")]
+    #[case(&["aaaab", "ab"], "aaaaaaaab")]
+    #[case(&["abcde", "b", "cdx"], "abcdx")]
+    #[case(&["", "a"], "bab")]
+    #[case(&[""], "")]
+    #[case(&["x"], "")]
+    #[case(&[], "abc")]
+    fn test_matches_naive(#[case] input: &[&str], #[case] haystack: &str) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        for kind in [MatchKind::LeftmostFirst, MatchKind::LeftmostLongest] {
+            let automaton = trie.aho_corasick(kind);
+            assert_eq!(
+                automaton.find_iter(haystack).collect::<Vec<_>>(),
+                naive(&trie, kind, haystack.as_bytes()),
+                "{kind:?}"
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/1984.txt"))]
+    fn test_full_text(#[case] text: &str) {
+        // Use short words as keys so that many of them overlap in the text
+        let trie = text
+            .split_whitespace()
+            .filter(|w| w.len() <= 4)
+            .collect::<TrieHard<'_, _>>();
+        for kind in [MatchKind::LeftmostFirst, MatchKind::LeftmostLongest] {
+            let automaton = trie.aho_corasick(kind);
+            let found = automaton.find_iter(text).collect::<Vec<_>>();
+            assert_eq!(found, naive(&trie, kind, text.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_normalized() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"Token"[..], 0), (b"ok", 1)]);
+        let automaton = trie.aho_corasick(MatchKind::LeftmostLongest);
+
+        assert_eq!(
+            automaton.find_iter("a TOKEN, OK").collect::<Vec<_>>(),
+            [(2, 7, 0), (9, 11, 1)]
+        );
+    }
+}
@@ -0,0 +1,465 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use crate::{u256::U256, TrieHard, TrieHardSized, TrieState};
+
+/// A deterministic automaton over bytes, such as a compiled regular
+/// expression, that can be run against every key of a trie at once with
+/// [`TrieHard::search`]. The automaton is stepped along each branch of the
+/// trie, and a branch is abandoned as soon as [`Automaton::can_match`] says
+/// that no key below it can match.
+///
+/// ```
+/// # use trie_hard::{Automaton, TrieHard};
+/// /// Matches keys containing the given byte
+/// struct Contains(u8);
+///
+/// impl Automaton for Contains {
+///     type State = bool;
+///
+///     fn start(&self) -> bool {
+///         false
+///     }
+///
+///     fn accept(&self, found: &bool, byte: u8) -> bool {
+///         *found || byte == self.0
+///     }
+///
+///     fn is_match(&self, found: &bool) -> bool {
+///         *found
+///     }
+/// }
+///
+/// let trie = ["and", "ant", "dad", "do", "dot"]
+///     .into_iter()
+///     .collect::<TrieHard<'_, _>>();
+///
+/// assert_eq!(
+///     trie.search(Contains(b't')).map(|(_, v)| v).collect::<Vec<_>>(),
+///     ["ant", "dot"]
+/// );
+/// ```
+pub trait Automaton {
+    /// State of the automaton after reading some bytes
+    type State;
+
+    /// State before any bytes have been read
+    fn start(&self) -> Self::State;
+
+    /// State after reading `byte` in `state`
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State;
+
+    /// Whether the bytes read to reach `state` are a match
+    fn is_match(&self, state: &Self::State) -> bool;
+
+    /// Whether `state`, or any state reached from it by reading more bytes,
+    /// is a match. Returning `false` lets the search skip the rest of the
+    /// branch. The default never skips anything.
+    fn can_match(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+impl<A> Automaton for &A
+where
+    A: Automaton + ?Sized,
+{
+    type State = A::State;
+
+    fn start(&self) -> Self::State {
+        (**self).start()
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        (**self).accept(state, byte)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        (**self).is_match(state)
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        (**self).can_match(state)
+    }
+}
+
+impl<'a, T> TrieHard<'a, T> {
+    /// Create an iterator over the keys of the trie that are matched by an
+    /// automaton, ordered by their keys. See [`Automaton`]. Tries that
+    /// normalize their keys pass the normalized bytes to the automaton.
+    ///
+    /// ```
+    /// # use trie_hard::{Automaton, TrieHard};
+    /// /// Matches keys of at most the given length
+    /// struct MaxLen(usize);
+    ///
+    /// impl Automaton for MaxLen {
+    ///     type State = usize;
+    ///
+    ///     fn start(&self) -> usize {
+    ///         0
+    ///     }
+    ///
+    ///     fn accept(&self, len: &usize, _: u8) -> usize {
+    ///         len + 1
+    ///     }
+    ///
+    ///     fn is_match(&self, len: &usize) -> bool {
+    ///         *len <= self.0
+    ///     }
+    ///
+    ///     fn can_match(&self, len: &usize) -> bool {
+    ///         *len <= self.0
+    ///     }
+    /// }
+    ///
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.search(MaxLen(2)).map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["do"]
+    /// );
+    /// ```
+    pub fn search<A: Automaton>(
+        &self,
+        automaton: A,
+    ) -> AutomatonIter<'_, 'a, T, A> {
+        match self {
+            TrieHard::U8(trie) => AutomatonIter::U8(trie.search(automaton)),
+            TrieHard::U16(trie) => AutomatonIter::U16(trie.search(automaton)),
+            TrieHard::U32(trie) => AutomatonIter::U32(trie.search(automaton)),
+            TrieHard::U64(trie) => AutomatonIter::U64(trie.search(automaton)),
+            TrieHard::U128(trie) => AutomatonIter::U128(trie.search(automaton)),
+            TrieHard::U256(trie) => AutomatonIter::U256(trie.search(automaton)),
+        }
+    }
+}
+
+/// Structure used for iterating over the keys of a trie matched by an
+/// automaton
+#[derive(Debug)]
+pub enum AutomatonIter<'b, 'a, T, A: Automaton> {
+    /// Variant for trie-hard tries built on u8
+    U8(AutomatonIterSized<'b, 'a, T, u8, A>),
+    /// Variant for trie-hard tries built on u16
+    U16(AutomatonIterSized<'b, 'a, T, u16, A>),
+    /// Variant for trie-hard tries built on u32
+    U32(AutomatonIterSized<'b, 'a, T, u32, A>),
+    /// Variant for trie-hard tries built on u64
+    U64(AutomatonIterSized<'b, 'a, T, u64, A>),
+    /// Variant for trie-hard tries built on u128
+    U128(AutomatonIterSized<'b, 'a, T, u128, A>),
+    /// Variant for trie-hard tries built on u256
+    U256(AutomatonIterSized<'b, 'a, T, U256, A>),
+}
+
+/// Structure for iterating over the keys of a trie-hard trie built on a
+/// specific integer size that are matched by an automaton
+pub struct AutomatonIterSized<'b, 'a, T, I, A: Automaton> {
+    trie: &'b TrieHardSized<'a, T, I>,
+    automaton: A,
+    /// One byte for each mask bit, so that edges can be fed to the automaton
+    edge_bytes: &'b [u8],
+    /// Node to visit next, with its depth and the automaton state there
+    pending: Option<(usize, usize, A::State)>,
+    stack: Vec<AutomatonFrame<A::State, I>>,
+}
+
+// Automaton states are not required to implement `Debug`
+impl<'b, 'a, T, I, A: Automaton> fmt::Debug
+    for AutomatonIterSized<'b, 'a, T, I, A>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AutomatonIterSized")
+            .field("depth", &self.stack.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A search node whose children are being visited
+#[derive(Debug)]
+struct AutomatonFrame<S, I> {
+    state: S,
+    depth: usize,
+    /// Mask bits of the children that have not been visited yet
+    bits: I,
+    /// Index of the next child to visit
+    child: usize,
+}
+
+impl<'b, 'a, T, A> Iterator for AutomatonIter<'b, 'a, T, A>
+where
+    T: Copy,
+    A: Automaton,
+{
+    type Item = (&'a [u8], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            AutomatonIter::U8(iter) => iter.next(),
+            AutomatonIter::U16(iter) => iter.next(),
+            AutomatonIter::U32(iter) => iter.next(),
+            AutomatonIter::U64(iter) => iter.next(),
+            AutomatonIter::U128(iter) => iter.next(),
+            AutomatonIter::U256(iter) => iter.next(),
+        }
+    }
+}
+
+macro_rules! automaton_impls {
+    ($($int_type:ty),+) => {
+        $(
+            automaton_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            /// Create an iterator over the keys of the trie that are matched
+            /// by an automaton, ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::{Automaton, TrieHard};
+            /// struct EndsWithT;
+            ///
+            /// impl Automaton for EndsWithT {
+            ///     type State = bool;
+            ///
+            ///     fn start(&self) -> bool {
+            ///         false
+            ///     }
+            ///
+            ///     fn accept(&self, _: &bool, byte: u8) -> bool {
+            ///         byte == b't'
+            ///     }
+            ///
+            ///     fn is_match(&self, last_is_t: &bool) -> bool {
+            ///         *last_is_t
+            ///     }
+            /// }
+            ///
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.search(EndsWithT).map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["ant", "dot"]
+            /// );
+            /// ```
+            pub fn search<A: Automaton>(
+                &self,
+                automaton: A,
+            ) -> AutomatonIterSized<'_, 'a, T, $int_type, A> {
+                let start = automaton.start();
+                let pending = (!self.nodes.is_empty() && automaton.can_match(&start))
+                    .then_some((0, 0, start));
+
+                AutomatonIterSized {
+                    trie: self,
+                    automaton,
+                    edge_bytes: self.masks.edge_bytes(),
+                    pending,
+                    stack: Vec::new(),
+                }
+            }
+        }
+
+        impl<'b, 'a, T, A> Iterator for AutomatonIterSized<'b, 'a, T, $int_type, A>
+        where
+            T: Copy,
+            A: Automaton,
+        {
+            type Item = (&'a [u8], T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let normalization = &self.trie.normalization;
+
+                loop {
+                    if let Some((node_index, depth, state)) = self.pending.take() {
+                        let (search, found) = match &self.trie.nodes[node_index] {
+                            TrieState::Leaf(k, v) => {
+                                // The rest of the key is held by the leaf itself
+                                let mut state = state;
+                                let mut alive = true;
+                                for c in &k[depth..] {
+                                    state = self.automaton.accept(&state, normalization.byte(*c));
+                                    alive = self.automaton.can_match(&state);
+                                    if !alive {
+                                        break;
+                                    }
+                                }
+                                if alive && self.automaton.is_match(&state) {
+                                    return Some((k, *v));
+                                }
+                                continue;
+                            }
+                            TrieState::Run(run, next) => {
+                                let mut state = state;
+                                for c in *run {
+                                    state = self.automaton.accept(&state, normalization.byte(*c));
+                                    if !self.automaton.can_match(&state) {
+                                        break;
+                                    }
+                                }
+                                if self.automaton.can_match(&state) {
+                                    self.pending = Some((*next, depth + run.len(), state));
+                                }
+                                continue;
+                            }
+                            TrieState::Search(search) => (search, None),
+                            TrieState::SearchOrLeaf(k, v, search) => (
+                                search,
+                                self.automaton.is_match(&state).then_some((*k, *v)),
+                            ),
+                        };
+
+                        self.stack.push(AutomatonFrame {
+                            state,
+                            depth,
+                            bits: search.mask,
+                            child: search.edge_start,
+                        });
+                        if found.is_some() {
+                            return found;
+                        }
+                        continue;
+                    }
+
+                    let frame = self.stack.last_mut()?;
+                    if frame.bits == <$int_type>::default() {
+                        self.stack.pop();
+                        continue;
+                    }
+
+                    let c = self.edge_bytes[frame.bits.trailing_zeros() as usize];
+                    frame.bits = frame.bits & (frame.bits - 1);
+                    let child = frame.child;
+                    frame.child += 1;
+
+                    let state = self.automaton.accept(&frame.state, normalization.byte(c));
+                    if self.automaton.can_match(&state) {
+                        self.pending = Some((child, frame.depth + 1, state));
+                    }
+                }
+            }
+        }
+    };
+}
+
+automaton_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    /// Matches keys that start with `first` and end with `last`, like the
+    /// regular expression `^first.*last$`
+    struct StartEnd {
+        first: u8,
+        last: u8,
+        steps: Cell<usize>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum State {
+        Start,
+        Dead,
+        Inside { at_end: bool },
+    }
+
+    impl Automaton for StartEnd {
+        type State = State;
+
+        fn start(&self) -> State {
+            State::Start
+        }
+
+        fn accept(&self, state: &State, byte: u8) -> State {
+            self.steps.set(self.steps.get() + 1);
+            match state {
+                State::Start if byte == self.first => State::Inside {
+                    at_end: byte == self.last,
+                },
+                State::Start | State::Dead => State::Dead,
+                State::Inside { .. } => State::Inside {
+                    at_end: byte == self.last,
+                },
+            }
+        }
+
+        fn is_match(&self, state: &State) -> bool {
+            *state == State::Inside { at_end: true }
+        }
+
+        fn can_match(&self, state: &State) -> bool {
+            *state != State::Dead
+        }
+    }
+
+    #[rstest]
+    #[case(b'a', b't')]
+    #[case(b't', b'e')]
+    #[case(b'x', b'x')]
+    #[case(b'I', b'.')]
+    fn test_matches_filter(#[case] first: u8, #[case] last: u8) {
+        let text = include_str!("../data/1984.txt");
+        let trie = text.split_whitespace().collect::<TrieHard<'_, _>>();
+        let automaton = StartEnd {
+            first,
+            last,
+            steps: Cell::new(0),
+        };
+
+        let expected = trie
+            .iter()
+            .filter(|(k, _)| {
+                k.first() == Some(&first) && k.last() == Some(&last)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(trie.search(&automaton).collect::<Vec<_>>(), expected);
+
+        // Only the branch below the first byte is walked
+        let under_first = trie
+            .prefix_search([first])
+            .map(|(k, _)| k.len())
+            .sum::<usize>();
+        assert!(automaton.steps.get() <= under_first + 256);
+    }
+
+    #[test]
+    fn test_empty() {
+        let trie = TrieHard::<'_, ()>::new(vec![]);
+        let automaton = StartEnd {
+            first: b'a',
+            last: b'a',
+            steps: Cell::new(0),
+        };
+        assert_eq!(trie.search(&automaton).count(), 0);
+        assert_eq!(automaton.steps.get(), 0);
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of Rust source for tries that are built ahead of time. This is
+//! meant to be used from a build script for key sets that are too large to
+//! write out in an invocation of the `trie_hard!` macro from the
+//! `trie-hard-macros` crate. The generated file declares a
+//! `static` [`TrieHard`] laid out exactly like one built at runtime by
+//! [`TrieHard::new`], so the program only needs to include it.
+//!
+//! ```no_run
+//! // build.rs
+//! use trie_hard::codegen::StaticTrie;
+//!
+//! fn main() -> std::io::Result<()> {
+//!     let out_dir = std::env::var("OUT_DIR").unwrap();
+//!
+//!     StaticTrie::new("HEADERS", "bool")
+//!         .words_from_file("data/headers.txt", |_| "true".to_owned())?
+//!         .write_to_file(format!("{out_dir}/headers.rs"))
+//! }
+//! ```
+//!
+//! The program then includes the generated file wherever the trie is needed:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/headers.rs"));
+//!
+//! assert_eq!(HEADERS.get("accept-datetime"), Some(true));
+//! ```
+//!
+//! Generated code refers to the crate as `::trie_hard`, so `trie-hard` must
+//! be a regular dependency as well as a build dependency.
+
+use std::{collections::BTreeMap, fs, io, path::Path};
+
+use crate::{render::render, TrieHard};
+
+/// Builder for a Rust source file declaring a `static` trie. Values are given
+/// as the Rust source of an expression that can be evaluated in a `const`
+/// context, and the value type must implement `Clone`.
+#[derive(Debug, Clone)]
+pub struct StaticTrie {
+    name: String,
+    value_type: String,
+    visibility: String,
+    entries: BTreeMap<Vec<u8>, String>,
+}
+
+impl StaticTrie {
+    /// Start a trie that will be declared as `pub static {name}:
+    /// TrieHard<'static, {value_type}>`.
+    ///
+    /// ```
+    /// # use trie_hard::codegen::StaticTrie;
+    /// let source = StaticTrie::new("EMPTY", "u8").to_source();
+    ///
+    /// assert!(source.contains(
+    ///     "pub static EMPTY: ::trie_hard::TrieHard<'static, u8> ="
+    /// ));
+    /// ```
+    pub fn new(name: impl Into<String>, value_type: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value_type: value_type.into(),
+            visibility: "pub".to_owned(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Set the visibility of the generated `static`, for example `pub(crate)`
+    /// or an empty string for a private item. Defaults to `pub`.
+    ///
+    /// ```
+    /// # use trie_hard::codegen::StaticTrie;
+    /// let source = StaticTrie::new("EMPTY", "u8")
+    ///     .visibility("pub(crate)")
+    ///     .to_source();
+    ///
+    /// assert!(source.contains("pub(crate) static EMPTY"));
+    /// ```
+    pub fn visibility(&mut self, visibility: impl Into<String>) -> &mut Self {
+        self.visibility = visibility.into();
+        self
+    }
+
+    /// Add a key along with the source of its value. Adding a key again
+    /// replaces its value, the same as repeating a key in [`TrieHard::new`]
+    /// or in the `trie_hard!` macro.
+    ///
+    /// ```
+    /// # use trie_hard::codegen::StaticTrie;
+    /// let source = StaticTrie::new("STATUS", "u16")
+    ///     .entry("ok", "200")
+    ///     .entry(b"not-found", "404")
+    ///     .to_source();
+    ///
+    /// assert!(source.contains(r#"leaf(b"not-found", 404)"#));
+    /// ```
+    pub fn entry(
+        &mut self,
+        key: impl AsRef<[u8]>,
+        value: impl Into<String>,
+    ) -> &mut Self {
+        self.entries.insert(key.as_ref().to_vec(), value.into());
+        self
+    }
+
+    /// Add every whitespace separated word in `text` as a key. The source of
+    /// each value is produced by calling `value` with the word.
+    ///
+    /// ```
+    /// # use trie_hard::codegen::StaticTrie;
+    /// let source = StaticTrie::new("WORDS", "&'static str")
+    ///     .words("and ant\ndad", |word| format!("{word:?}"))
+    ///     .to_source();
+    ///
+    /// assert!(source.contains(r#"leaf(b"dad", "dad")"#));
+    /// ```
+    pub fn words(
+        &mut self,
+        text: &str,
+        mut value: impl FnMut(&str) -> String,
+    ) -> &mut Self {
+        for word in text.split_whitespace() {
+            self.entry(word, value(word));
+        }
+        self
+    }
+
+    /// Read a file and add the whitespace separated words in it as keys, like
+    /// [`StaticTrie::words`].
+    pub fn words_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        value: impl FnMut(&str) -> String,
+    ) -> io::Result<&mut Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(self.words(&text, value))
+    }
+
+    /// Render the Rust source declaring the trie.
+    ///
+    /// ```
+    /// # use trie_hard::codegen::StaticTrie;
+    /// let source = StaticTrie::new("WORDS", "u8")
+    ///     .entry("and", "0")
+    ///     .entry("ant", "1")
+    ///     .to_source();
+    ///
+    /// assert!(source.contains("::trie_hard::TrieHard::U8("));
+    /// ```
+    pub fn to_source(&self) -> String {
+        let (keys, values): (Vec<_>, Vec<_>) = self
+            .entries
+            .iter()
+            .map(|(key, value)| (key.as_slice(), value.clone()))
+            .unzip();
+        let trie = TrieHard::new(keys.into_iter().zip(0..).collect());
+
+        let visibility = if self.visibility.is_empty() {
+            String::new()
+        } else {
+            format!("{} ", self.visibility)
+        };
+
+        format!(
+            "// Generated by trie-hard. Do not edit.\n\n\
+            {visibility}static {}: ::trie_hard::TrieHard<'static, {}> =\n\
+            {};\n",
+            self.name,
+            self.value_type,
+            render(&trie, &values, "::trie_hard")
+        )
+    }
+
+    /// Render the Rust source declaring the trie and write it to `path`. The
+    /// file is left untouched if it already holds the same source, so that
+    /// dependent code is not rebuilt needlessly.
+    pub fn write_to_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let source = self.to_source();
+
+        if fs::read_to_string(path).is_ok_and(|existing| existing == source) {
+            return Ok(());
+        }
+        fs::write(path, source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_to_file() {
+        let path = std::env::temp_dir()
+            .join(format!("trie-hard-codegen-{}.rs", std::process::id()));
+        let mut trie = StaticTrie::new("WORDS", "()");
+        trie.entry("a", "()");
+
+        trie.write_to_file(&path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), trie.to_source());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_repeated_key() {
+        let source = StaticTrie::new("WORDS", "u8")
+            .entry("and", "0")
+            .entry("ant", "1")
+            .entry("and", "2")
+            .to_source();
+
+        assert!(source.contains(r#"leaf(b"and", 2)"#));
+        assert!(!source.contains(r#"leaf(b"and", 0)"#));
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{u256::U256, TrieHard, TrieHardSized, TrieState};
+
+/// Result of feeding one byte to a [`TrieCursor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorState<T> {
+    /// No key in the trie starts with the bytes fed so far
+    Dead,
+    /// Some key in the trie starts with the bytes fed so far, but none is
+    /// equal to them
+    InProgress,
+    /// The bytes fed so far are a key of the trie with the given value. Longer
+    /// keys may still match if more bytes are fed.
+    Matched(T),
+}
+
+/// Cursor for matching a key against a trie one byte at a time, for keys that
+/// arrive in pieces. Each byte costs the same as one step of
+/// [`TrieHard::get`], and the cursor gives up as soon as no key can match.
+///
+/// ```
+/// # use trie_hard::{CursorState, TrieHard};
+/// let trie = ["and", "ant", "dad", "do", "dot"]
+///     .into_iter()
+///     .collect::<TrieHard<'_, _>>();
+///
+/// let mut cursor = trie.cursor();
+/// assert_eq!(cursor.advance(b'd'), CursorState::InProgress);
+/// assert_eq!(cursor.advance(b'o'), CursorState::Matched("do"));
+/// assert_eq!(cursor.advance(b'g'), CursorState::Dead);
+/// ```
+#[derive(Debug, Clone)]
+pub enum TrieCursor<'b, 'a, T> {
+    /// Variant for cursors over trie-hard tries built on u8
+    U8(TrieCursorSized<'b, 'a, T, u8>),
+    /// Variant for cursors over trie-hard tries built on u16
+    U16(TrieCursorSized<'b, 'a, T, u16>),
+    /// Variant for cursors over trie-hard tries built on u32
+    U32(TrieCursorSized<'b, 'a, T, u32>),
+    /// Variant for cursors over trie-hard tries built on u64
+    U64(TrieCursorSized<'b, 'a, T, u64>),
+    /// Variant for cursors over trie-hard tries built on u128
+    U128(TrieCursorSized<'b, 'a, T, u128>),
+    /// Variant for cursors over trie-hard tries built on u256
+    U256(TrieCursorSized<'b, 'a, T, U256>),
+}
+
+/// Cursor for matching a key one byte at a time against a trie-hard trie
+/// built on a specific integer size
+#[derive(Debug, Clone)]
+pub struct TrieCursorSized<'b, 'a, T, I> {
+    /// Node reached by the bytes fed so far, or `None` once no key can match
+    node_index: Option<usize>,
+    /// Number of bytes fed since the last reset
+    depth: usize,
+    /// Number of bytes of the run at `node_index` that have been matched
+    offset: usize,
+    trie: &'b TrieHardSized<'a, T, I>,
+}
+
+impl<'b, 'a, T, I> TrieCursorSized<'b, 'a, T, I> {
+    fn new(trie: &'b TrieHardSized<'a, T, I>) -> Self {
+        Self {
+            node_index: (!trie.nodes.is_empty()).then_some(0),
+            depth: 0,
+            offset: 0,
+            trie,
+        }
+    }
+
+    /// Go back to the start so that a new key can be fed
+    pub fn reset(&mut self) {
+        *self = Self::new(self.trie);
+    }
+
+    /// Whether no key in the trie starts with the bytes fed so far. Once dead,
+    /// a cursor stays dead until it is reset.
+    pub fn is_dead(&self) -> bool {
+        self.node_index.is_none()
+    }
+
+    /// Key and value of the entry matching exactly the bytes fed so far
+    fn entry(&self) -> Option<(&'a [u8], &'b T)> {
+        match &self.trie.nodes[self.node_index?] {
+            TrieState::Leaf(k, v) | TrieState::SearchOrLeaf(k, v, _)
+                if k.len() == self.depth =>
+            {
+                Some((k, v))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'b, 'a, T> TrieCursor<'b, 'a, T>
+where
+    T: Copy,
+{
+    /// Feed the next byte of the key to the cursor
+    ///
+    /// ```
+    /// # use trie_hard::{CursorState, TrieHard};
+    /// let trie = ["content-length", "content-type"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let mut cursor = trie.cursor();
+    /// for chunk in [&b"content-"[..], b"ty", b"pe"] {
+    ///     for c in chunk {
+    ///         cursor.advance(*c);
+    ///     }
+    /// }
+    /// assert_eq!(cursor.value(), Some("content-type"));
+    /// ```
+    pub fn advance(&mut self, c: u8) -> CursorState<T> {
+        match self {
+            TrieCursor::U8(cursor) => cursor.advance(c),
+            TrieCursor::U16(cursor) => cursor.advance(c),
+            TrieCursor::U32(cursor) => cursor.advance(c),
+            TrieCursor::U64(cursor) => cursor.advance(c),
+            TrieCursor::U128(cursor) => cursor.advance(c),
+            TrieCursor::U256(cursor) => cursor.advance(c),
+        }
+    }
+
+    /// Value of the key matching exactly the bytes fed so far, if there is one
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["", "a"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let mut cursor = trie.cursor();
+    /// assert_eq!(cursor.value(), Some(""));
+    ///
+    /// cursor.advance(b'a');
+    /// assert_eq!(cursor.value(), Some("a"));
+    /// ```
+    pub fn value(&self) -> Option<T> {
+        match self {
+            TrieCursor::U8(cursor) => cursor.value(),
+            TrieCursor::U16(cursor) => cursor.value(),
+            TrieCursor::U32(cursor) => cursor.value(),
+            TrieCursor::U64(cursor) => cursor.value(),
+            TrieCursor::U128(cursor) => cursor.value(),
+            TrieCursor::U256(cursor) => cursor.value(),
+        }
+    }
+}
+
+impl<'b, 'a, T> TrieCursor<'b, 'a, T> {
+    /// Go back to the start so that a new key can be fed
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let mut cursor = trie.cursor();
+    /// cursor.advance(b'x');
+    /// assert!(cursor.is_dead());
+    ///
+    /// cursor.reset();
+    /// assert!(!cursor.is_dead());
+    /// ```
+    pub fn reset(&mut self) {
+        match self {
+            TrieCursor::U8(cursor) => cursor.reset(),
+            TrieCursor::U16(cursor) => cursor.reset(),
+            TrieCursor::U32(cursor) => cursor.reset(),
+            TrieCursor::U64(cursor) => cursor.reset(),
+            TrieCursor::U128(cursor) => cursor.reset(),
+            TrieCursor::U256(cursor) => cursor.reset(),
+        }
+    }
+
+    /// Whether no key in the trie starts with the bytes fed so far. Once dead,
+    /// a cursor stays dead until it is reset.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let mut cursor = trie.cursor();
+    /// cursor.advance(b'a');
+    /// assert!(!cursor.is_dead());
+    ///
+    /// cursor.advance(b'd');
+    /// assert!(cursor.is_dead());
+    /// ```
+    pub fn is_dead(&self) -> bool {
+        match self {
+            TrieCursor::U8(cursor) => cursor.is_dead(),
+            TrieCursor::U16(cursor) => cursor.is_dead(),
+            TrieCursor::U32(cursor) => cursor.is_dead(),
+            TrieCursor::U64(cursor) => cursor.is_dead(),
+            TrieCursor::U128(cursor) => cursor.is_dead(),
+            TrieCursor::U256(cursor) => cursor.is_dead(),
+        }
+    }
+}
+
+impl<'a, T> TrieHard<'a, T> {
+    /// Create a cursor for matching a key that is fed one byte at a time. See
+    /// [`TrieCursor`].
+    ///
+    /// ```
+    /// # use trie_hard::{CursorState, TrieHard};
+    /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let mut cursor = trie.cursor();
+    /// let states = b"ant".map(|c| cursor.advance(c));
+    ///
+    /// assert_eq!(
+    ///     states,
+    ///     [
+    ///         CursorState::InProgress,
+    ///         CursorState::InProgress,
+    ///         CursorState::Matched("ant")
+    ///     ]
+    /// );
+    /// ```
+    pub fn cursor(&self) -> TrieCursor<'_, 'a, T> {
+        match self {
+            TrieHard::U8(trie) => TrieCursor::U8(trie.cursor()),
+            TrieHard::U16(trie) => TrieCursor::U16(trie.cursor()),
+            TrieHard::U32(trie) => TrieCursor::U32(trie.cursor()),
+            TrieHard::U64(trie) => TrieCursor::U64(trie.cursor()),
+            TrieHard::U128(trie) => TrieCursor::U128(trie.cursor()),
+            TrieHard::U256(trie) => TrieCursor::U256(trie.cursor()),
+        }
+    }
+}
+
+impl<'a, T, I> TrieHardSized<'a, T, I> {
+    /// Create a cursor for matching a key that is fed one byte at a time
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// let TrieHard::U8(sized_trie) = trie else {
+    ///     unreachable!()
+    /// };
+    ///
+    /// let mut cursor = sized_trie.cursor();
+    /// b"and".map(|c| cursor.advance(c));
+    /// assert_eq!(cursor.value(), Some("and"));
+    /// ```
+    pub fn cursor(&self) -> TrieCursorSized<'_, 'a, T, I> {
+        TrieCursorSized::new(self)
+    }
+}
+
+macro_rules! cursor_impls {
+    ($($int_type:ty),+) => {
+        $(
+            cursor_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'b, 'a, T> TrieCursorSized<'b, 'a, T, $int_type>
+        where
+            T: Copy
+        {
+            /// Feed the next byte of the key to the cursor
+            pub fn advance(&mut self, c: u8) -> CursorState<T> {
+                let Some(node_index) = self.node_index else {
+                    return CursorState::Dead;
+                };
+
+                let normalization = &self.trie.normalization;
+                let next = match &self.trie.nodes[node_index] {
+                    // Below a leaf the rest of its key is compared directly
+                    TrieState::Leaf(k, _) => k
+                        .get(self.depth)
+                        .filter(|k| normalization.byte(**k) == normalization.byte(c))
+                        .map(|_| node_index),
+                    TrieState::Run(run, next) => {
+                        if normalization.byte(run[self.offset]) != normalization.byte(c) {
+                            None
+                        } else if self.offset + 1 < run.len() {
+                            self.offset += 1;
+                            Some(node_index)
+                        } else {
+                            self.offset = 0;
+                            Some(*next)
+                        }
+                    }
+                    TrieState::Search(search)
+                    | TrieState::SearchOrLeaf(_, _, search) => {
+                        search.evaluate(c, &self.trie.masks)
+                    }
+                };
+
+                self.node_index = next;
+                self.depth += 1;
+
+                match next {
+                    None => CursorState::Dead,
+                    Some(_) => match self.entry() {
+                        Some((_, v)) => CursorState::Matched(*v),
+                        None => CursorState::InProgress,
+                    },
+                }
+            }
+
+            /// Value of the key matching exactly the bytes fed so far, if
+            /// there is one
+            pub fn value(&self) -> Option<T> {
+                self.entry().map(|(_, v)| *v)
+            }
+        }
+    };
+}
+
+cursor_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::TrieHardBuilder;
+
+    #[rstest]
+    #[case(&["and", "ant", "dad", "do", "dot"], "do")]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dot")]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dots")]
+    #[case(&["and", "ant", "dad", "do", "dot"], "an")]
+    #[case(&["and", "ant", "dad", "do", "dot"], "x")]
+    #[case(&["abcdef"], "abcdef")]
+    #[case(&["abcdef"], "abcdeg")]
+    #[case(&["abcdef"], "abcdefg")]
+    #[case(&["", "a"], "")]
+    #[case(&[], "")]
+    #[case(&[], "a")]
+    fn test_matches_get(#[case] input: &[&str], #[case] key: &str) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let mut cursor = trie.cursor();
+
+        for (i, c) in key.bytes().enumerate() {
+            let fed = &key[..=i];
+            let expected = match trie.get(fed) {
+                Some(v) => CursorState::Matched(v),
+                None if trie.prefix_search(fed).next().is_some() => {
+                    CursorState::InProgress
+                }
+                None => CursorState::Dead,
+            };
+            assert_eq!(cursor.advance(c), expected);
+            assert_eq!(cursor.is_dead(), expected == CursorState::Dead);
+        }
+        assert_eq!(cursor.value(), trie.get(key));
+    }
+
+    #[test]
+    fn test_full_text() {
+        let text = include_str!("../data/1984.txt");
+        let trie = text.split_whitespace().collect::<TrieHard<'_, _>>();
+        let mut cursor = trie.cursor();
+
+        for word in text.split_whitespace() {
+            cursor.reset();
+            for c in word.bytes() {
+                assert!(!matches!(cursor.advance(c), CursorState::Dead));
+            }
+            assert_eq!(cursor.value(), Some(word));
+        }
+    }
+
+    #[test]
+    fn test_normalized() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"Host"[..], 0)]);
+        let mut cursor = trie.cursor();
+
+        assert_eq!(
+            b"hO".map(|c| cursor.advance(c)),
+            [CursorState::InProgress; 2]
+        );
+        assert_eq!(cursor.advance(b'S'), CursorState::InProgress);
+        assert_eq!(cursor.advance(b't'), CursorState::Matched(0));
+        assert_eq!(cursor.advance(b's'), CursorState::Dead);
+        assert_eq!(cursor.advance(b's'), CursorState::Dead);
+        assert_eq!(cursor.value(), None);
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::VecDeque, fmt::Debug, fmt::Write};
+
+use crate::{u256::U256, TrieHard, TrieHardSized, TrieState};
+
+/// Quote text for use as a string in the DOT language. Line breaks become
+/// `\n` so Graphviz centres each line of the label.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Text for a key or a run of bytes, with bytes that are not printable ASCII
+/// escaped the same way as in a Rust byte string literal
+fn bytes_label(bytes: &[u8]) -> String {
+    bytes.escape_ascii().to_string()
+}
+
+impl<'a, T> TrieHard<'a, T>
+where
+    T: Debug,
+{
+    /// Render the structure of the trie in the DOT language of
+    /// [Graphviz](https://graphviz.org/). Each edge is labelled with the bytes
+    /// that lead along it, so a missing key can be traced by hand. Search
+    /// nodes are drawn as circles labelled with their index in the node array,
+    /// search nodes that also hold an entry as double circles, and leaves as
+    /// boxes holding their key and value. A run of bytes shared by every key
+    /// below it is drawn as a single edge. Nodes that are not reachable from
+    /// the root are left out.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let dot = trie.to_dot();
+    /// assert!(dot.starts_with("digraph trie {"));
+    /// assert!(dot.contains(r#"4 [shape=box, label="dad = \"dad\""];"#));
+    /// ```
+    ///
+    /// Write the output to a file and pass it to `dot -Tsvg` to draw it.
+    pub fn to_dot(&self) -> String {
+        match self {
+            TrieHard::U8(trie) => trie.to_dot(),
+            TrieHard::U16(trie) => trie.to_dot(),
+            TrieHard::U32(trie) => trie.to_dot(),
+            TrieHard::U64(trie) => trie.to_dot(),
+            TrieHard::U128(trie) => trie.to_dot(),
+            TrieHard::U256(trie) => trie.to_dot(),
+        }
+    }
+}
+
+macro_rules! dot_impls {
+    ($($int_type:ty),+) => {
+        $(
+            dot_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type>
+        where
+            T: Debug
+        {
+            /// Render the structure of the trie in the DOT language of
+            /// Graphviz. See [`TrieHard::to_dot`].
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.to_dot().contains(r#"0 -> 1 [label="an"];"#));
+            /// ```
+            pub fn to_dot(&self) -> String {
+                // Invert the mask table to find the bytes that lead to each
+                // child of a search node. Bytes that are the same after
+                // normalization share a mask and so share an edge.
+                let mut edges = Vec::<($int_type, String)>::new();
+                for (byte, mask) in self.masks.by_byte.iter().enumerate() {
+                    if *mask > 0 {
+                        let bit = mask.trailing_zeros() as usize;
+                        if edges.len() <= bit {
+                            edges.resize(bit + 1, (<$int_type>::default(), String::new()));
+                        }
+                        let (edge_mask, label) = &mut edges[bit];
+                        *edge_mask = *mask;
+                        if !label.is_empty() {
+                            label.push(' ');
+                        }
+                        label.push_str(&bytes_label(&[byte as u8]));
+                    }
+                }
+
+                let mut out = String::new();
+                // Writing to a `String` cannot fail
+                let _ = writeln!(out, "digraph trie {{");
+                let _ = writeln!(out, "    node [shape=circle];");
+
+                let mut queue = VecDeque::new();
+                if !self.nodes.is_empty() {
+                    queue.push_back(0);
+                }
+
+                while let Some(node_index) = queue.pop_front() {
+                    let _ = match &self.nodes[node_index] {
+                        TrieState::Leaf(key, value) => writeln!(
+                            out,
+                            "    {node_index} [shape=box, label={}];",
+                            quote(&format!("{} = {value:?}", bytes_label(key)))
+                        ),
+                        TrieState::Search(_) | TrieState::Run(..) => writeln!(
+                            out,
+                            "    {node_index} [label=\"{node_index}\"];"
+                        ),
+                        TrieState::SearchOrLeaf(key, value, _) => writeln!(
+                            out,
+                            "    {node_index} [shape=doublecircle, label={}];",
+                            quote(&format!(
+                                "{node_index}\n{} = {value:?}",
+                                bytes_label(key)
+                            ))
+                        ),
+                    };
+
+                    match &self.nodes[node_index] {
+                        TrieState::Leaf(..) => (),
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => {
+                            let mut child = search.edge_start;
+                            for (mask, label) in &edges {
+                                if search.mask & *mask > 0 {
+                                    let _ = writeln!(
+                                        out,
+                                        "    {node_index} -> {child} [label={}];",
+                                        quote(label)
+                                    );
+                                    queue.push_back(child);
+                                    child += 1;
+                                }
+                            }
+                        }
+                        TrieState::Run(run, next) => {
+                            let _ = writeln!(
+                                out,
+                                "    {node_index} -> {next} [label={}];",
+                                quote(&bytes_label(run))
+                            );
+                            queue.push_back(*next);
+                        }
+                    }
+                }
+
+                let _ = writeln!(out, "}}");
+                out
+            }
+        }
+    };
+}
+
+dot_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::TrieHardBuilder;
+
+    #[test]
+    fn test_to_dot() {
+        let trie =
+            TrieHard::new(vec![(&b"a"[..], 0), (b"ab", 1), (b"b\"\xff", 2)]);
+
+        assert_eq!(
+            trie.to_dot(),
+            r#"digraph trie {
+    node [shape=circle];
+    0 [label="0"];
+    0 -> 1 [label="a"];
+    0 -> 2 [label="b"];
+    1 [shape=doublecircle, label="1\na = 0"];
+    1 -> 3 [label="b"];
+    2 [shape=box, label="b\\\"\\xff = 2"];
+    3 [shape=box, label="ab = 1"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_dot_runs() {
+        let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(
+            trie.to_dot(),
+            r#"digraph trie {
+    node [shape=circle];
+    0 [label="0"];
+    0 -> 1 [label="an"];
+    1 [label="1"];
+    1 -> 2 [label="d"];
+    1 -> 3 [label="t"];
+    2 [shape=box, label="and = \"and\""];
+    3 [shape=box, label="ant = \"ant\""];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_dot_shared_edges() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(b"A", 0), (b"b", 1)]);
+
+        assert_eq!(
+            trie.to_dot(),
+            r#"digraph trie {
+    node [shape=circle];
+    0 [label="0"];
+    0 -> 1 [label="A a"];
+    0 -> 2 [label="B b"];
+    1 [shape=box, label="A = 0"];
+    2 [shape=box, label="b = 1"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn test_to_dot_after_remove() {
+        let mut trie = ["and", "ant", "dad"]
+            .into_iter()
+            .collect::<TrieHard<'_, _>>();
+        trie.remove("dad");
+
+        let dot = trie.to_dot();
+        assert!(dot.contains("and"));
+        assert!(!dot.contains("dad"));
+    }
+
+    #[test]
+    fn test_to_dot_empty() {
+        let trie = TrieHard::<'_, usize>::default();
+
+        assert_eq!(
+            trie.to_dot(),
+            "digraph trie {\n    node [shape=circle];\n}\n"
+        );
+    }
+
+    #[rstest]
+    #[case("", r#""""#)]
+    #[case(r#"a"b"#, r#""a\"b""#)]
+    #[case(r"a\b", r#""a\\b""#)]
+    #[case("a\nb", r#""a\nb""#)]
+    fn test_quote(#[case] text: &str, #[case] expected: &str) {
+        assert_eq!(quote(text), expected);
+    }
+}
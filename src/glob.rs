@@ -0,0 +1,461 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use crate::{u256::U256, Normalization, TrieHard, TrieHardSized, TrieState};
+
+/// Error returned when a glob pattern cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GlobError {
+    /// The `[` at the given byte offset has no closing `]`
+    UnclosedClass(usize),
+    /// The range in the class at the given byte offset ends before it starts
+    InvalidRange(usize),
+    /// The pattern ends with an unescaped `\`
+    TrailingEscape,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlobError::UnclosedClass(offset) => {
+                write!(f, "unclosed character class at offset {offset}")
+            }
+            GlobError::InvalidRange(offset) => {
+                write!(f, "invalid range in character class at offset {offset}")
+            }
+            GlobError::TrailingEscape => {
+                write!(f, "pattern ends with an escape character")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GlobError {}
+
+/// One element of a parsed glob pattern
+#[derive(Debug, Clone)]
+enum Token {
+    /// Matches this byte, or any byte that normalizes to the same value
+    Literal(u8),
+    /// `?`, matches any single byte
+    Any,
+    /// `[...]`, matches any single byte in the set
+    Class(Box<[bool; 256]>),
+    /// `*`, matches any sequence of bytes, including none
+    Star,
+}
+
+/// A parsed glob pattern together with the normalization of the trie it is
+/// matched against
+#[derive(Debug)]
+struct Glob<'n> {
+    tokens: Vec<Token>,
+    normalization: &'n Normalization,
+}
+
+impl<'n> Glob<'n> {
+    fn new(
+        pattern: &[u8],
+        normalization: &'n Normalization,
+    ) -> Result<Self, GlobError> {
+        let mut tokens = Vec::new();
+        let mut i = 0;
+
+        while i < pattern.len() {
+            let token = match pattern[i] {
+                b'*' => Token::Star,
+                b'?' => Token::Any,
+                b'\\' => {
+                    i += 1;
+                    let c = pattern.get(i).ok_or(GlobError::TrailingEscape)?;
+                    Token::Literal(*c)
+                }
+                b'[' => {
+                    let (class, end) = Self::class(pattern, i, normalization)?;
+                    i = end;
+                    Token::Class(class)
+                }
+                c => Token::Literal(c),
+            };
+
+            // Runs of stars match the same as a single one
+            if !matches!(
+                (&token, tokens.last()),
+                (Token::Star, Some(Token::Star))
+            ) {
+                tokens.push(token);
+            }
+            i += 1;
+        }
+
+        Ok(Self {
+            tokens,
+            normalization,
+        })
+    }
+
+    /// Parse the class starting at `start`, returning it along with the
+    /// offset of its closing `]`
+    fn class(
+        pattern: &[u8],
+        start: usize,
+        normalization: &Normalization,
+    ) -> Result<(Box<[bool; 256]>, usize), GlobError> {
+        let mut i = start + 1;
+        let negated = matches!(pattern.get(i), Some(b'!' | b'^'));
+        if negated {
+            i += 1;
+        }
+
+        // Bytes in the class, by their normalized value
+        let mut normalized = [false; 256];
+        let mut first = true;
+        loop {
+            let mut c =
+                *pattern.get(i).ok_or(GlobError::UnclosedClass(start))?;
+            // A `]` right after the opening bracket is part of the class
+            if c == b']' && !first {
+                break;
+            }
+            if c == b'\\' {
+                i += 1;
+                c = *pattern.get(i).ok_or(GlobError::UnclosedClass(start))?;
+            }
+            first = false;
+
+            let mut last = c;
+            if pattern.get(i + 1) == Some(&b'-')
+                && pattern.get(i + 2).is_some_and(|end| *end != b']')
+            {
+                last = pattern[i + 2];
+                i += 2;
+                if last < c {
+                    return Err(GlobError::InvalidRange(start));
+                }
+            }
+            for b in c..=last {
+                normalized[normalization.byte(b) as usize] = true;
+            }
+            i += 1;
+        }
+
+        let mut class = Box::new([false; 256]);
+        for (b, in_class) in class.iter_mut().enumerate() {
+            *in_class =
+                normalized[normalization.byte(b as u8) as usize] != negated;
+        }
+        Ok((class, i))
+    }
+
+    /// Add the positions reachable from `pos` without consuming a byte
+    fn insert(&self, positions: &mut Vec<usize>, mut pos: usize) {
+        loop {
+            if !positions.contains(&pos) {
+                positions.push(pos);
+            }
+            match self.tokens.get(pos) {
+                Some(Token::Star) => pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn start(&self) -> Vec<usize> {
+        let mut positions = Vec::new();
+        self.insert(&mut positions, 0);
+        positions
+    }
+
+    /// Positions in the pattern reached by matching `c` from `positions`
+    fn step(&self, positions: &[usize], c: u8) -> Vec<usize> {
+        let mut next = Vec::new();
+        for pos in positions {
+            let matched = match self.tokens.get(*pos) {
+                None => false,
+                Some(Token::Star) => {
+                    self.insert(&mut next, *pos);
+                    continue;
+                }
+                Some(Token::Any) => true,
+                Some(Token::Literal(l)) => {
+                    self.normalization.byte(*l) == self.normalization.byte(c)
+                }
+                Some(Token::Class(class)) => class[c as usize],
+            };
+            if matched {
+                self.insert(&mut next, pos + 1);
+            }
+        }
+        next
+    }
+
+    fn accepts(&self, positions: &[usize]) -> bool {
+        positions.contains(&self.tokens.len())
+    }
+
+    /// The bytes that can be matched next, if they are all literals
+    fn literals(&self, positions: &[usize]) -> Option<Vec<u8>> {
+        let mut literals = Vec::new();
+        for pos in positions {
+            match self.tokens.get(*pos) {
+                None => {}
+                Some(Token::Literal(l)) => literals.push(*l),
+                Some(_) => return None,
+            }
+        }
+        Some(literals)
+    }
+}
+
+impl<'a, T> TrieHard<'a, T>
+where
+    T: Copy,
+{
+    /// Find every key matching a glob pattern, in key order. `?` matches any
+    /// single byte, `*` matches any run of bytes and `[...]` matches one byte
+    /// from a set such as `[abc]`, a range such as `[a-c]`, or the bytes not
+    /// in a set such as `[!abc]`. Any byte can be escaped with `\`.
+    ///
+    /// Only the branches of the trie that can still match are visited, and
+    /// at wildcard positions only the edges that exist in each node are
+    /// followed.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["x-request-id", "x-trace-id", "x-forwarded-for", "user"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let found = trie.glob_search("x-*-id").unwrap();
+    /// assert_eq!(
+    ///     found.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["x-request-id", "x-trace-id"]
+    /// );
+    ///
+    /// assert_eq!(trie.glob_search("[t-z]se?").unwrap().len(), 1);
+    /// assert!(trie.glob_search("[a-").is_err());
+    /// ```
+    pub fn glob_search<P: AsRef<[u8]>>(
+        &self,
+        pattern: P,
+    ) -> Result<Vec<(&'a [u8], T)>, GlobError> {
+        match self {
+            TrieHard::U8(trie) => trie.glob_search(pattern),
+            TrieHard::U16(trie) => trie.glob_search(pattern),
+            TrieHard::U32(trie) => trie.glob_search(pattern),
+            TrieHard::U64(trie) => trie.glob_search(pattern),
+            TrieHard::U128(trie) => trie.glob_search(pattern),
+            TrieHard::U256(trie) => trie.glob_search(pattern),
+        }
+    }
+}
+
+macro_rules! glob_impls {
+    ($($int_type:ty),+) => {
+        $(
+            glob_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type>
+        where
+            T: Copy
+        {
+            /// Find every key matching a glob pattern, in key order
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.glob_search("?o*").unwrap(),
+            ///     [(&b"do"[..], "do"), (b"dot", "dot")]
+            /// );
+            /// ```
+            pub fn glob_search<P: AsRef<[u8]>>(
+                &self,
+                pattern: P,
+            ) -> Result<Vec<(&'a [u8], T)>, GlobError> {
+                let glob = Glob::new(pattern.as_ref(), &self.normalization)?;
+                let mut found = Vec::new();
+                if !self.nodes.is_empty() {
+                    let edge_bytes = self.masks.edge_bytes();
+                    self.glob_node(0, 0, glob.start(), &glob, &edge_bytes, &mut found);
+                }
+                Ok(found)
+            }
+
+            fn glob_node(
+                &self,
+                node_index: usize,
+                depth: usize,
+                mut positions: Vec<usize>,
+                glob: &Glob<'_>,
+                edge_bytes: &[u8],
+                found: &mut Vec<(&'a [u8], T)>,
+            ) {
+                let search = match &self.nodes[node_index] {
+                    TrieState::Leaf(k, v) => {
+                        // The rest of the key is held by the leaf itself
+                        for c in &k[depth..] {
+                            positions = glob.step(&positions, *c);
+                            if positions.is_empty() {
+                                return;
+                            }
+                        }
+                        if glob.accepts(&positions) {
+                            found.push((k, *v));
+                        }
+                        return;
+                    }
+                    TrieState::Run(run, next) => {
+                        for c in *run {
+                            positions = glob.step(&positions, *c);
+                            if positions.is_empty() {
+                                return;
+                            }
+                        }
+                        self.glob_node(*next, depth + run.len(), positions, glob, edge_bytes, found);
+                        return;
+                    }
+                    TrieState::Search(search) => search,
+                    TrieState::SearchOrLeaf(k, v, search) => {
+                        if glob.accepts(&positions) {
+                            found.push((k, *v));
+                        }
+                        search
+                    }
+                };
+
+                let mut visit = |c: u8, child: usize| {
+                    let next = glob.step(&positions, c);
+                    if !next.is_empty() {
+                        self.glob_node(child, depth + 1, next, glob, edge_bytes, found);
+                    }
+                };
+
+                if let Some(mut literals) = glob.literals(&positions) {
+                    // Only the edges for the literal bytes can match, visited
+                    // in edge order
+                    literals.sort_unstable_by_key(|c| self.masks.by_byte[*c as usize].trailing_zeros());
+                    literals.dedup_by_key(|c| self.masks.by_byte[*c as usize]);
+                    for c in literals {
+                        if let Some(child) = search.evaluate(c, &self.masks) {
+                            visit(c, child);
+                        }
+                    }
+                } else {
+                    // Walk the set bits of the node mask, one per child
+                    let mut bits = search.mask;
+                    let mut child = search.edge_start;
+                    while bits != <$int_type>::default() {
+                        let c = edge_bytes[bits.trailing_zeros() as usize];
+                        visit(c, child);
+                        bits = bits & (bits - 1);
+                        child += 1;
+                    }
+                }
+            }
+        }
+    };
+}
+
+glob_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::TrieHardBuilder;
+
+    const WORDS: &[&str] = &[
+        "",
+        "a",
+        "and",
+        "ant",
+        "dad",
+        "do",
+        "dot",
+        "x-*",
+        "x-request-id",
+        "x-trace-id",
+        "user",
+        "user1",
+        "user12",
+        "users",
+    ];
+
+    #[rstest]
+    #[case("*", WORDS)]
+    #[case("", &[""])]
+    #[case("a*", &["a", "and", "ant"])]
+    #[case("an?", &["and", "ant"])]
+    #[case("?", &["a"])]
+    #[case("d*t", &["dot"])]
+    #[case("*d", &["and", "dad", "x-request-id", "x-trace-id"])]
+    #[case("x-*-id", &["x-request-id", "x-trace-id"])]
+    #[case("x-\\*", &["x-*"])]
+    #[case("user?", &["user1", "users"])]
+    #[case("user[0-9]*", &["user1", "user12"])]
+    #[case("user[!0-9]", &["users"])]
+    #[case("[a-c]*", &["a", "and", "ant"])]
+    #[case("[]a]", &["a"])]
+    #[case("*-*-*", &["x-request-id", "x-trace-id"])]
+    #[case("**o**", &["do", "dot"])]
+    #[case("z*", &[])]
+    fn test_glob_search(#[case] pattern: &str, #[case] expected: &[&str]) {
+        let trie = WORDS.iter().copied().collect::<TrieHard<'_, _>>();
+        let mut expected = expected.to_vec();
+        expected.sort_unstable();
+
+        let found = trie.glob_search(pattern).unwrap();
+        assert_eq!(
+            found.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case("[abc", GlobError::UnclosedClass(0))]
+    #[case("a[", GlobError::UnclosedClass(1))]
+    #[case("[z-a]", GlobError::InvalidRange(0))]
+    #[case("a\\", GlobError::TrailingEscape)]
+    fn test_glob_error(#[case] pattern: &str, #[case] expected: GlobError) {
+        let trie = WORDS.iter().copied().collect::<TrieHard<'_, _>>();
+        assert_eq!(trie.glob_search(pattern).unwrap_err(), expected);
+    }
+
+    #[test]
+    fn test_glob_search_normalized() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"X-Trace-Id"[..], 0), (b"x-span-id", 1)]);
+
+        let found = trie.glob_search("x-[R-t]*-ID").unwrap();
+        assert_eq!(found, [(&b"x-span-id"[..], 1), (b"X-Trace-Id", 0)]);
+        assert!(trie.glob_search("x-[!s]*").unwrap().len() == 1);
+    }
+}
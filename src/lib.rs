@@ -22,25 +22,82 @@
 )]
 #![warn(rust_2018_idioms)]
 
+#[doc(hidden)]
+pub mod __private;
+mod aho_corasick;
+mod automaton;
+pub mod codegen;
+mod cursor;
+mod dot;
+mod glob;
+mod minimized;
+mod owned;
+mod render;
+mod serialize;
+mod stats;
 mod u256;
+mod view;
+mod walk;
 
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
     collections::{BTreeMap, BTreeSet, VecDeque},
-    ops::RangeFrom,
+    fmt::{self, Debug},
+    ops::{Bound, Deref, DerefMut, Range, RangeBounds, RangeFrom},
 };
 
 use u256::U256;
+use walk::{walk, Target, TrieNodes, WalkNode};
 
+pub use aho_corasick::{
+    AhoCorasick, AhoCorasickSized, FindIter, FindIterSized, MatchKind,
+};
+pub use automaton::{Automaton, AutomatonIter, AutomatonIterSized};
+pub use cursor::{CursorState, TrieCursor, TrieCursorSized};
+pub use glob::GlobError;
+pub use minimized::{
+    MinimizeReport, MinimizedIter, TrieHardMinimized, TrieHardMinimizedSized,
+};
+pub use owned::{
+    TrieHardOwned, TrieHardOwnedSized, TrieOwnedIter, TrieOwnedIterSized,
+};
+pub use serialize::{DecodeError, DecodeValue, EncodeValue};
+pub use stats::{MemoryUsage, NodeUsage, TrieStats};
+pub use view::{
+    TrieHardView, TrieHardViewSized, TrieViewIter, TrieViewIterSized,
+};
+
+#[doc(hidden)]
 #[derive(Debug, Clone)]
-#[repr(transparent)]
-struct MasksByByteSized<I>([I; 256]);
+pub struct MasksByByteSized<I> {
+    /// Mask of each byte, zero for bytes that do not appear in any key
+    by_byte: [I; 256],
+    /// One byte for each mask bit in use, in bit order, which is also the
+    /// order of the edges out of a search node. Bytes that share a mask are
+    /// represented by the lowest of them.
+    edge_bytes: [u8; 256],
+    /// Number of mask bits in use
+    edge_count: usize,
+}
 
 impl<I> Default for MasksByByteSized<I>
 where
     I: Default + Copy,
 {
     fn default() -> Self {
-        Self([I::default(); 256])
+        Self {
+            by_byte: [I::default(); 256],
+            edge_bytes: [0; 256],
+            edge_count: 0,
+        }
+    }
+}
+
+impl<I> MasksByByteSized<I> {
+    /// One byte for each mask bit in use, in bit order
+    fn edge_bytes(&self) -> &[u8] {
+        &self.edge_bytes[..self.edge_count]
     }
 }
 
@@ -55,35 +112,99 @@ enum MasksByByte {
 }
 
 impl MasksByByte {
-    fn new(used_bytes: BTreeSet<u8>) -> Self {
+    fn new(used_bytes: BTreeSet<u8>, normalization: &Normalization) -> Self {
         match used_bytes.len() {
-            ..=8 => MasksByByte::U8(MasksByByteSized::<u8>::new(used_bytes)),
-            9..=16 => {
-                MasksByByte::U16(MasksByByteSized::<u16>::new(used_bytes))
-            }
-            17..=32 => {
-                MasksByByte::U32(MasksByByteSized::<u32>::new(used_bytes))
-            }
-            33..=64 => {
-                MasksByByte::U64(MasksByByteSized::<u64>::new(used_bytes))
-            }
-            65..=128 => {
-                MasksByByte::U128(MasksByByteSized::<u128>::new(used_bytes))
-            }
-            129..=256 => {
-                MasksByByte::U256(MasksByByteSized::<U256>::new(used_bytes))
-            }
+            ..=8 => MasksByByte::U8(MasksByByteSized::<u8>::new(
+                used_bytes,
+                normalization,
+            )),
+            9..=16 => MasksByByte::U16(MasksByByteSized::<u16>::new(
+                used_bytes,
+                normalization,
+            )),
+            17..=32 => MasksByByte::U32(MasksByByteSized::<u32>::new(
+                used_bytes,
+                normalization,
+            )),
+            33..=64 => MasksByByte::U64(MasksByByteSized::<u64>::new(
+                used_bytes,
+                normalization,
+            )),
+            65..=128 => MasksByByte::U128(MasksByByteSized::<u128>::new(
+                used_bytes,
+                normalization,
+            )),
+            129..=256 => MasksByByte::U256(MasksByByteSized::<U256>::new(
+                used_bytes,
+                normalization,
+            )),
             _ => unreachable!("There are only 256 possible u8s"),
         }
     }
 }
 
+/// How key bytes are compared. The masks of bytes that compare equal are
+/// folded together when the trie is built, so lookups cost the same whatever
+/// the normalization; only the tails of leaf keys need to be compared with it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+enum Normalization {
+    #[default]
+    None,
+    AsciiCase,
+    /// Every byte is replaced by its entry in the table
+    Table(Box<[u8; 256]>),
+}
+
+impl Normalization {
+    fn byte(&self, c: u8) -> u8 {
+        match self {
+            Normalization::None => c,
+            Normalization::AsciiCase => c.to_ascii_lowercase(),
+            Normalization::Table(table) => table[c as usize],
+        }
+    }
+
+    fn eq(&self, left: &[u8], right: &[u8]) -> bool {
+        match self {
+            Normalization::None => left == right,
+            Normalization::AsciiCase => left.eq_ignore_ascii_case(right),
+            Normalization::Table(table) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right)
+                        .all(|(l, r)| table[*l as usize] == table[*r as usize])
+            }
+        }
+    }
+
+    fn starts_with(&self, haystack: &[u8], prefix: &[u8]) -> bool {
+        haystack.len() >= prefix.len()
+            && self.eq(&haystack[..prefix.len()], prefix)
+    }
+
+    /// Order of two keys in the trie, which is the order of their normalized
+    /// bytes
+    fn cmp(&self, left: &[u8], right: &[u8]) -> Ordering {
+        let left = left.iter().map(|c| self.byte(*c));
+        left.cmp(right.iter().map(|c| self.byte(*c)))
+    }
+}
+
 /// Inner representation of a trie-hard trie that is generic to a specific size
 /// of integer.
 #[derive(Debug, Clone)]
 pub struct TrieHardSized<'a, T, I> {
     masks: MasksByByteSized<I>,
-    nodes: Vec<TrieState<'a, T, I>>,
+    nodes: NodeStorage<'a, T, I>,
+    /// Number of slots in `nodes` that are no longer reachable from the root
+    /// because of incremental updates
+    dead: usize,
+    normalization: Normalization,
+    /// Number of entries in the subtree of each node, its own included.
+    /// Counted when the trie is built and kept up to date along the path of
+    /// every key that is added or removed.
+    below: Cow<'a, [usize]>,
 }
 
 impl<'a, T, I> Default for TrieHardSized<'a, T, I>
@@ -94,8 +215,72 @@ where
         Self {
             masks: MasksByByteSized::default(),
             nodes: Default::default(),
+            dead: 0,
+            normalization: Normalization::None,
+            below: Cow::Borrowed(&[]),
+        }
+    }
+}
+
+impl<'a, T, I> TrieHardSized<'a, T, I> {
+    /// Consume the trie, returning all of its keys and values in no particular
+    /// order
+    fn into_entries(self) -> Vec<(&'a [u8], T)> {
+        self.nodes
+            .into_vec()
+            .into_iter()
+            .filter_map(|state| match state {
+                TrieState::Leaf(key, value)
+                | TrieState::SearchOrLeaf(key, value, _) => Some((key, value)),
+                TrieState::Search(_) | TrieState::Run(..) => None,
+            })
+            .collect()
+    }
+
+    /// All of the keys along with references to their values, in no
+    /// particular order
+    pub(crate) fn entry_refs(&self) -> Vec<(&'a [u8], &T)> {
+        self.nodes
+            .iter()
+            .filter_map(|state| match state {
+                TrieState::Leaf(key, value)
+                | TrieState::SearchOrLeaf(key, value, _) => Some((*key, value)),
+                TrieState::Search(_) | TrieState::Run(..) => None,
+            })
+            .collect()
+    }
+
+    /// Key and value of the entry held by the node at `index`
+    fn entry(&self, index: usize) -> Option<(&'a [u8], T)>
+    where
+        T: Copy,
+    {
+        match self.nodes.get(index)? {
+            TrieState::Leaf(key, value)
+            | TrieState::SearchOrLeaf(key, value, _) => Some((key, *value)),
+            TrieState::Search(_) | TrieState::Run(..) => None,
         }
     }
+
+    /// Depth reached by following a run that starts at `depth`, if the key
+    /// continues with the bytes of the run
+    fn follow_run(
+        &self,
+        run: &[u8],
+        key: &[u8],
+        depth: usize,
+    ) -> Option<usize> {
+        self.normalization
+            .starts_with(&key[depth..], run)
+            .then_some(depth + run.len())
+    }
+
+    /// Order of the keys below a run that starts at `depth` relative to the
+    /// key, which is `Equal` when the key continues with the bytes of the run
+    fn cmp_run(&self, run: &[u8], key: &[u8], depth: usize) -> Ordering {
+        let end = key.len().min(depth + run.len());
+        self.normalization.cmp(run, &key[depth..end])
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord)]
@@ -104,17 +289,146 @@ struct StateSpec<'a> {
     index: usize,
 }
 
+/// Node array of a trie. Tries generated at compile time borrow their nodes
+/// from static memory until they are first modified, all others own them.
+enum NodeStorage<'a, T, I> {
+    Owned(Vec<TrieState<'a, T, I>>),
+    /// Nodes in static memory, along with the function that copies them into
+    /// owned storage. Keeping the function here means the nodes can only be
+    /// borrowed when they can also be cloned.
+    Static(&'a [TrieState<'a, T, I>], CopyNodes<'a, T, I>),
+}
+
+type CopyNodes<'a, T, I> =
+    fn(&'a [TrieState<'a, T, I>]) -> Vec<TrieState<'a, T, I>>;
+
+/// Only the nodes are shown, so a trie generated at compile time looks the
+/// same as one built at runtime
+impl<'a, T, I> Debug for NodeStorage<'a, T, I>
+where
+    T: Debug,
+    I: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl<'a, T, I> Default for NodeStorage<'a, T, I> {
+    fn default() -> Self {
+        NodeStorage::Owned(Vec::new())
+    }
+}
+
+impl<'a, T, I> Clone for NodeStorage<'a, T, I>
+where
+    T: Clone,
+    I: Clone,
+{
+    /// Cloning always produces owned nodes
+    fn clone(&self) -> Self {
+        NodeStorage::Owned(self.to_vec())
+    }
+}
+
+impl<'a, T, I> From<Vec<TrieState<'a, T, I>>> for NodeStorage<'a, T, I> {
+    fn from(nodes: Vec<TrieState<'a, T, I>>) -> Self {
+        NodeStorage::Owned(nodes)
+    }
+}
+
+impl<'a, T, I> NodeStorage<'a, T, I> {
+    /// Access the owned nodes for modification. Static nodes are copied into
+    /// owned storage first, so a copy of a trie generated at compile time can
+    /// be modified while the original stays as it is.
+    fn to_mut(&mut self) -> &mut Vec<TrieState<'a, T, I>> {
+        if let NodeStorage::Static(nodes, copy) = *self {
+            *self = NodeStorage::Owned(copy(nodes));
+        }
+        match self {
+            NodeStorage::Owned(nodes) => nodes,
+            NodeStorage::Static(..) => unreachable!(),
+        }
+    }
+
+    /// Number of nodes that fit in the storage without reallocating
+    fn capacity(&self) -> usize {
+        match self {
+            NodeStorage::Owned(nodes) => nodes.capacity(),
+            NodeStorage::Static(nodes, _) => nodes.len(),
+        }
+    }
+
+    fn into_vec(self) -> Vec<TrieState<'a, T, I>> {
+        match self {
+            NodeStorage::Owned(nodes) => nodes,
+            NodeStorage::Static(nodes, copy) => copy(nodes),
+        }
+    }
+}
+
+impl<'a, T, I> Deref for NodeStorage<'a, T, I> {
+    type Target = [TrieState<'a, T, I>];
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            NodeStorage::Owned(nodes) => nodes,
+            NodeStorage::Static(nodes, _) => nodes,
+        }
+    }
+}
+
+impl<'a, T, I> DerefMut for NodeStorage<'a, T, I> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.to_mut()
+    }
+}
+
+#[doc(hidden)]
 #[derive(Debug, Clone)]
-struct SearchNode<I> {
+pub struct SearchNode<I> {
     mask: I,
     edge_start: usize,
 }
 
+#[doc(hidden)]
 #[derive(Debug, Clone)]
-enum TrieState<'a, T, I> {
+pub enum TrieState<'a, T, I> {
     Leaf(&'a [u8], T),
     Search(SearchNode<I>),
     SearchOrLeaf(&'a [u8], T, SearchNode<I>),
+    /// Chain of nodes with a single child each, stored as the bytes leading
+    /// through the chain and the index of the node at its end
+    Run(&'a [u8], usize),
+}
+
+impl<'a, T, I> TrieState<'a, T, I> {
+    /// Placeholder for slots in the node array that are not reachable
+    fn dead() -> Self
+    where
+        I: Default,
+    {
+        TrieState::Search(SearchNode {
+            mask: I::default(),
+            edge_start: 0,
+        })
+    }
+
+    fn value(&self) -> Option<&T> {
+        match self {
+            TrieState::Leaf(_, value)
+            | TrieState::SearchOrLeaf(_, value, _) => Some(value),
+            TrieState::Search(_) | TrieState::Run(..) => None,
+        }
+    }
+
+    fn value_mut(&mut self) -> Option<&mut T> {
+        match self {
+            TrieState::Leaf(_, value)
+            | TrieState::SearchOrLeaf(_, value, _) => Some(value),
+            TrieState::Search(_) | TrieState::Run(..) => None,
+        }
+    }
 }
 
 /// Enumeration of all the possible sizes of trie-hard tries. An instance of
@@ -163,7 +477,7 @@ impl<'a, T> Default for TrieHard<'a, T> {
 
 impl<'a, T> TrieHard<'a, T>
 where
-    T: 'a + Copy,
+    T: 'a,
 {
     /// Create an instance of a trie-hard trie with the given keys and values.
     /// The variant returned will be determined based on the number of unique
@@ -187,40 +501,321 @@ where
     /// assert!(trie.get("don't").is_none());
     /// ```
     pub fn new(values: Vec<(&'a [u8], T)>) -> Self {
+        Self::build(values, Normalization::None)
+    }
+
+    fn build(values: Vec<(&'a [u8], T)>, normalization: Normalization) -> Self {
         if values.is_empty() {
-            return Self::default();
+            return match Self::default() {
+                TrieHard::U8(trie) => TrieHard::U8(TrieHardSized {
+                    normalization,
+                    ..trie
+                }),
+                _ => unreachable!("Empty tries use u8s"),
+            };
         }
 
         let used_bytes = values
             .iter()
             .flat_map(|(k, _)| k.iter())
-            .cloned()
+            .map(|c| normalization.byte(*c))
             .collect::<BTreeSet<_>>();
 
-        let masks = MasksByByte::new(used_bytes);
+        let masks = MasksByByte::new(used_bytes, &normalization);
 
         match masks {
-            MasksByByte::U8(masks) => {
-                TrieHard::U8(TrieHardSized::<'_, _, u8>::new(masks, values))
-            }
-            MasksByByte::U16(masks) => {
-                TrieHard::U16(TrieHardSized::<'_, _, u16>::new(masks, values))
-            }
-            MasksByByte::U32(masks) => {
-                TrieHard::U32(TrieHardSized::<'_, _, u32>::new(masks, values))
-            }
-            MasksByByte::U64(masks) => {
-                TrieHard::U64(TrieHardSized::<'_, _, u64>::new(masks, values))
-            }
+            MasksByByte::U8(masks) => TrieHard::U8(
+                TrieHardSized::<'_, _, u8>::new(masks, values, normalization),
+            ),
+            MasksByByte::U16(masks) => TrieHard::U16(
+                TrieHardSized::<'_, _, u16>::new(masks, values, normalization),
+            ),
+            MasksByByte::U32(masks) => TrieHard::U32(
+                TrieHardSized::<'_, _, u32>::new(masks, values, normalization),
+            ),
+            MasksByByte::U64(masks) => TrieHard::U64(
+                TrieHardSized::<'_, _, u64>::new(masks, values, normalization),
+            ),
             MasksByByte::U128(masks) => {
-                TrieHard::U128(TrieHardSized::<'_, _, u128>::new(masks, values))
+                TrieHard::U128(TrieHardSized::<'_, _, u128>::new(
+                    masks,
+                    values,
+                    normalization,
+                ))
             }
             MasksByByte::U256(masks) => {
-                TrieHard::U256(TrieHardSized::<'_, _, U256>::new(masks, values))
+                TrieHard::U256(TrieHardSized::<'_, _, U256>::new(
+                    masks,
+                    values,
+                    normalization,
+                ))
+            }
+        }
+    }
+
+    /// Get a reference to the value stored for the given key. Unlike
+    /// [`TrieHard::get`], this does not require the values to be `Copy`.
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = TrieHard::new(vec![
+    ///     (b"and", String::from("first")),
+    ///     (b"ant", String::from("second")),
+    /// ]);
+    ///
+    /// assert_eq!(trie.get_ref("ant").map(String::as_str), Some("second"));
+    /// assert!(trie.get_ref("an").is_none());
+    /// ```
+    pub fn get_ref<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<&T> {
+        match self {
+            TrieHard::U8(trie) => trie.get_ref(raw_key),
+            TrieHard::U16(trie) => trie.get_ref(raw_key),
+            TrieHard::U32(trie) => trie.get_ref(raw_key),
+            TrieHard::U64(trie) => trie.get_ref(raw_key),
+            TrieHard::U128(trie) => trie.get_ref(raw_key),
+            TrieHard::U256(trie) => trie.get_ref(raw_key),
+        }
+    }
+
+    /// Get a mutable reference to the value stored for the given key. The set
+    /// of keys in the trie cannot be changed this way, only their values.
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let mut trie = TrieHard::new(vec![
+    ///     (b"and", vec![0]),
+    ///     (b"ant", vec![1]),
+    /// ]);
+    ///
+    /// trie.get_mut("and").unwrap().push(2);
+    ///
+    /// assert_eq!(trie.get_ref("and"), Some(&vec![0, 2]));
+    /// assert!(trie.get_mut("dad").is_none());
+    /// ```
+    pub fn get_mut<K: AsRef<[u8]>>(&mut self, raw_key: K) -> Option<&mut T> {
+        match self {
+            TrieHard::U8(trie) => trie.get_mut(raw_key),
+            TrieHard::U16(trie) => trie.get_mut(raw_key),
+            TrieHard::U32(trie) => trie.get_mut(raw_key),
+            TrieHard::U64(trie) => trie.get_mut(raw_key),
+            TrieHard::U128(trie) => trie.get_mut(raw_key),
+            TrieHard::U256(trie) => trie.get_mut(raw_key),
+        }
+    }
+
+    /// Insert a key and value into the trie, returning the value previously
+    /// stored for the key if there was one. When keys are matched without
+    /// regard to case or through a normalization table, the key given also
+    /// replaces the stored key it matches, as it would when building.
+    ///
+    /// Trie-hard is still optimized for bulk loading, but small changes do not
+    /// require rebuilding the trie. The cost of an insert depends on where the
+    /// new key leaves the existing structure:
+    ///
+    /// - Replacing the value of an existing key, or adding a key that ends on
+    ///   an existing branch, is done in place in _O(key length)_.
+    /// - Adding a key that diverges from a stored key part way through its
+    ///   tail, or part way through a run of bytes shared by several keys,
+    ///   appends at most a few nodes, also _O(key length)_.
+    /// - Adding a key that branches off a node with existing children moves
+    ///   that node's children to the end of the node array in
+    ///   _O(key length + fan-out)_. The slots they leave behind are reclaimed
+    ///   by rebuilding the trie once they outnumber the live nodes, so this is
+    ///   amortized.
+    /// - Adding a key that contains a byte that does not appear in any other
+    ///   key requires new byte masks, so the whole trie is rebuilt (possibly
+    ///   into a wider variant) in _O(n log n)_.
+    ///
+    /// Every new key also adds one to the entry count of each node on its
+    /// path, which is another _O(key length)_.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let mut trie = TrieHard::new(vec![(b"and", 0), (b"ant", 1)]);
+    ///
+    /// assert_eq!(trie.insert("dad", 2), None);
+    /// assert_eq!(trie.insert("and", 3), Some(0));
+    ///
+    /// assert_eq!(trie.get("dad"), Some(2));
+    /// assert_eq!(trie.get("and"), Some(3));
+    /// ```
+    pub fn insert<K: AsRef<[u8]> + ?Sized>(
+        &mut self,
+        key: &'a K,
+        value: T,
+    ) -> Option<T> {
+        let key = key.as_ref();
+        let result = match self {
+            TrieHard::U8(trie) => trie.try_insert(key, value),
+            TrieHard::U16(trie) => trie.try_insert(key, value),
+            TrieHard::U32(trie) => trie.try_insert(key, value),
+            TrieHard::U64(trie) => trie.try_insert(key, value),
+            TrieHard::U128(trie) => trie.try_insert(key, value),
+            TrieHard::U256(trie) => trie.try_insert(key, value),
+        };
+
+        match result {
+            Ok(previous) => previous,
+            Err(value) => {
+                let normalization = self.normalization().clone();
+                let mut values = std::mem::take(self).into_entries();
+                values.push((key, value));
+                *self = TrieHard::build(values, normalization);
+                None
             }
         }
     }
 
+    /// Remove a key from the trie, returning its value if it was present.
+    /// Removing a key takes _O(key length + fan-out)_, taking one away from
+    /// the entry count of each node on its path, and never requires new byte
+    /// masks. See [`TrieHard::insert`] for more details on how the trie is
+    /// updated.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let mut trie = ["and", "ant", "dad"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.remove("ant"), Some("ant"));
+    /// assert_eq!(trie.remove("ant"), None);
+    ///
+    /// assert_eq!(trie.iter().map(|(_, v)| v).collect::<Vec<_>>(), ["and", "dad"]);
+    /// ```
+    pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> Option<T> {
+        match self {
+            TrieHard::U8(trie) => trie.remove(key),
+            TrieHard::U16(trie) => trie.remove(key),
+            TrieHard::U32(trie) => trie.remove(key),
+            TrieHard::U64(trie) => trie.remove(key),
+            TrieHard::U128(trie) => trie.remove(key),
+            TrieHard::U256(trie) => trie.remove(key),
+        }
+    }
+
+    /// Number of keys in the trie. The trie keeps the number of entries in
+    /// the subtree of each node, so this is the count of the root in _O(1)_.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let mut trie = ["and", "ant", "dad"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.len(), 3);
+    ///
+    /// trie.remove("ant");
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        match self {
+            TrieHard::U8(trie) => trie.len(),
+            TrieHard::U16(trie) => trie.len(),
+            TrieHard::U32(trie) => trie.len(),
+            TrieHard::U64(trie) => trie.len(),
+            TrieHard::U128(trie) => trie.len(),
+            TrieHard::U256(trie) => trie.len(),
+        }
+    }
+
+    /// Whether the trie has no keys at all
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// assert!(TrieHard::<'_, ()>::default().is_empty());
+    /// assert!(!TrieHard::new(vec![(b"and", ())]).is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        match self {
+            TrieHard::U8(trie) => trie.is_empty(),
+            TrieHard::U16(trie) => trie.is_empty(),
+            TrieHard::U32(trie) => trie.is_empty(),
+            TrieHard::U64(trie) => trie.is_empty(),
+            TrieHard::U128(trie) => trie.is_empty(),
+            TrieHard::U256(trie) => trie.is_empty(),
+        }
+    }
+
+    /// Position of the given key in the sorted order of the keys in the
+    /// trie, or `None` if the key is not in the trie. Together with
+    /// [`TrieHard::select`] this maps each key to a dense ordinal. The entries
+    /// in the subtrees of the earlier siblings are added up at each step down
+    /// to the key, so this takes _O(key length × fan-out)_.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.rank("and"), Some(0));
+    /// assert_eq!(trie.rank("dot"), Some(4));
+    /// assert!(trie.rank("da").is_none());
+    /// ```
+    pub fn rank<K: AsRef<[u8]>>(&self, key: K) -> Option<usize> {
+        match self {
+            TrieHard::U8(trie) => trie.rank(key),
+            TrieHard::U16(trie) => trie.rank(key),
+            TrieHard::U32(trie) => trie.rank(key),
+            TrieHard::U64(trie) => trie.rank(key),
+            TrieHard::U128(trie) => trie.rank(key),
+            TrieHard::U256(trie) => trie.rank(key),
+        }
+    }
+
+    /// Count the keys that start with the given prefix. This gives the same
+    /// result as `prefix_search(prefix).count()` without walking the subtree
+    /// below the prefix. The count is kept by the node where the prefix ends,
+    /// so this takes _O(prefix length)_.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.count_prefix("d"), 3);
+    /// assert_eq!(trie.count_prefix("an"), 2);
+    /// assert_eq!(trie.count_prefix(""), 5);
+    /// assert_eq!(trie.count_prefix("b"), 0);
+    /// ```
+    pub fn count_prefix<K: AsRef<[u8]>>(&self, prefix: K) -> usize {
+        match self {
+            TrieHard::U8(trie) => trie.count_prefix(prefix),
+            TrieHard::U16(trie) => trie.count_prefix(prefix),
+            TrieHard::U32(trie) => trie.count_prefix(prefix),
+            TrieHard::U64(trie) => trie.count_prefix(prefix),
+            TrieHard::U128(trie) => trie.count_prefix(prefix),
+            TrieHard::U256(trie) => trie.count_prefix(prefix),
+        }
+    }
+
+    fn normalization(&self) -> &Normalization {
+        match self {
+            TrieHard::U8(trie) => &trie.normalization,
+            TrieHard::U16(trie) => &trie.normalization,
+            TrieHard::U32(trie) => &trie.normalization,
+            TrieHard::U64(trie) => &trie.normalization,
+            TrieHard::U128(trie) => &trie.normalization,
+            TrieHard::U256(trie) => &trie.normalization,
+        }
+    }
+
+    fn into_entries(self) -> Vec<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.into_entries(),
+            TrieHard::U16(trie) => trie.into_entries(),
+            TrieHard::U32(trie) => trie.into_entries(),
+            TrieHard::U64(trie) => trie.into_entries(),
+            TrieHard::U128(trie) => trie.into_entries(),
+            TrieHard::U256(trie) => trie.into_entries(),
+        }
+    }
+}
+
+impl<'a, T> TrieHard<'a, T>
+where
+    T: 'a + Copy,
+{
     /// Get the value stored for the given key. Any key type can be used here as
     /// long as the type implements `AsRef<[u8]>`. The byte slice referenced
     /// will serve as the actual key.
@@ -305,6 +900,12 @@ where
     ///     trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
     ///     ["dad", "do", "dot"]
     /// );
+    ///
+    /// // The iterator can also be walked backwards
+    /// assert_eq!(
+    ///     trie.prefix_search("d").rev().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["dot", "do", "dad"]
+    /// );
     /// ```
     pub fn prefix_search<K: AsRef<[u8]>>(
         &self,
@@ -319,26 +920,299 @@ where
             TrieHard::U256(trie) => TrieIter::U256(trie.prefix_search(prefix)),
         }
     }
-}
 
-/// Structure used for iterative over the contents of trie
-#[derive(Debug)]
-pub enum TrieIter<'b, 'a, T> {
-    /// Variant for iterating over trie-hard tries built on u8
-    U8(TrieIterSized<'b, 'a, T, u8>),
-    /// Variant for iterating over trie-hard tries built on u16
-    U16(TrieIterSized<'b, 'a, T, u16>),
-    /// Variant for iterating over trie-hard tries built on u32
-    U32(TrieIterSized<'b, 'a, T, u32>),
-    /// Variant for iterating over trie-hard tries built on u64
-    U64(TrieIterSized<'b, 'a, T, u64>),
-    /// Variant for iterating over trie-hard tries built on u128
-    U128(TrieIterSized<'b, 'a, T, u128>),
-    /// Variant for iterating over trie-hard tries built on u256
-    U256(TrieIterSized<'b, 'a, T, U256>),
-}
+    /// Create an iterator over the keys of the trie within a range, ordered
+    /// by their keys. Combined with [`TrieHard::successor`] this can be used
+    /// to page through a large trie.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.range("ant".."do").map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["ant", "dad"]
+    /// );
+    /// assert_eq!(
+    ///     trie.range("b"..="do").map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["dad", "do"]
+    /// );
+    /// assert_eq!(
+    ///     trie.range::<str, _>(..).map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["and", "ant", "dad", "do", "dot"]
+    /// );
+    /// assert_eq!(
+    ///     trie.range("ant".."do").rev().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["dad", "ant"]
+    /// );
+    /// ```
+    pub fn range<K, R>(&self, range: R) -> TrieIter<'_, 'a, T>
+    where
+        K: AsRef<[u8]> + ?Sized,
+        R: RangeBounds<K>,
+    {
+        match self {
+            TrieHard::U8(trie) => TrieIter::U8(trie.range(range)),
+            TrieHard::U16(trie) => TrieIter::U16(trie.range(range)),
+            TrieHard::U32(trie) => TrieIter::U32(trie.range(range)),
+            TrieHard::U64(trie) => TrieIter::U64(trie.range(range)),
+            TrieHard::U128(trie) => TrieIter::U128(trie.range(range)),
+            TrieHard::U256(trie) => TrieIter::U256(trie.range(range)),
+        }
+    }
 
-#[derive(Debug, Default)]
+    /// Find the first key in the trie that is not less than the given key,
+    /// along with its value
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.lower_bound("ant").map(|(_, v)| v), Some("ant"));
+    /// assert_eq!(trie.lower_bound("ap").map(|(_, v)| v), Some("dad"));
+    /// assert!(trie.lower_bound("e").is_none());
+    /// ```
+    pub fn lower_bound<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.lower_bound(key),
+            TrieHard::U16(trie) => trie.lower_bound(key),
+            TrieHard::U32(trie) => trie.lower_bound(key),
+            TrieHard::U64(trie) => trie.lower_bound(key),
+            TrieHard::U128(trie) => trie.lower_bound(key),
+            TrieHard::U256(trie) => trie.lower_bound(key),
+        }
+    }
+
+    /// Find the first key in the trie that is greater than the given key,
+    /// along with its value
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.successor("ant").map(|(_, v)| v), Some("dad"));
+    /// assert_eq!(trie.successor("d").map(|(_, v)| v), Some("dad"));
+    /// assert!(trie.successor("dot").is_none());
+    /// ```
+    pub fn successor<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.successor(key),
+            TrieHard::U16(trie) => trie.successor(key),
+            TrieHard::U32(trie) => trie.successor(key),
+            TrieHard::U64(trie) => trie.successor(key),
+            TrieHard::U128(trie) => trie.successor(key),
+            TrieHard::U256(trie) => trie.successor(key),
+        }
+    }
+
+    /// Find the last key in the trie that is less than the given key, along
+    /// with its value
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.predecessor("dad").map(|(_, v)| v), Some("ant"));
+    /// assert_eq!(trie.predecessor("dots").map(|(_, v)| v), Some("dot"));
+    /// assert!(trie.predecessor("a").is_none());
+    /// ```
+    pub fn predecessor<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.predecessor(key),
+            TrieHard::U16(trie) => trie.predecessor(key),
+            TrieHard::U32(trie) => trie.predecessor(key),
+            TrieHard::U64(trie) => trie.predecessor(key),
+            TrieHard::U128(trie) => trie.predecessor(key),
+            TrieHard::U256(trie) => trie.predecessor(key),
+        }
+    }
+
+    /// Get the key at the given position in the sorted order of the keys in
+    /// the trie, along with its value. This is the inverse of
+    /// [`TrieHard::rank`] and takes _O(key length × fan-out)_, skipping the
+    /// subtrees of the children before the position at each step.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(trie.select(2).map(|(_, v)| v), Some("dad"));
+    /// assert_eq!(trie.select(trie.rank("ant").unwrap()), Some((&b"ant"[..], "ant")));
+    /// assert!(trie.select(trie.len()).is_none());
+    /// ```
+    pub fn select(&self, index: usize) -> Option<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.select(index),
+            TrieHard::U16(trie) => trie.select(index),
+            TrieHard::U32(trie) => trie.select(index),
+            TrieHard::U64(trie) => trie.select(index),
+            TrieHard::U128(trie) => trie.select(index),
+            TrieHard::U256(trie) => trie.select(index),
+        }
+    }
+
+    /// Find the longest key in the trie that is a prefix of the given input,
+    /// along with its value.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["/", "/api", "/api/v1", "/static"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.longest_prefix("/api/v1/users").map(|(_, v)| v),
+    ///     Some("/api/v1")
+    /// );
+    /// assert_eq!(trie.longest_prefix("/api/v2").map(|(_, v)| v), Some("/api"));
+    /// assert!(trie.longest_prefix("api").is_none());
+    /// ```
+    pub fn longest_prefix<K: AsRef<[u8]>>(
+        &self,
+        input: K,
+    ) -> Option<(&'a [u8], T)> {
+        match self {
+            TrieHard::U8(trie) => trie.longest_prefix(input),
+            TrieHard::U16(trie) => trie.longest_prefix(input),
+            TrieHard::U32(trie) => trie.longest_prefix(input),
+            TrieHard::U64(trie) => trie.longest_prefix(input),
+            TrieHard::U128(trie) => trie.longest_prefix(input),
+            TrieHard::U256(trie) => trie.longest_prefix(input),
+        }
+    }
+
+    /// Create an iterator over every key in the trie that is a prefix of the
+    /// given input. Emitted items will be ordered from shortest to longest
+    /// key.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["/", "/api", "/api/v1", "/api/v2", "/static"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.prefixes_of("/api/v1/users/42")
+    ///         .map(|(_, v)| v)
+    ///         .collect::<Vec<_>>(),
+    ///     ["/", "/api", "/api/v1"]
+    /// );
+    /// ```
+    pub fn prefixes_of<'k, K: AsRef<[u8]> + ?Sized>(
+        &self,
+        input: &'k K,
+    ) -> PrefixesIter<'_, 'a, 'k, T> {
+        match self {
+            TrieHard::U8(trie) => PrefixesIter::U8(trie.prefixes_of(input)),
+            TrieHard::U16(trie) => PrefixesIter::U16(trie.prefixes_of(input)),
+            TrieHard::U32(trie) => PrefixesIter::U32(trie.prefixes_of(input)),
+            TrieHard::U64(trie) => PrefixesIter::U64(trie.prefixes_of(input)),
+            TrieHard::U128(trie) => PrefixesIter::U128(trie.prefixes_of(input)),
+            TrieHard::U256(trie) => PrefixesIter::U256(trie.prefixes_of(input)),
+        }
+    }
+
+    /// Find every key within `max_distance` edits of the given key, where an
+    /// edit is inserting, deleting or substituting one byte (Levenshtein
+    /// distance). Matches are returned in key order along with their
+    /// distance. Branches of the trie are abandoned as soon as every key
+    /// below them is too far away.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["commit", "config", "clone", "checkout"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.fuzzy_search("comit", 1),
+    ///     [(&b"commit"[..], "commit", 1)]
+    /// );
+    /// assert_eq!(
+    ///     trie.fuzzy_search("conit", 2)
+    ///         .into_iter()
+    ///         .map(|(_, v, d)| (v, d))
+    ///         .collect::<Vec<_>>(),
+    ///     [("commit", 2), ("config", 2)]
+    /// );
+    /// ```
+    pub fn fuzzy_search<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        max_distance: usize,
+    ) -> Vec<(&'a [u8], T, usize)> {
+        match self {
+            TrieHard::U8(trie) => trie.fuzzy_search(key, max_distance),
+            TrieHard::U16(trie) => trie.fuzzy_search(key, max_distance),
+            TrieHard::U32(trie) => trie.fuzzy_search(key, max_distance),
+            TrieHard::U64(trie) => trie.fuzzy_search(key, max_distance),
+            TrieHard::U128(trie) => trie.fuzzy_search(key, max_distance),
+            TrieHard::U256(trie) => trie.fuzzy_search(key, max_distance),
+        }
+    }
+
+    /// Like [`TrieHard::fuzzy_search`], but swapping two adjacent bytes also
+    /// counts as a single edit (optimal string alignment distance)
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["status", "stash"].into_iter().collect::<TrieHard<'_, _>>();
+    ///
+    /// assert!(trie.fuzzy_search("stauts", 1).is_empty());
+    /// assert_eq!(
+    ///     trie.fuzzy_search_damerau("stauts", 1),
+    ///     [(&b"status"[..], "status", 1)]
+    /// );
+    /// ```
+    pub fn fuzzy_search_damerau<K: AsRef<[u8]>>(
+        &self,
+        key: K,
+        max_distance: usize,
+    ) -> Vec<(&'a [u8], T, usize)> {
+        match self {
+            TrieHard::U8(trie) => trie.fuzzy_search_damerau(key, max_distance),
+            TrieHard::U16(trie) => trie.fuzzy_search_damerau(key, max_distance),
+            TrieHard::U32(trie) => trie.fuzzy_search_damerau(key, max_distance),
+            TrieHard::U64(trie) => trie.fuzzy_search_damerau(key, max_distance),
+            TrieHard::U128(trie) => {
+                trie.fuzzy_search_damerau(key, max_distance)
+            }
+            TrieHard::U256(trie) => {
+                trie.fuzzy_search_damerau(key, max_distance)
+            }
+        }
+    }
+}
+
+/// Structure used for iterative over the contents of trie. Entries are
+/// yielded in key order, or in reverse key order when iterating from the back.
+#[derive(Debug)]
+pub enum TrieIter<'b, 'a, T> {
+    /// Variant for iterating over trie-hard tries built on u8
+    U8(TrieIterSized<'b, 'a, T, u8>),
+    /// Variant for iterating over trie-hard tries built on u16
+    U16(TrieIterSized<'b, 'a, T, u16>),
+    /// Variant for iterating over trie-hard tries built on u32
+    U32(TrieIterSized<'b, 'a, T, u32>),
+    /// Variant for iterating over trie-hard tries built on u64
+    U64(TrieIterSized<'b, 'a, T, u64>),
+    /// Variant for iterating over trie-hard tries built on u128
+    U128(TrieIterSized<'b, 'a, T, u128>),
+    /// Variant for iterating over trie-hard tries built on u256
+    U256(TrieIterSized<'b, 'a, T, U256>),
+}
+
+#[derive(Debug, Default)]
 struct TrieNodeIter {
     node_index: usize,
     stage: TrieNodeIterStage,
@@ -356,6 +1230,17 @@ enum TrieNodeIterStage {
 #[derive(Debug)]
 pub struct TrieIterSized<'b, 'a, T, I> {
     stack: Vec<TrieNodeIter>,
+    /// Stack for iterating from the back. The stage of a search node is the
+    /// number of children that have not been visited yet; its own key is
+    /// emitted after all of them.
+    back: Vec<TrieNodeIter>,
+    /// Bounds on the keys emitted, for iterators over a range
+    start: Bound<Vec<u8>>,
+    end: Bound<Vec<u8>>,
+    /// Last keys emitted from each end, so that the two ends stop when they
+    /// meet
+    front_key: Option<&'a [u8]>,
+    back_key: Option<&'a [u8]>,
     trie: &'b TrieHardSized<'a, T, I>,
 }
 
@@ -363,17 +1248,50 @@ impl<'b, 'a, T, I> TrieIterSized<'b, 'a, T, I> {
     fn empty(trie: &'b TrieHardSized<'a, T, I>) -> Self {
         Self {
             stack: Default::default(),
+            back: Default::default(),
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+            front_key: None,
+            back_key: None,
             trie,
         }
     }
 
     fn new(trie: &'b TrieHardSized<'a, T, I>, node_index: usize) -> Self {
+        let root = || TrieNodeIter {
+            node_index,
+            stage: Default::default(),
+        };
         Self {
-            stack: vec![TrieNodeIter {
-                node_index,
-                stage: Default::default(),
-            }],
-            trie,
+            stack: vec![root()],
+            back: vec![root()],
+            ..Self::empty(trie)
+        }
+    }
+
+    /// Whether `key` is after the start of the range being iterated
+    fn after_start(&self, key: &[u8]) -> bool {
+        match &self.start {
+            Bound::Included(start) => {
+                self.trie.normalization.cmp(key, start).is_ge()
+            }
+            Bound::Excluded(start) => {
+                self.trie.normalization.cmp(key, start).is_gt()
+            }
+            Bound::Unbounded => true,
+        }
+    }
+
+    /// Whether `key` is before the end of the range being iterated
+    fn before_end(&self, key: &[u8]) -> bool {
+        match &self.end {
+            Bound::Included(end) => {
+                self.trie.normalization.cmp(key, end).is_le()
+            }
+            Bound::Excluded(end) => {
+                self.trie.normalization.cmp(key, end).is_lt()
+            }
+            Bound::Unbounded => true,
         }
     }
 }
@@ -396,6 +1314,242 @@ where
     }
 }
 
+impl<'b, 'a, T> DoubleEndedIterator for TrieIter<'b, 'a, T>
+where
+    T: Copy,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match self {
+            TrieIter::U8(iter) => iter.next_back(),
+            TrieIter::U16(iter) => iter.next_back(),
+            TrieIter::U32(iter) => iter.next_back(),
+            TrieIter::U64(iter) => iter.next_back(),
+            TrieIter::U128(iter) => iter.next_back(),
+            TrieIter::U256(iter) => iter.next_back(),
+        }
+    }
+}
+
+/// Structure used for iterating over the keys of a trie that are prefixes of
+/// some input
+#[derive(Debug)]
+pub enum PrefixesIter<'b, 'a, 'k, T> {
+    /// Variant for trie-hard tries built on u8
+    U8(PrefixesIterSized<'b, 'a, 'k, T, u8>),
+    /// Variant for trie-hard tries built on u16
+    U16(PrefixesIterSized<'b, 'a, 'k, T, u16>),
+    /// Variant for trie-hard tries built on u32
+    U32(PrefixesIterSized<'b, 'a, 'k, T, u32>),
+    /// Variant for trie-hard tries built on u64
+    U64(PrefixesIterSized<'b, 'a, 'k, T, u64>),
+    /// Variant for trie-hard tries built on u128
+    U128(PrefixesIterSized<'b, 'a, 'k, T, u128>),
+    /// Variant for trie-hard tries built on u256
+    U256(PrefixesIterSized<'b, 'a, 'k, T, U256>),
+}
+
+/// Structure for iterating over the keys of a trie-hard trie built on a
+/// specific integer size that are prefixes of some input
+#[derive(Debug)]
+pub struct PrefixesIterSized<'b, 'a, 'k, T, I> {
+    node_index: Option<usize>,
+    depth: usize,
+    input: &'k [u8],
+    trie: &'b TrieHardSized<'a, T, I>,
+}
+
+impl<'b, 'a, 'k, T> Iterator for PrefixesIter<'b, 'a, 'k, T>
+where
+    T: Copy,
+{
+    type Item = (&'a [u8], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            PrefixesIter::U8(iter) => iter.next(),
+            PrefixesIter::U16(iter) => iter.next(),
+            PrefixesIter::U32(iter) => iter.next(),
+            PrefixesIter::U64(iter) => iter.next(),
+            PrefixesIter::U128(iter) => iter.next(),
+            PrefixesIter::U256(iter) => iter.next(),
+        }
+    }
+}
+
+/// State of a fuzzy search while walking down the trie. There is one row of
+/// the edit distance table for every byte on the path from the root.
+#[derive(Debug)]
+struct FuzzySearch<'a, T> {
+    /// Normalized key being searched for
+    key: Vec<u8>,
+    max_distance: usize,
+    transpositions: bool,
+    /// Normalized bytes on the path from the root
+    path: Vec<u8>,
+    rows: Vec<Vec<usize>>,
+    found: Vec<(&'a [u8], T, usize)>,
+}
+
+impl<'a, T> FuzzySearch<'a, T> {
+    fn new(key: Vec<u8>, max_distance: usize, transpositions: bool) -> Self {
+        Self {
+            rows: vec![(0..=key.len()).collect()],
+            key,
+            max_distance,
+            transpositions,
+            path: Vec::new(),
+            found: Vec::new(),
+        }
+    }
+
+    /// Extend the path by one byte, returning whether any key below it can
+    /// still be within the maximum distance. Every push must be matched by a
+    /// pop.
+    fn push(&mut self, c: u8) -> bool {
+        // Safety: The first row is never popped
+        let prev = self.rows.last().unwrap();
+        let mut row = Vec::with_capacity(prev.len());
+        row.push(prev[0] + 1);
+
+        for (j, k) in self.key.iter().enumerate() {
+            let substitute = prev[j] + usize::from(*k != c);
+            let mut cost = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+
+            if self.transpositions && j > 0 {
+                if let Some(last) = self.path.last() {
+                    if *k == *last && self.key[j - 1] == c {
+                        let before = &self.rows[self.rows.len() - 2];
+                        cost = cost.min(before[j - 1] + 1);
+                    }
+                }
+            }
+            row.push(cost);
+        }
+
+        // Safety: Rows always have at least one entry
+        let reachable = *row.iter().min().unwrap() <= self.max_distance;
+        self.path.push(c);
+        self.rows.push(row);
+        reachable
+    }
+
+    fn pop(&mut self) {
+        self.path.pop();
+        self.rows.pop();
+    }
+
+    /// Record a key ending at the current path if it is close enough
+    fn report(&mut self, key: &'a [u8], value: T) {
+        // Safety: Rows always have at least one entry
+        let distance = *self.rows.last().unwrap().last().unwrap();
+        if distance <= self.max_distance {
+            self.found.push((key, value, distance));
+        }
+    }
+}
+
+/// Builder for tries with options that change how keys are matched. Tries
+/// built without any options behave exactly like those from
+/// [`TrieHard::new`].
+///
+/// ```
+/// # use trie_hard::TrieHardBuilder;
+/// let trie = TrieHardBuilder::new()
+///     .ascii_case_insensitive(true)
+///     .build(vec![(b"Content-Type", 0), (b"Host", 1)]);
+///
+/// assert_eq!(trie.get("content-type"), Some(0));
+/// assert_eq!(trie.get("HOST"), Some(1));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrieHardBuilder {
+    normalization: Normalization,
+}
+
+impl TrieHardBuilder {
+    /// Create a builder with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match keys without regard to ASCII case. Upper and lower case letters
+    /// are given the same mask when the trie is built, so lookups need no
+    /// extra work or allocation. Keys that only differ by case are treated as
+    /// the same key, and the last one given is kept. Iterators return keys
+    /// as they were given.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardBuilder;
+    /// let trie = TrieHardBuilder::new()
+    ///     .ascii_case_insensitive(true)
+    ///     .build(vec![(b"Accept", 0), (b"accept-ranges", 1)]);
+    ///
+    /// assert_eq!(trie.get("ACCEPT-Ranges"), Some(1));
+    /// assert_eq!(
+    ///     trie.prefix_search("ACC").map(|(k, _)| k).collect::<Vec<_>>(),
+    ///     [&b"Accept"[..], b"accept-ranges"]
+    /// );
+    /// ```
+    pub fn ascii_case_insensitive(&mut self, yes: bool) -> &mut Self {
+        self.normalization = if yes {
+            Normalization::AsciiCase
+        } else {
+            Normalization::None
+        };
+        self
+    }
+
+    /// Match keys through a byte normalization table. Two bytes are treated
+    /// as equal when they have the same entry in `table`, and like
+    /// [`TrieHardBuilder::ascii_case_insensitive`] they share a mask, so the
+    /// table costs nothing at lookup time and can make the trie use a
+    /// narrower integer type. This replaces ASCII case folding if it was
+    /// enabled; a table that lower cases ASCII letters gives both.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardBuilder;
+    /// let mut table: [u8; 256] = std::array::from_fn(|c| c as u8);
+    /// table[b'_' as usize] = b'-';
+    ///
+    /// let trie = TrieHardBuilder::new()
+    ///     .normalization_table(table)
+    ///     .build(vec![(b"x-forwarded-for", 0)]);
+    ///
+    /// assert_eq!(trie.get("x_forwarded-for"), Some(0));
+    /// assert_eq!(trie.get("x_forwarded_for"), Some(0));
+    /// ```
+    pub fn normalization_table(&mut self, table: [u8; 256]) -> &mut Self {
+        self.normalization = Normalization::Table(Box::new(table));
+        self
+    }
+
+    /// Build a trie from the given keys and values with the options of this
+    /// builder
+    pub fn build<'a, T>(&self, values: Vec<(&'a [u8], T)>) -> TrieHard<'a, T> {
+        TrieHard::build(values, self.normalization.clone())
+    }
+
+    /// Build a minimized trie from the given keys and values with the options
+    /// of this builder. Identical subtrees are merged, which can take much
+    /// less memory for large word lists; see [`TrieHardMinimized`].
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardBuilder;
+    /// let trie = TrieHardBuilder::new()
+    ///     .ascii_case_insensitive(true)
+    ///     .build_minimized(vec![(b"Content-Length", 0), (b"Content-Type", 1)]);
+    ///
+    /// assert_eq!(trie.get("content-type"), Some(1));
+    /// assert_eq!(trie.prefix_search("CONTENT-").count(), 2);
+    /// ```
+    pub fn build_minimized<'a, T>(
+        &self,
+        values: Vec<(&'a [u8], T)>,
+    ) -> TrieHardMinimized<'a, T> {
+        TrieHardMinimized::build(values, self.normalization.clone())
+    }
+}
+
 impl<'a, T> FromIterator<&'a T> for TrieHard<'a, &'a T>
 where
     T: 'a + AsRef<[u8]> + ?Sized,
@@ -419,9 +1573,23 @@ macro_rules! trie_impls {
 
     (_impl $int_type:ty) => {
 
+        impl<'a, T> TrieNodes for TrieHardSized<'a, T, $int_type> {
+            fn normalization(&self) -> &Normalization {
+                &self.normalization
+            }
+
+            fn walk_node(&self, index: usize) -> Option<WalkNode<'_>> {
+                Some(self.nodes.get(index)?.walk_node())
+            }
+
+            fn child(&self, index: usize, c: u8) -> Option<usize> {
+                self.nodes[index].child(c, &self.masks)
+            }
+        }
+
         impl SearchNode<$int_type> {
-            fn evaluate<T>(&self, c: u8, trie: &TrieHardSized<'_, T, $int_type>) -> Option<usize> {
-                let c_mask = trie.masks.0[c as usize];
+            fn evaluate(&self, c: u8, masks: &MasksByByteSized<$int_type>) -> Option<usize> {
+                let c_mask = masks.by_byte[c as usize];
                 let mask_res = self.mask & c_mask;
                 (mask_res > 0).then(|| {
                     let smaller_bits = mask_res - 1;
@@ -430,115 +1598,1296 @@ macro_rules! trie_impls {
                     self.edge_start + index_offset
                 })
             }
+
+            /// Indices of the children of the node
+            fn children(&self) -> Range<usize> {
+                self.edge_start..self.edge_start + self.mask.count_ones() as usize
+            }
         }
 
-        impl<'a, T> TrieHardSized<'a, T, $int_type>
-        where
-            T: Copy
-        {
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            /// Get a reference to the value stored for the given key. Unlike
+            /// `get`, this does not require the values to be `Copy`.
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = TrieHard::new(vec![
+            ///     (b"and", String::from("first")),
+            ///     (b"ant", String::from("second")),
+            /// ]);
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.get_ref("ant").map(String::as_str),
+            ///     Some("second")
+            /// );
+            /// assert!(sized_trie.get_ref("an").is_none());
+            /// ```
+            pub fn get_ref<K: AsRef<[u8]>>(&self, key: K) -> Option<&T> {
+                let index = self.find_index(key.as_ref())?;
+                self.nodes[index].value()
+            }
+
+            /// Get a mutable reference to the value stored for the given key.
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = TrieHard::new(vec![
+            ///     (b"and", vec![0]),
+            ///     (b"ant", vec![1]),
+            /// ]);
+            ///
+            /// let TrieHard::U8(mut sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// sized_trie.get_mut("and").unwrap().push(2);
+            ///
+            /// assert_eq!(sized_trie.get_ref("and"), Some(&vec![0, 2]));
+            /// ```
+            pub fn get_mut<K: AsRef<[u8]>>(&mut self, key: K) -> Option<&mut T> {
+                let index = self.find_index(key.as_ref())?;
+                self.nodes[index].value_mut()
+            }
+
+            /// Number of keys in the trie in _O(1)_. See [`TrieHard::len`].
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.len(), 3);
+            /// assert!(!sized_trie.is_empty());
+            /// ```
+            pub fn len(&self) -> usize {
+                self.below.first().copied().unwrap_or(0)
+            }
+
+            /// Whether the trie has no keys at all
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = TrieHard::<'_, ()>::default();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.is_empty());
+            /// ```
+            pub fn is_empty(&self) -> bool {
+                self.nodes.is_empty()
+            }
+
+            /// Position of the given key in the sorted order of the keys in
+            /// the trie, or `None` if the key is not in the trie. This takes
+            /// _O(key length × fan-out)_.
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.rank("and"), Some(0));
+            /// assert_eq!(sized_trie.rank("do"), Some(3));
+            /// assert!(sized_trie.rank("d").is_none());
+            /// ```
+            pub fn rank<K: AsRef<[u8]>>(&self, key: K) -> Option<usize> {
+                let mut rank = 0;
+                walk(self, key.as_ref(), Target::Entry, |parent, child| {
+                    rank += self.rank_step(parent, child);
+                })?;
+                Some(rank)
+            }
+
+            /// Count the keys that start with the given prefix in _O(prefix
+            /// length)_
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.count_prefix("do"), 2);
+            /// assert_eq!(sized_trie.count_prefix("dad"), 1);
+            /// assert_eq!(sized_trie.count_prefix("dada"), 0);
+            /// ```
+            pub fn count_prefix<K: AsRef<[u8]>>(&self, prefix: K) -> usize {
+                match walk(self, prefix.as_ref(), Target::Prefix, |_, _| ()) {
+                    Some(node_index) => self.below[node_index],
+                    None => 0,
+                }
+            }
+
+            /// Number of entries that come before `child` but not before
+            /// `parent`: the entry of the parent itself and the entries below
+            /// the earlier children of the parent
+            fn rank_step(&self, parent: usize, child: usize) -> usize {
+                let parent_entry = matches!(self.nodes[parent], TrieState::SearchOrLeaf(..));
+                let first = self.nodes[parent].children().start;
+                usize::from(parent_entry) + self.below[first..child].iter().sum::<usize>()
+            }
+
+            /// Add or take away one entry from the count of every node on the
+            /// path to a key, given as the edges followed from the root
+            fn count_path(&mut self, path: &[(usize, usize)], added: bool) {
+                let below = self.below.to_mut();
+                let nodes = std::iter::once(0).chain(path.iter().map(|(_, child)| *child));
+                for node_index in nodes {
+                    if added {
+                        below[node_index] += 1;
+                    } else {
+                        below[node_index] -= 1;
+                    }
+                }
+            }
+
+            /// Count the entries in the subtree of each node. The nodes
+            /// reachable from the root are visited breadth first, so that
+            /// counting them in reverse sees every child before its parent.
+            fn count_below(nodes: &[TrieState<'a, T, $int_type>]) -> Vec<usize> {
+                let mut order = Vec::with_capacity(nodes.len());
+                if !nodes.is_empty() {
+                    order.push(0);
+                }
+
+                let mut next = 0;
+                while let Some(&node_index) = order.get(next) {
+                    next += 1;
+                    order.extend(nodes[node_index].children());
+                }
+
+                let mut below = vec![0; nodes.len()];
+                for &node_index in order.iter().rev() {
+                    let node = &nodes[node_index];
+                    let children = node.children().map(|child| below[child]).sum::<usize>();
+                    below[node_index] = children + usize::from(node.value().is_some());
+                }
+
+                below
+            }
+
+            /// Find the index of the node holding the entry for the given key
+            fn find_index(&self, key: &[u8]) -> Option<usize> {
+                walk(self, key, Target::Entry, |_, _| ())
+            }
+
+            /// Remove a key from the trie, returning its value if it was
+            /// present. See [`TrieHard::remove`] for the cost of removals.
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(mut sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.remove("ant"), Some("ant"));
+            /// assert!(sized_trie.get("ant").is_none());
+            /// ```
+            pub fn remove<K: AsRef<[u8]>>(&mut self, key: K) -> Option<T> {
+                // Each step on the path is the parent and child of an edge
+                // that was followed, so the child can be unlinked from its
+                // parent
+                let mut path = Vec::new();
+                let node_index = walk(self, key.as_ref(), Target::Entry, |parent, child| {
+                    path.push((parent, child));
+                })?;
+                self.count_path(&path, false);
+
+                let value = match std::mem::replace(
+                    &mut self.nodes[node_index],
+                    TrieState::dead(),
+                ) {
+                    TrieState::Leaf(_, value) => value,
+                    TrieState::SearchOrLeaf(_, value, search) => {
+                        self.nodes[node_index] = TrieState::Search(search);
+                        return Some(value);
+                    }
+                    TrieState::Search(_) | TrieState::Run(..) => {
+                        unreachable!("Only nodes with an entry are found")
+                    }
+                };
+
+                // The removed leaf leaves a hole in its parent's children. Keep
+                // unlinking nodes towards the root for as long as that leaves
+                // a node without any entries below it.
+                loop {
+                    let Some((parent, removed)) = path.pop() else {
+                        self.nodes.to_mut().clear();
+                        self.below.to_mut().clear();
+                        self.dead = 0;
+                        break;
+                    };
+
+                    // The slot of the removed child is freed whatever its
+                    // parent is. The parent's own slot is counted once it is
+                    // removed in turn.
+                    self.dead += 1;
+
+                    let search = match &mut self.nodes[parent] {
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => search,
+                        // A run only has the one child, so it goes as well
+                        TrieState::Run(..) => {
+                            self.nodes[parent] = TrieState::dead();
+                            continue;
+                        }
+                        TrieState::Leaf(..) => {
+                            unreachable!("Only search nodes have children")
+                        }
+                    };
+
+                    // Children are in the order of their mask bits, so the
+                    // removed child has the lowest bit left after clearing
+                    // those of its earlier siblings
+                    let block = search.children();
+                    let mut bits = search.mask;
+                    for _ in block.start..removed {
+                        bits = bits & (bits - 1);
+                    }
+                    let mut removed_bit = bits;
+                    removed_bit ^= bits & (bits - 1);
+                    search.mask ^= removed_bit;
+                    let now_empty = search.mask == 0;
+
+                    // Shift the later siblings down, leaving the dead slot
+                    // at the end of the block
+                    self.nodes[removed..block.end].rotate_left(1);
+                    self.below.to_mut()[removed..block.end].rotate_left(1);
+
+                    if !now_empty {
+                        break;
+                    }
+
+                    if let TrieState::SearchOrLeaf(k, v, _) = std::mem::replace(
+                        &mut self.nodes[parent],
+                        TrieState::dead(),
+                    ) {
+                        self.nodes[parent] = TrieState::Leaf(k, v);
+                        break;
+                    }
+                }
+
+                self.compact_if_sparse();
+                Some(value)
+            }
+
+            /// Try to insert the key and value without changing the byte masks.
+            /// If the key uses a byte that has no mask, the value is handed back
+            /// so the caller can rebuild the trie.
+            fn try_insert(&mut self, key: &'a [u8], value: T) -> Result<Option<T>, T> {
+                if self.nodes.is_empty()
+                    || key.iter().any(|c| self.masks.by_byte[*c as usize] == 0)
+                {
+                    return Err(value);
+                }
+
+                if let Some(previous) = self.insert_entry(key, value) {
+                    return Ok(Some(previous));
+                }
+
+                // The new key is counted by every node on its path
+                let mut path = Vec::new();
+                walk(self, key, Target::Entry, |parent, child| {
+                    path.push((parent, child));
+                });
+                self.count_path(&path, true);
+
+                self.compact_if_sparse();
+                Ok(None)
+            }
+
+            /// Add the key and value to the nodes, returning the value stored
+            /// for the key before if there was one. New nodes are given the
+            /// counts of the keys that were already below them, and counting
+            /// the new key itself is left for the caller.
+            fn insert_entry(&mut self, key: &'a [u8], value: T) -> Option<T> {
+                let mut node_index = 0;
+
+                let mut depth = 0;
+                while let Some(c) = key.get(depth) {
+                    let next_state_opt = match &mut self.nodes[node_index] {
+                        TrieState::Leaf(k, v) => {
+                            if self.normalization.eq(k, key) {
+                                *k = key;
+                                return Some(std::mem::replace(v, value));
+                            }
+                            self.split_leaf(node_index, depth, key, value);
+                            return None;
+                        }
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => {
+                            depth += 1;
+                            search.evaluate(*c, &self.masks)
+                        }
+                        TrieState::Run(run, next) => {
+                            let (run, next) = (*run, *next);
+                            let Some(next_depth) = self.follow_run(run, key, depth) else {
+                                self.split_run(node_index, depth, key, value);
+                                return None;
+                            };
+                            depth = next_depth;
+                            Some(next)
+                        }
+                    };
+
+                    if let Some(next_state_index) = next_state_opt {
+                        node_index = next_state_index;
+                    } else {
+                        self.add_child(node_index, *c, TrieState::Leaf(key, value));
+                        return None;
+                    }
+                }
+
+                match &mut self.nodes[node_index] {
+                    TrieState::Leaf(k, v) if k.len() == key.len() => {
+                        *k = key;
+                        Some(std::mem::replace(v, value))
+                    }
+                    TrieState::Leaf(..) => {
+                        self.split_leaf(node_index, key.len(), key, value);
+                        None
+                    }
+                    TrieState::SearchOrLeaf(k, v, _) => {
+                        *k = key;
+                        Some(std::mem::replace(v, value))
+                    }
+                    TrieState::Search(search) => {
+                        let search = search.clone();
+                        self.nodes[node_index] =
+                            TrieState::SearchOrLeaf(key, value, search);
+                        None
+                    }
+                    TrieState::Run(..) => {
+                        self.split_run(node_index, key.len(), key, value);
+                        None
+                    }
+                }
+            }
+
+            /// Replace the leaf at `node_index`, which sits at `depth`, with the
+            /// nodes needed to hold both its own key and the given key.
+            fn split_leaf(
+                &mut self,
+                node_index: usize,
+                depth: usize,
+                key: &'a [u8],
+                value: T,
+            ) {
+                let TrieState::Leaf(old_key, old_value) = std::mem::replace(
+                    &mut self.nodes[node_index],
+                    TrieState::dead(),
+                ) else {
+                    unreachable!("Only leaves can be split")
+                };
+
+                let shared = old_key[depth..]
+                    .iter()
+                    .zip(&key[depth..])
+                    .take_while(|(l, r)| {
+                        self.normalization.byte(**l) == self.normalization.byte(**r)
+                    })
+                    .count();
+
+                let current = self.chain(node_index, &key[depth..depth + shared]);
+
+                let split = depth + shared;
+                let edge_start = self.nodes.len();
+                let (state, children) = match (old_key.get(split), key.get(split)) {
+                    (None, Some(c)) => (
+                        TrieState::SearchOrLeaf(old_key, old_value, SearchNode {
+                            mask: self.masks.by_byte[*c as usize],
+                            edge_start,
+                        }),
+                        [Some((TrieState::Leaf(key, value), 0)), None],
+                    ),
+                    (Some(c), None) => (
+                        TrieState::SearchOrLeaf(key, value, SearchNode {
+                            mask: self.masks.by_byte[*c as usize],
+                            edge_start,
+                        }),
+                        [Some((TrieState::Leaf(old_key, old_value), 1)), None],
+                    ),
+                    (Some(old_c), Some(c)) => {
+                        let mut mask = self.masks.by_byte[*old_c as usize];
+                        mask |= self.masks.by_byte[*c as usize];
+                        let old_leaf = TrieState::Leaf(old_key, old_value);
+                        let new_leaf = TrieState::Leaf(key, value);
+                        (
+                            TrieState::Search(SearchNode { mask, edge_start }),
+                            if self.normalization.byte(*old_c) < self.normalization.byte(*c) {
+                                [Some((old_leaf, 1)), Some((new_leaf, 0))]
+                            } else {
+                                [Some((new_leaf, 0)), Some((old_leaf, 1))]
+                            },
+                        )
+                    }
+                    (None, None) => unreachable!("Keys are known to differ"),
+                };
+
+                self.nodes[current] = state;
+                for (child, below) in children.into_iter().flatten() {
+                    self.push_node(child, below);
+                }
+            }
+
+            /// Replace the run at `node_index`, which starts at `depth`, with the
+            /// nodes needed to hold both the keys below it and the given key,
+            /// which leaves the run part way through.
+            fn split_run(
+                &mut self,
+                node_index: usize,
+                depth: usize,
+                key: &'a [u8],
+                value: T,
+            ) {
+                let TrieState::Run(run, next) = std::mem::replace(
+                    &mut self.nodes[node_index],
+                    TrieState::dead(),
+                ) else {
+                    unreachable!("Only runs can be split")
+                };
+
+                let shared = run
+                    .iter()
+                    .zip(&key[depth..])
+                    .take_while(|(l, r)| {
+                        self.normalization.byte(**l) == self.normalization.byte(**r)
+                    })
+                    .count();
+                let current = self.chain(node_index, &run[..shared]);
+
+                // The bytes of the run after the one where the key leaves it
+                // lead to the node at the end of the run as before. Without
+                // any, that node takes the place of the run.
+                let run_c = run[shared];
+                let tail_count = self.below[next];
+                let tail = match &run[shared + 1..] {
+                    [] => {
+                        self.dead += 1;
+                        std::mem::replace(&mut self.nodes[next], TrieState::dead())
+                    }
+                    [c] => TrieState::Search(SearchNode {
+                        mask: self.masks.by_byte[*c as usize],
+                        edge_start: next,
+                    }),
+                    rest => TrieState::Run(rest, next),
+                };
+
+                let edge_start = self.nodes.len();
+                let (state, children) = match key.get(depth + shared) {
+                    None => (
+                        TrieState::SearchOrLeaf(key, value, SearchNode {
+                            mask: self.masks.by_byte[run_c as usize],
+                            edge_start,
+                        }),
+                        [Some((tail, tail_count)), None],
+                    ),
+                    Some(c) => {
+                        let mut mask = self.masks.by_byte[run_c as usize];
+                        mask |= self.masks.by_byte[*c as usize];
+                        let leaf = TrieState::Leaf(key, value);
+                        (
+                            TrieState::Search(SearchNode { mask, edge_start }),
+                            if self.normalization.byte(run_c) < self.normalization.byte(*c) {
+                                [Some((tail, tail_count)), Some((leaf, 0))]
+                            } else {
+                                [Some((leaf, 0)), Some((tail, tail_count))]
+                            },
+                        )
+                    }
+                };
+
+                self.nodes[current] = state;
+                for (child, below) in children.into_iter().flatten() {
+                    self.push_node(child, below);
+                }
+            }
+
+            /// Turn the node at `node_index` into a chain of single children
+            /// through the given bytes, a run unless there is only one byte.
+            /// Returns the index of the empty slot at the end of the chain.
+            fn chain(&mut self, node_index: usize, bytes: &'a [u8]) -> usize {
+                let next = self.nodes.len();
+                self.nodes[node_index] = match bytes {
+                    [] => return node_index,
+                    [c] => TrieState::Search(SearchNode {
+                        mask: self.masks.by_byte[*c as usize],
+                        edge_start: next,
+                    }),
+                    _ => TrieState::Run(bytes, next),
+                };
+                // The chain holds the same keys as the node it replaces
+                self.push_node(TrieState::dead(), self.below[node_index]);
+                next
+            }
+
+            /// Append a node along with the number of entries in its subtree
+            fn push_node(&mut self, node: TrieState<'a, T, $int_type>, below: usize) {
+                self.nodes.to_mut().push(node);
+                self.below.to_mut().push(below);
+            }
+
+            /// Add a child for byte `c` to the search node at `node_index`
+            fn add_child(&mut self, node_index: usize, c: u8, child: TrieState<'a, T, $int_type>) {
+                let node_count = self.nodes.len();
+                let c_mask = self.masks.by_byte[c as usize];
+                let (TrieState::Search(search)
+                | TrieState::SearchOrLeaf(_, _, search)) =
+                    &self.nodes[node_index]
+                else {
+                    unreachable!("Only search nodes have children")
+                };
+
+                let position = ((c_mask - 1) & search.mask).count_ones() as usize;
+                let child_count = search.mask.count_ones() as usize;
+                let edge_start = search.edge_start;
+
+                let (TrieState::Search(search)
+                | TrieState::SearchOrLeaf(_, _, search)) =
+                    &mut self.nodes[node_index]
+                else {
+                    unreachable!("Only search nodes have children")
+                };
+                search.mask |= c_mask;
+
+                // When the children are the last block in the array they can
+                // grow in place, otherwise they have to move to the end
+                if edge_start + child_count == node_count {
+                    self.nodes.to_mut().insert(edge_start + position, child);
+                    self.below.to_mut().insert(edge_start + position, 0);
+                    return;
+                }
+
+                search.edge_start = node_count;
+
+                // The new key is counted by the caller
+                let mut child = Some(child);
+                for i in 0..child_count {
+                    if i == position {
+                        if let Some(child) = child.take() {
+                            self.push_node(child, 0);
+                        }
+                    }
+                    let moved = std::mem::replace(
+                        &mut self.nodes[edge_start + i],
+                        TrieState::dead(),
+                    );
+                    self.push_node(moved, self.below[edge_start + i]);
+                }
+                if let Some(child) = child {
+                    self.push_node(child, 0);
+                }
+
+                self.dead += child_count;
+            }
+
+            /// Rebuild the trie once dead slots outnumber the live ones
+            fn compact_if_sparse(&mut self) {
+                if self.dead * 2 > self.nodes.len() {
+                    let masks = self.masks.clone();
+                    let normalization = self.normalization.clone();
+                    let values = std::mem::take(self).into_entries();
+                    if values.is_empty() {
+                        self.masks = masks;
+                        self.normalization = normalization;
+                    } else {
+                        *self = Self::new(masks, values, normalization);
+                    }
+                }
+            }
+        }
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type>
+        where
+            T: Copy
+        {
+
+            /// Get the value stored for the given key. Any key type can be used
+            /// here as long as the type implements `AsRef<[u8]>`. The byte slice
+            /// referenced will serve as the actual key.
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.get("dad".to_owned()).is_some());
+            /// assert!(sized_trie.get(b"do").is_some());
+            /// assert!(sized_trie.get(b"don't".to_vec()).is_none());
+            /// ```
+            pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<T> {
+                self.get_from_bytes(key.as_ref())
+            }
+
+            /// Get the value stored for the given byte-slice key.
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.get_from_bytes(b"dad").is_some());
+            /// assert!(sized_trie.get_from_bytes(b"do").is_some());
+            /// assert!(sized_trie.get_from_bytes(b"don't").is_none());
+            /// ```
+            pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+                self.get_ref(key).copied()
+            }
+
+            /// Create an iterator over the entire trie. Emitted items will be
+            /// ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["and", "ant", "dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn iter(&self) -> TrieIterSized<'_, 'a, T, $int_type> {
+                TrieIterSized::new(self, 0)
+            }
+
+
+            /// Create an iterator over the portion of the trie starting with the given
+            /// prefix
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn prefix_search<K: AsRef<[u8]>>(&self, prefix: K) -> TrieIterSized<'_, 'a, T, $int_type> {
+                match walk(self, prefix.as_ref(), Target::Prefix, |_, _| ()) {
+                    Some(node_index) => TrieIterSized::new(self, node_index),
+                    None => TrieIterSized::empty(self),
+                }
+            }
+
+            /// Create an iterator over the keys of the trie within a range,
+            /// ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.range("ant".."do").map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["ant", "dad"]
+            /// );
+            /// ```
+            pub fn range<K, R>(&self, range: R) -> TrieIterSized<'_, 'a, T, $int_type>
+            where
+                K: AsRef<[u8]> + ?Sized,
+                R: RangeBounds<K>,
+            {
+                let mut iter = match range.start_bound() {
+                    Bound::Included(start) => self.seek(start.as_ref(), true),
+                    Bound::Excluded(start) => self.seek(start.as_ref(), false),
+                    Bound::Unbounded => self.iter(),
+                };
+                iter.back = match range.end_bound() {
+                    Bound::Included(end) => self.seek_back(end.as_ref(), true),
+                    Bound::Excluded(end) => self.seek_back(end.as_ref(), false),
+                    Bound::Unbounded => self.iter().back,
+                };
+                iter.start = range.start_bound().map(|start| start.as_ref().to_vec());
+                iter.end = range.end_bound().map(|end| end.as_ref().to_vec());
+                iter
+            }
+
+            /// Find the first key in the trie that is not less than the given
+            /// key, along with its value
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.lower_bound("do").map(|(_, v)| v), Some("do"));
+            /// assert_eq!(sized_trie.lower_bound("b").map(|(_, v)| v), Some("dad"));
+            /// assert!(sized_trie.lower_bound("e").is_none());
+            /// ```
+            pub fn lower_bound<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+                self.seek(key.as_ref(), true).next()
+            }
+
+            /// Find the first key in the trie that is greater than the given
+            /// key, along with its value
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.successor("do").map(|(_, v)| v), Some("dot"));
+            /// assert!(sized_trie.successor("dot").is_none());
+            /// ```
+            pub fn successor<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+                self.seek(key.as_ref(), false).next()
+            }
+
+            /// Find the last key in the trie that is less than the given key,
+            /// along with its value
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.predecessor("do").map(|(_, v)| v), Some("dad"));
+            /// assert_eq!(sized_trie.predecessor("zoo").map(|(_, v)| v), Some("dot"));
+            /// assert!(sized_trie.predecessor("and").is_none());
+            /// ```
+            pub fn predecessor<K: AsRef<[u8]>>(&self, key: K) -> Option<(&'a [u8], T)> {
+                let key = key.as_ref();
+                if self.nodes.is_empty() {
+                    return None;
+                }
+
+                let normalization = &self.normalization;
+                let edge_bytes = self.masks.edge_bytes();
+
+                // Closest entry before the key found so far, or the root of
+                // the closest subtree whose keys are all before it. Deeper
+                // candidates are always closer.
+                let mut entry = None;
+                let mut subtree = None;
+
+                let mut node_index = 0;
+                let mut depth = 0;
+                loop {
+                    let search = match &self.nodes[node_index] {
+                        TrieState::Leaf(k, v) => {
+                            if normalization.cmp(k, key).is_lt() {
+                                return Some((k, *v));
+                            }
+                            break;
+                        }
+                        TrieState::Search(search) => search,
+                        TrieState::SearchOrLeaf(k, v, search) => {
+                            if depth < key.len() {
+                                entry = Some((*k, *v));
+                                subtree = None;
+                            }
+                            search
+                        }
+                        TrieState::Run(run, next) => {
+                            match self.cmp_run(run, key, depth) {
+                                Ordering::Less => subtree = Some(node_index),
+                                Ordering::Equal => {
+                                    node_index = *next;
+                                    depth += run.len();
+                                    continue;
+                                }
+                                Ordering::Greater => {}
+                            }
+                            break;
+                        }
+                    };
+
+                    let Some(c) = key.get(depth) else {
+                        break;
+                    };
+                    let c = normalization.byte(*c);
+
+                    let mut bits = search.mask;
+                    let mut child = search.edge_start;
+                    let mut next = None;
+                    while bits != <$int_type>::default() {
+                        let b = normalization.byte(edge_bytes[bits.trailing_zeros() as usize]);
+                        match b.cmp(&c) {
+                            Ordering::Less => subtree = Some(child),
+                            Ordering::Equal => next = Some(child),
+                            Ordering::Greater => {}
+                        }
+                        if b >= c {
+                            break;
+                        }
+                        bits = bits & (bits - 1);
+                        child += 1;
+                    }
+
+                    match next {
+                        Some(next) => node_index = next,
+                        None => break,
+                    }
+                    depth += 1;
+                }
+
+                match subtree {
+                    Some(subtree) => Some(self.last_entry(subtree)),
+                    None => entry,
+                }
+            }
+
+            /// Get the key at the given position in the sorted order of the
+            /// keys in the trie, along with its value. This is the inverse of
+            /// [`rank`](Self::rank) and takes _O(key length × fan-out)_.
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.select(0).map(|(_, v)| v), Some("and"));
+            /// assert_eq!(sized_trie.select(3).map(|(_, v)| v), Some("do"));
+            /// assert!(sized_trie.select(5).is_none());
+            /// ```
+            pub fn select(&self, index: usize) -> Option<(&'a [u8], T)> {
+                if index >= self.len() {
+                    return None;
+                }
+
+                // The index always stays below the count of the current
+                // node, so one of its children holds the entry whenever the
+                // node itself does not
+                let mut index = index;
+                let mut node_index = 0;
+                loop {
+                    let search = match &self.nodes[node_index] {
+                        TrieState::Leaf(k, v) => {
+                            debug_assert_eq!(index, 0);
+                            return Some((k, *v));
+                        }
+                        TrieState::SearchOrLeaf(k, v, _) if index == 0 => {
+                            return Some((k, *v));
+                        }
+                        TrieState::SearchOrLeaf(_, _, search) => {
+                            index -= 1;
+                            search
+                        }
+                        TrieState::Search(search) => search,
+                        TrieState::Run(_, next) => {
+                            node_index = *next;
+                            continue;
+                        }
+                    };
+
+                    // Skip the children whose subtrees end before the index
+                    for child in search.children() {
+                        node_index = child;
+                        if index < self.below[child] {
+                            break;
+                        }
+                        index -= self.below[child];
+                    }
+                }
+            }
+
+            /// Create an iterator that starts at the first key after `key`, or
+            /// at `key` itself if it is in the trie and `inclusive` is set
+            fn seek(&self, key: &[u8], inclusive: bool) -> TrieIterSized<'_, 'a, T, $int_type> {
+                let mut iter = TrieIterSized::empty(self);
+                if self.nodes.is_empty() {
+                    return iter;
+                }
+
+                let normalization = &self.normalization;
+                let edge_bytes = self.masks.edge_bytes();
+
+                let mut node_index = 0;
+                let mut depth = 0;
+                loop {
+                    let search = match &self.nodes[node_index] {
+                        TrieState::Leaf(k, _) => {
+                            let order = normalization.cmp(k, key);
+                            if order.is_gt() || (inclusive && order.is_eq()) {
+                                iter.stack.push(TrieNodeIter {
+                                    node_index,
+                                    stage: TrieNodeIterStage::Inner,
+                                });
+                            }
+                            break;
+                        }
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => search,
+                        TrieState::Run(run, next) => {
+                            match self.cmp_run(run, key, depth) {
+                                Ordering::Less => {}
+                                Ordering::Equal => {
+                                    node_index = *next;
+                                    depth += run.len();
+                                    continue;
+                                }
+                                Ordering::Greater => iter.stack.push(TrieNodeIter {
+                                    node_index,
+                                    stage: TrieNodeIterStage::Inner,
+                                }),
+                            }
+                            break;
+                        }
+                    };
+                    let child_count = search.mask.count_ones() as usize;
+
+                    let Some(c) = key.get(depth) else {
+                        // Every key below this node starts with `key`, and
+                        // only the key of the node itself can be equal to it
+                        if inclusive {
+                            iter.stack.push(TrieNodeIter {
+                                node_index,
+                                stage: TrieNodeIterStage::Inner,
+                            });
+                        } else {
+                            iter.stack.push(TrieNodeIter {
+                                node_index,
+                                stage: TrieNodeIterStage::Child(0, child_count),
+                            });
+                            iter.stack.push(TrieNodeIter {
+                                node_index: search.edge_start,
+                                stage: TrieNodeIterStage::Inner,
+                            });
+                        }
+                        break;
+                    };
+                    let c = normalization.byte(*c);
+
+                    // Find the first child whose edge is not before `c`
+                    let mut bits = search.mask;
+                    let mut child = 0;
+                    let mut found = None;
+                    while bits != <$int_type>::default() {
+                        let b = normalization.byte(edge_bytes[bits.trailing_zeros() as usize]);
+                        if b >= c {
+                            found = Some((child, b == c));
+                            break;
+                        }
+                        bits = bits & (bits - 1);
+                        child += 1;
+                    }
+
+                    let Some((child, equal)) = found else {
+                        break;
+                    };
+                    iter.stack.push(TrieNodeIter {
+                        node_index,
+                        stage: TrieNodeIterStage::Child(child, child_count),
+                    });
+                    if equal {
+                        node_index = search.edge_start + child;
+                    } else {
+                        iter.stack.push(TrieNodeIter {
+                            node_index: search.edge_start + child,
+                            stage: TrieNodeIterStage::Inner,
+                        });
+                        break;
+                    }
+                    depth += 1;
+                }
+
+                iter
+            }
+
+            /// Stack for iterating back from the last key before `key`, or
+            /// from `key` itself if it is in the trie and `inclusive` is set
+            fn seek_back(&self, key: &[u8], inclusive: bool) -> Vec<TrieNodeIter> {
+                let mut stack = Vec::new();
+                if self.nodes.is_empty() {
+                    return stack;
+                }
+
+                let normalization = &self.normalization;
+                let edge_bytes = self.masks.edge_bytes();
+
+                let mut node_index = 0;
+                let mut depth = 0;
+                loop {
+                    let search = match &self.nodes[node_index] {
+                        TrieState::Leaf(k, _) => {
+                            let order = normalization.cmp(k, key);
+                            if order.is_lt() || (inclusive && order.is_eq()) {
+                                stack.push(TrieNodeIter {
+                                    node_index,
+                                    stage: TrieNodeIterStage::Inner,
+                                });
+                            }
+                            break;
+                        }
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => search,
+                        TrieState::Run(run, next) => {
+                            match self.cmp_run(run, key, depth) {
+                                Ordering::Less => stack.push(TrieNodeIter {
+                                    node_index,
+                                    stage: TrieNodeIterStage::Inner,
+                                }),
+                                Ordering::Equal => {
+                                    node_index = *next;
+                                    depth += run.len();
+                                    continue;
+                                }
+                                Ordering::Greater => {}
+                            }
+                            break;
+                        }
+                    };
+                    let child_count = search.mask.count_ones() as usize;
+
+                    let Some(c) = key.get(depth) else {
+                        // Every key below this node is after `key`, except
+                        // the key of the node itself which is equal to it
+                        if inclusive {
+                            stack.push(TrieNodeIter {
+                                node_index,
+                                stage: TrieNodeIterStage::Child(0, child_count),
+                            });
+                        }
+                        break;
+                    };
+                    let c = normalization.byte(*c);
+
+                    // Count the children whose edges are before `c`
+                    let mut bits = search.mask;
+                    let mut before = 0;
+                    let mut equal = false;
+                    while bits != <$int_type>::default() {
+                        let b = normalization.byte(edge_bytes[bits.trailing_zeros() as usize]);
+                        if b >= c {
+                            equal = b == c;
+                            break;
+                        }
+                        bits = bits & (bits - 1);
+                        before += 1;
+                    }
+
+                    stack.push(TrieNodeIter {
+                        node_index,
+                        stage: TrieNodeIterStage::Child(before, child_count),
+                    });
+                    if !equal {
+                        break;
+                    }
+                    node_index = search.edge_start + before;
+                    depth += 1;
+                }
+
+                stack
+            }
 
-            /// Get the value stored for the given key. Any key type can be used
-            /// here as long as the type implements `AsRef<[u8]>`. The byte slice
-            /// referenced will serve as the actual key.
-            /// ```
-            /// # use trie_hard::TrieHard;
-            /// let trie = ["and", "ant", "dad", "do", "dot"]
-            ///     .into_iter()
-            ///     .collect::<TrieHard<'_, _>>();
-            ///
-            /// let TrieHard::U8(sized_trie) = trie else {
-            ///     unreachable!()
-            /// };
-            ///
-            /// assert!(sized_trie.get("dad".to_owned()).is_some());
-            /// assert!(sized_trie.get(b"do").is_some());
-            /// assert!(sized_trie.get(b"don't".to_vec()).is_none());
-            /// ```
-            pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<T> {
-                self.get_from_bytes(key.as_ref())
+            /// The last entry in the subtree below a node
+            fn last_entry(&self, mut node_index: usize) -> (&'a [u8], T) {
+                loop {
+                    match &self.nodes[node_index] {
+                        TrieState::Leaf(k, v) => return (k, *v),
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => {
+                            node_index = search.edge_start
+                                + search.mask.count_ones() as usize
+                                - 1;
+                        }
+                        TrieState::Run(_, next) => node_index = *next,
+                    }
+                }
             }
 
-            /// Get the value stored for the given byte-slice key.
+            /// Find the longest key in the trie that is a prefix of the given
+            /// input, along with its value.
+            ///
             /// ```
             /// # use trie_hard::TrieHard;
-            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            /// let trie = ["/", "/api", "/api/v1", "/static"]
             ///     .into_iter()
             ///     .collect::<TrieHard<'_, _>>();
             ///
-            /// let TrieHard::U8(sized_trie) = trie else {
+            /// let TrieHard::U16(sized_trie) = trie else {
             ///     unreachable!()
             /// };
             ///
-            /// assert!(sized_trie.get_from_bytes(b"dad").is_some());
-            /// assert!(sized_trie.get_from_bytes(b"do").is_some());
-            /// assert!(sized_trie.get_from_bytes(b"don't").is_none());
+            /// assert_eq!(
+            ///     sized_trie.longest_prefix("/api/v1/users").map(|(_, v)| v),
+            ///     Some("/api/v1")
+            /// );
+            /// assert!(sized_trie.longest_prefix("api").is_none());
             /// ```
-            pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
-                let mut state = self.nodes.get(0)?;
-
-                for (i, c) in key.iter().enumerate() {
+            pub fn longest_prefix<K: AsRef<[u8]>>(&self, input: K) -> Option<(&'a [u8], T)> {
+                let input = input.as_ref();
+                let mut state = self.nodes.first()?;
+                let mut longest = None;
 
+                let mut depth = 0;
+                while let Some(c) = input.get(depth) {
                     let next_state_opt = match state {
                         TrieState::Leaf(k, value) => {
-                            return (
-                                k.len() == key.len()
-                                && k[i..] == key[i..]
-                            ).then_some(*value)
+                            return if self.normalization.starts_with(input, k) {
+                                Some((*k, *value))
+                            } else {
+                                longest
+                            };
                         }
-                        TrieState::Search(search)
-                        | TrieState::SearchOrLeaf(_, _, search) => {
-                            search.evaluate(*c, self)
+                        TrieState::SearchOrLeaf(k, value, search) => {
+                            longest = Some((*k, *value));
+                            depth += 1;
+                            search.evaluate(*c, &self.masks)
+                        }
+                        TrieState::Search(search) => {
+                            depth += 1;
+                            search.evaluate(*c, &self.masks)
+                        }
+                        TrieState::Run(run, next) => {
+                            self.follow_run(run, input, depth).map(|next_depth| {
+                                depth = next_depth;
+                                *next
+                            })
                         }
                     };
 
                     if let Some(next_state_index) = next_state_opt {
                         state = &self.nodes[next_state_index];
                     } else {
-                        return None;
+                        return longest;
                     }
                 }
 
-                if let TrieState::Leaf(k, value)
-                    | TrieState::SearchOrLeaf(k, value, _) = state
-                {
-                    (k.len() == key.len()).then_some(*value)
-                } else {
-                    None
+                match state {
+                    TrieState::Leaf(k, value)
+                    | TrieState::SearchOrLeaf(k, value, _)
+                        if k.len() == input.len() =>
+                    {
+                        Some((*k, *value))
+                    }
+                    _ => longest,
                 }
             }
 
-            /// Create an iterator over the entire trie. Emitted items will be
-            /// ordered by their keys
+            /// Create an iterator over every key in the trie that is a prefix
+            /// of the given input. Emitted items will be ordered from shortest
+            /// to longest key.
             ///
             /// ```
             /// # use trie_hard::TrieHard;
-            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            /// let trie = ["/", "/api", "/api/v1", "/api/v2", "/static"]
             ///     .into_iter()
             ///     .collect::<TrieHard<'_, _>>();
             ///
-            /// let TrieHard::U8(sized_trie) = trie else {
+            /// let TrieHard::U16(sized_trie) = trie else {
             ///     unreachable!()
             /// };
             ///
             /// assert_eq!(
-            ///     sized_trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
-            ///     ["and", "ant", "dad", "do", "dot"]
+            ///     sized_trie.prefixes_of("/api/v2")
+            ///         .map(|(_, v)| v)
+            ///         .collect::<Vec<_>>(),
+            ///     ["/", "/api", "/api/v2"]
             /// );
             /// ```
-            pub fn iter(&self) -> TrieIterSized<'_, 'a, T, $int_type> {
-                TrieIterSized {
-                    stack: vec![TrieNodeIter::default()],
-                    trie: self
+            pub fn prefixes_of<'k, K: AsRef<[u8]> + ?Sized>(
+                &self,
+                input: &'k K
+            ) -> PrefixesIterSized<'_, 'a, 'k, T, $int_type> {
+                PrefixesIterSized {
+                    node_index: (!self.nodes.is_empty()).then_some(0),
+                    depth: 0,
+                    input: input.as_ref(),
+                    trie: self,
                 }
             }
 
+            /// Find every key within `max_distance` insertions, deletions or
+            /// substitutions of the given key, in key order and along with
+            /// their distance
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.fuzzy_search("dat", 1)
+            ///         .into_iter()
+            ///         .map(|(_, v, d)| (v, d))
+            ///         .collect::<Vec<_>>(),
+            ///     [("dad", 1), ("dot", 1)]
+            /// );
+            /// ```
+            pub fn fuzzy_search<K: AsRef<[u8]>>(
+                &self,
+                key: K,
+                max_distance: usize,
+            ) -> Vec<(&'a [u8], T, usize)> {
+                self.fuzzy(key.as_ref(), max_distance, false)
+            }
 
-            /// Create an iterator over the portion of the trie starting with the given
-            /// prefix
+            /// Like `fuzzy_search`, but swapping two adjacent bytes also
+            /// counts as a single edit
             ///
             /// ```
             /// # use trie_hard::TrieHard;
-            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
             ///     .into_iter()
             ///     .collect::<TrieHard<'_, _>>();
             ///
@@ -547,51 +2896,118 @@ macro_rules! trie_impls {
             /// };
             ///
             /// assert_eq!(
-            ///     sized_trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
-            ///     ["dad", "do", "dot"]
+            ///     sized_trie.fuzzy_search_damerau("nat", 1)
+            ///         .into_iter()
+            ///         .map(|(_, v, d)| (v, d))
+            ///         .collect::<Vec<_>>(),
+            ///     [("ant", 1)]
             /// );
             /// ```
-            pub fn prefix_search<K: AsRef<[u8]>>(&self, prefix: K) -> TrieIterSized<'_, 'a, T, $int_type> {
-                let key = prefix.as_ref();
-                let mut node_index = 0;
-                let Some(mut state) = self.nodes.get(node_index) else {
-                    return TrieIterSized::empty(self);
-                };
+            pub fn fuzzy_search_damerau<K: AsRef<[u8]>>(
+                &self,
+                key: K,
+                max_distance: usize,
+            ) -> Vec<(&'a [u8], T, usize)> {
+                self.fuzzy(key.as_ref(), max_distance, true)
+            }
 
-                for (i, c) in key.iter().enumerate() {
-                    let next_state_opt = match state {
-                        TrieState::Leaf(k, _) => {
-                            if k.len() == key.len() && k[i..] == key[i..] {
-                                return TrieIterSized::new(self, node_index);
-                            } else {
-                                return TrieIterSized::empty(self);
-                            }
+            fn fuzzy(
+                &self,
+                key: &[u8],
+                max_distance: usize,
+                transpositions: bool,
+            ) -> Vec<(&'a [u8], T, usize)> {
+                if self.nodes.is_empty() {
+                    return Vec::new();
+                }
+
+                let key = key.iter().map(|c| self.normalization.byte(*c)).collect();
+                let mut search = FuzzySearch::new(key, max_distance, transpositions);
+                self.fuzzy_node(0, &self.masks.edge_bytes(), &mut search);
+                search.found
+            }
+
+            fn fuzzy_node(
+                &self,
+                node_index: usize,
+                edge_bytes: &[u8],
+                search: &mut FuzzySearch<'a, T>,
+            ) {
+                match &self.nodes[node_index] {
+                    TrieState::Leaf(k, v) => {
+                        // The rest of the key is held by the leaf itself
+                        let rest = &k[search.path.len()..];
+                        let mut pushed = 0;
+                        let mut reachable = true;
+                        while reachable && pushed < rest.len() {
+                            reachable = search.push(self.normalization.byte(rest[pushed]));
+                            pushed += 1;
                         }
-                        TrieState::Search(search)
-                        | TrieState::SearchOrLeaf(_, _, search) => {
-                            search.evaluate(*c, self)
+                        if reachable {
+                            search.report(k, *v);
+                        }
+                        for _ in 0..pushed {
+                            search.pop();
+                        }
+                    }
+                    TrieState::Run(run, next) => {
+                        let mut pushed = 0;
+                        let mut reachable = true;
+                        while reachable && pushed < run.len() {
+                            reachable = search.push(self.normalization.byte(run[pushed]));
+                            pushed += 1;
+                        }
+                        if reachable {
+                            self.fuzzy_node(*next, edge_bytes, search);
+                        }
+                        for _ in 0..pushed {
+                            search.pop();
+                        }
+                    }
+                    TrieState::Search(node) | TrieState::SearchOrLeaf(_, _, node) => {
+                        if let TrieState::SearchOrLeaf(k, v, _) = &self.nodes[node_index] {
+                            search.report(k, *v);
+                        }
+                        for c in edge_bytes {
+                            let Some(child) = node.evaluate(*c, &self.masks) else {
+                                continue;
+                            };
+                            if search.push(self.normalization.byte(*c)) {
+                                self.fuzzy_node(child, edge_bytes, search);
+                            }
+                            search.pop();
                         }
-                    };
-
-                    if let Some(next_state_index) = next_state_opt {
-                        node_index = next_state_index;
-                        state = &self.nodes[next_state_index];
-                    } else {
-                        return TrieIterSized::empty(self);
                     }
                 }
-
-                TrieIterSized::new(self, node_index)
             }
         }
 
-        impl<'a, T> TrieHardSized<'a, T, $int_type> where T: 'a + Copy {
-            fn new(masks: MasksByByteSized<$int_type>, values: Vec<(&'a [u8], T)>) -> Self {
-                let values = values.into_iter().collect::<Vec<_>>();
-                let sorted = values
-                    .iter()
-                    .map(|(k, v)| (*k, *v))
-                    .collect::<BTreeMap<_, _>>();
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            fn new(
+                masks: MasksByByteSized<$int_type>,
+                values: Vec<(&'a [u8], T)>,
+                normalization: Normalization,
+            ) -> Self {
+                // The layout follows the order of the normalized keys, so keys
+                // that only differ by normalization end up in the same place
+                let normalized = match normalization {
+                    Normalization::None => Vec::new(),
+                    _ => values
+                        .iter()
+                        .map(|(k, _)| k.iter().map(|c| normalization.byte(*c)).collect())
+                        .collect::<Vec<Vec<u8>>>(),
+                };
+                let mut sorted = match normalization {
+                    Normalization::None => values
+                        .into_iter()
+                        .map(|(k, v)| (k, (k, v)))
+                        .collect::<BTreeMap<_, _>>(),
+                    _ => normalized
+                        .iter()
+                        .map(Vec::as_slice)
+                        .zip(values)
+                        .collect(),
+                };
 
                 let mut nodes = Vec::new();
                 let mut next_index = 1;
@@ -609,8 +3025,8 @@ macro_rules! trie_impls {
                     let (state, next_specs) = TrieState::<'_, _, $int_type>::new(
                         spec,
                         next_index,
-                        &masks.0,
-                        &sorted,
+                        &masks.by_byte,
+                        &mut sorted,
                     );
 
                     next_index += next_specs.len();
@@ -619,20 +3035,64 @@ macro_rules! trie_impls {
                 }
 
                 TrieHardSized {
-                    nodes,
+                    below: Self::count_below(&nodes).into(),
+                    nodes: nodes.into(),
                     masks,
+                    dead: 0,
+                    normalization,
                 }
             }
         }
 
 
-        impl <'a, T> TrieState<'a, T, $int_type> where T: 'a + Copy {
-            fn new(
-                spec: StateSpec<'a>,
+        impl <'a, T> TrieState<'a, T, $int_type> {
+            /// Indices of the children of the node
+            fn children(&self) -> Range<usize> {
+                match self {
+                    TrieState::Leaf(..) => 0..0,
+                    TrieState::Search(search)
+                    | TrieState::SearchOrLeaf(_, _, search) => search.children(),
+                    TrieState::Run(_, next) => *next..*next + 1,
+                }
+            }
+
+            /// The node reduced to what the shared walks need
+            fn walk_node(&self) -> WalkNode<'a> {
+                match self {
+                    TrieState::Leaf(key, _) => WalkNode::Leaf(Some(key)),
+                    TrieState::Search(search) => WalkNode::Search {
+                        entry: false,
+                        children: search.children(),
+                    },
+                    TrieState::SearchOrLeaf(_, _, search) => WalkNode::Search {
+                        entry: true,
+                        children: search.children(),
+                    },
+                    TrieState::Run(run, next) => WalkNode::Run(run, *next),
+                }
+            }
+
+            /// Index of the child that the byte `c` leads to, if this is a
+            /// search node
+            fn child(&self, c: u8, masks: &MasksByByteSized<$int_type>) -> Option<usize> {
+                match self {
+                    TrieState::Search(search)
+                    | TrieState::SearchOrLeaf(_, _, search) => search.evaluate(c, masks),
+                    TrieState::Leaf(..) | TrieState::Run(..) => None,
+                }
+            }
+
+            /// Create the state for the given spec. `sorted` maps normalized
+            /// keys to the original keys and their values. Values are moved out
+            /// of `sorted` as they are placed in a node, which is safe because
+            /// every key belongs to exactly one node and is never looked at
+            /// again after that node is created.
+            fn new<'n>(
+                spec: StateSpec<'n>,
                 edge_start: usize,
                 byte_masks: &[$int_type; 256],
-                sorted: &BTreeMap<&'a [u8], T>,
-            ) -> (Self, Vec<StateSpec<'a>>) {
+                sorted: &mut BTreeMap<&'n [u8], (&'a [u8], T)>,
+            ) -> (Self, Vec<StateSpec<'n>>) {
                 let StateSpec { prefix, .. } = spec;
 
                 let prefix_len = prefix.len();
@@ -640,17 +3100,19 @@ macro_rules! trie_impls {
 
                 let mut prefix_match = None;
                 let mut children_seen = 0;
+                let mut first_seen = None;
                 let mut last_seen = None;
 
                 let next_states_paired = sorted
                     .range(RangeFrom { start: prefix })
                     .take_while(|(key, _)| key.starts_with(prefix))
-                    .filter_map(|(key, val)| {
+                    .filter_map(|(key, (raw_key, _))| {
                         children_seen += 1;
-                        last_seen = Some((key, *val));
+                        first_seen.get_or_insert((*key, *raw_key));
+                        last_seen = Some(*key);
 
                         if *key == prefix {
-                            prefix_match = Some((key, *val));
+                            prefix_match = Some(*key);
                             None
                         } else {
                             // Safety: The byte at prefix_len must exist otherwise we
@@ -660,10 +3122,13 @@ macro_rules! trie_impls {
 
                             Some((
                                 *next_c,
-                                StateSpec {
-                                    prefix: next_prefix,
-                                    index: 0,
-                                },
+                                (
+                                    raw_key[prefix_len],
+                                    StateSpec {
+                                        prefix: next_prefix,
+                                        index: 0,
+                                    },
+                                ),
                             ))
                         }
                     })
@@ -673,15 +3138,36 @@ macro_rules! trie_impls {
 
                 // Safety: last_seen will be present because we saw at least one
                 //         entry must be present for this function to be called
-                let (last_k, last_v) = last_seen.unwrap();
+                let last_k = last_seen.unwrap();
 
-                if children_seen == 1 {
+                // Safety: Keys are only removed from `sorted` once their node
+                //         has been created, and that has not happened yet
+                if children_seen == 1 || next_states_paired.is_empty() {
+                    let (last_k, last_v) = sorted.remove(last_k).unwrap();
                     return (TrieState::Leaf(last_k, last_v), vec![]);
                 }
 
-                // No next_states means we hit a leaf node
-                if next_states_paired.is_empty() {
-                    return (TrieState::Leaf(last_k, last_v), vec![], );
+                // A chain of nodes with one child each is collapsed into a run
+                // holding the bytes shared by every key below it
+                if prefix_match.is_none() && next_states_paired.len() == 1 {
+                    // Safety: At least one key was seen, see above
+                    let (first_k, first_raw_k) = first_seen.unwrap();
+                    let run_len = first_k[prefix_len..]
+                        .iter()
+                        .zip(&last_k[prefix_len..])
+                        .take_while(|(a, b)| a == b)
+                        .count();
+
+                    if run_len >= 2 {
+                        let run_end = prefix_len + run_len;
+                        return (
+                            TrieState::Run(&first_raw_k[prefix_len..run_end], edge_start),
+                            vec![StateSpec {
+                                prefix: &first_k[..run_end],
+                                index: edge_start,
+                            }],
+                        );
+                    }
                 }
 
                 let mut mask = Default::default();
@@ -690,17 +3176,18 @@ macro_rules! trie_impls {
                 let next_state_specs = next_states_paired
                     .into_iter()
                     .enumerate()
-                    .map(|(i, (c, mut next_state))| {
+                    .map(|(i, (_, (raw_c, mut next_state)))| {
                         let next_node = edge_start + i;
                         next_state.index = next_node;
-                        mask |= byte_masks[c as usize];
+                        mask |= byte_masks[raw_c as usize];
                         next_state
                     })
                     .collect();
 
                 let search_node = SearchNode { mask, edge_start };
                 let state = match prefix_match {
-                    Some((key, value)) => {
+                    Some(key) => {
+                        let (key, value) = sorted.remove(key).unwrap();
                         TrieState::SearchOrLeaf(key, value, search_node)
                     }
                     _ => TrieState::Search(search_node),
@@ -711,7 +3198,7 @@ macro_rules! trie_impls {
         }
 
         impl MasksByByteSized<$int_type> {
-            fn new(used_bytes: BTreeSet<u8>) -> Self {
+            fn new(used_bytes: BTreeSet<u8>, normalization: &Normalization) -> Self {
                 let mut mask = Default::default();
                 mask += 1;
 
@@ -721,114 +3208,437 @@ macro_rules! trie_impls {
                     byte_masks[c as usize] = mask;
                     mask <<= 1;
 
-                }
+                }
+
+                // Every byte shares the mask of the byte it normalizes to
+                if *normalization != Normalization::None {
+                    let normalized_masks = byte_masks;
+                    for (c, byte_mask) in byte_masks.iter_mut().enumerate() {
+                        *byte_mask = normalized_masks[normalization.byte(c as u8) as usize];
+                    }
+                }
+
+                Self::from_byte_masks(byte_masks)
+            }
+
+            /// Wrap the mask of each byte along with the byte for each bit
+            fn from_byte_masks(by_byte: [$int_type; 256]) -> Self {
+                let mut edge_bytes = [0; 256];
+                let mut edge_count = 0;
+                // Going down leaves the lowest byte with each mask
+                for (c, mask) in by_byte.iter().enumerate().rev() {
+                    if *mask != <$int_type>::default() {
+                        let bit = mask.trailing_zeros() as usize;
+                        edge_bytes[bit] = c as u8;
+                        edge_count = edge_count.max(bit + 1);
+                    }
+                }
+
+                Self { by_byte, edge_bytes, edge_count }
+            }
+        }
+
+        impl <'b, 'a, 'k, T> Iterator for PrefixesIterSized<'b, 'a, 'k, T, $int_type>
+        where
+            T: Copy
+        {
+            type Item = (&'a [u8], T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                while let Some(node_index) = self.node_index {
+                    let (found, search) = match &self.trie.nodes[node_index] {
+                        TrieState::Leaf(k, value) => {
+                            self.node_index = None;
+                            return self.trie.normalization
+                                .starts_with(self.input, k)
+                                .then_some((*k, *value));
+                        }
+                        TrieState::SearchOrLeaf(k, value, search) => {
+                            (Some((*k, *value)), search)
+                        }
+                        TrieState::Search(search) => (None, search),
+                        TrieState::Run(run, next) => {
+                            let next_depth = self.trie.follow_run(run, self.input, self.depth);
+                            self.node_index = next_depth.map(|_| *next);
+                            self.depth = next_depth.unwrap_or(self.depth);
+                            continue;
+                        }
+                    };
+
+                    self.node_index = self.input
+                        .get(self.depth)
+                        .and_then(|c| search.evaluate(*c, &self.trie.masks));
+                    self.depth += 1;
+
+                    if found.is_some() {
+                        return found;
+                    }
+                }
+
+                None
+            }
+        }
+
+        impl <'b, 'a, T> Iterator for TrieIterSized<'b, 'a, T, $int_type>
+        where
+            T: Copy
+        {
+            type Item = (&'a [u8], T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let (key, value) = self.next_entry()?;
+                let met = self.back_key.is_some_and(|back| {
+                    self.trie.normalization.cmp(key, back).is_ge()
+                });
+                if met || !self.before_end(key) {
+                    self.stack.clear();
+                    return None;
+                }
+                self.front_key = Some(key);
+                Some((key, value))
+            }
+        }
+
+        impl <'b, 'a, T> DoubleEndedIterator for TrieIterSized<'b, 'a, T, $int_type>
+        where
+            T: Copy
+        {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                let (key, value) = self.next_back_entry()?;
+                let met = self.front_key.is_some_and(|front| {
+                    self.trie.normalization.cmp(key, front).is_le()
+                });
+                if met || !self.after_start(key) {
+                    self.back.clear();
+                    return None;
+                }
+                self.back_key = Some(key);
+                Some((key, value))
+            }
+        }
+
+        impl <'b, 'a, T> TrieIterSized<'b, 'a, T, $int_type>
+        where
+            T: Copy
+        {
+            fn next_back_entry(&mut self) -> Option<(&'a [u8], T)> {
+                let node_index = walk::next_back_entry(self.trie, &mut self.back)?;
+                self.trie.entry(node_index)
+            }
+
+            fn next_entry(&mut self) -> Option<(&'a [u8], T)> {
+                let node_index = walk::next_entry(self.trie, &mut self.stack)?;
+                self.trie.entry(node_index)
+            }
+        }
+    }
+}
+
+trie_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[test]
+    fn test_trivial() {
+        let empty: Vec<&str> = vec![];
+        let empty_trie = empty.iter().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(None, empty_trie.get("anything"));
+    }
+
+    #[rstest]
+    #[case("", Some(""))]
+    #[case("a", Some("a"))]
+    #[case("ab", Some("ab"))]
+    #[case("abc", None)]
+    #[case("aac", Some("aac"))]
+    #[case("aa", None)]
+    #[case("aab", None)]
+    #[case("adddd", Some("adddd"))]
+    fn test_small_get(#[case] key: &str, #[case] expected: Option<&str>) {
+        let trie = ["", "a", "ab", "aac", "adddd", "addde"]
+            .into_iter()
+            .collect::<TrieHard<'_, _>>();
+        assert_eq!(expected, trie.get(key));
+    }
+
+    #[test]
+    fn test_skip_to_leaf() {
+        let trie = ["a", "aa", "aaa"].into_iter().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(trie.get("aa"), Some("aa"))
+    }
+
+    #[test]
+    fn test_move_only_values() {
+        #[derive(Debug, PartialEq)]
+        struct MoveOnly(String);
+
+        let mut trie = TrieHard::new(vec![
+            (&b"and"[..], MoveOnly("and".to_owned())),
+            (&b"ant"[..], MoveOnly("ant".to_owned())),
+            (&b"an"[..], MoveOnly("an".to_owned())),
+            (&b"and"[..], MoveOnly("and again".to_owned())),
+        ]);
+
+        assert_eq!(trie.get_ref("an"), Some(&MoveOnly("an".to_owned())));
+        assert_eq!(
+            trie.get_ref("and"),
+            Some(&MoveOnly("and again".to_owned()))
+        );
+        assert_eq!(trie.get_ref("a"), None);
+
+        trie.get_mut("ant").unwrap().0.push('s');
+        assert_eq!(trie.get_ref("ant"), Some(&MoveOnly("ants".to_owned())));
+        assert!(trie.get_mut("ants").is_none());
+    }
+
+    /// Check every lookup against the expected entries, including keys that
+    /// were removed along the way
+    fn assert_entries(
+        trie: &TrieHard<'_, usize>,
+        expected: &BTreeMap<&[u8], usize>,
+        all_keys: &[&[u8]],
+    ) {
+        for key in all_keys {
+            assert_eq!(trie.get(key), expected.get(key).copied());
+        }
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            expected.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    #[case(&["and", "ant", "dad", "do", "dot"], "an", &["an", "and", "ant", "dad", "do", "dot"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dots", &["and", "ant", "dad", "do", "dot", "dots"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dan", &["and", "ant", "dad", "dan", "do", "dot"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "", &["", "and", "ant", "dad", "do", "dot"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "zoo", &["and", "ant", "dad", "do", "dot", "zoo"])]
+    #[case(&["abcdef"], "abcxyz", &["abcdef", "abcxyz"])]
+    #[case(&["abcdef"], "abc", &["abc", "abcdef"])]
+    #[case(&["abc"], "abcdef", &["abc", "abcdef"])]
+    #[case(&[], "a", &["a"])]
+    #[case(&[], "", &[""])]
+    fn test_insert(
+        #[case] input: &[&str],
+        #[case] key: &'static str,
+        #[case] output: &[&str],
+    ) {
+        let mut trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(trie.insert(key, key), None);
+        assert_eq!(trie.get(key), Some(key));
+
+        let mut sorted = output.to_vec();
+        sorted.sort();
+        assert_eq!(trie.iter().map(|(_, v)| v).collect::<Vec<_>>(), sorted);
+
+        assert_eq!(trie.insert(key, "again"), Some(key));
+        assert_eq!(trie.get(key), Some("again"));
+    }
+
+    #[test]
+    fn test_insert_grows_width() {
+        let mut trie = TrieHard::new(vec![(&b"a"[..], 0)]);
+        assert!(matches!(trie, TrieHard::U8(_)));
+
+        let keys = (0..32).map(|b| [b'a' + b]).collect::<Vec<_>>();
+        for (i, key) in keys.iter().enumerate() {
+            trie.insert(key, i);
+        }
+
+        assert!(matches!(trie, TrieHard::U32(_)));
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(trie.get(key), Some(i));
+        }
+    }
+
+    #[rstest]
+    #[case(&["and", "ant", "dad", "do", "dot"], "ant", &["and", "dad", "do", "dot"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "do", &["and", "ant", "dad", "dot"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dot", &["and", "ant", "dad", "do"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "dad", &["and", "ant", "do", "dot"])]
+    #[case(&["", "a", "aaa"], "aaa", &["", "a"])]
+    #[case(&["", "a", "aaa"], "", &["a", "aaa"])]
+    #[case(&["abc"], "abc", &[])]
+    fn test_remove(
+        #[case] input: &[&str],
+        #[case] key: &str,
+        #[case] output: &[&str],
+    ) {
+        let mut trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(trie.remove(key), Some(key));
+        assert_eq!(trie.remove(key), None);
+        assert_eq!(trie.get(key), None);
+        assert_eq!(trie.iter().map(|(_, v)| v).collect::<Vec<_>>(), output);
+
+        for word in output {
+            assert_eq!(trie.get(word), Some(*word));
+        }
+    }
+
+    const HEADERS: [&str; 4] = [
+        "x-cloudflare-request-id",
+        "x-cloudflare-ray-id",
+        "x-forwarded-for",
+        "x-forwarded-proto",
+    ];
 
-                Self(byte_masks)
-            }
-        }
+    #[test]
+    fn test_runs() {
+        let trie = HEADERS.into_iter().collect::<TrieHard<'_, _>>();
+        let TrieHard::U32(sized) = &trie else {
+            unreachable!()
+        };
 
-        impl <'b, 'a, T> Iterator for TrieIterSized<'b, 'a, T, $int_type>
-        where
-            T: Copy
-        {
-            type Item = (&'a [u8], T);
+        // The shared "x-", "loudflare-r" and "orwarded-" infixes are each
+        // held by a single node
+        let runs = sized
+            .nodes
+            .iter()
+            .filter_map(|state| match state {
+                TrieState::Run(run, _) => Some(*run),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(runs, [&b"x-"[..], b"loudflare-r", b"orwarded-"]);
+        assert_eq!(sized.nodes.len(), 10);
 
-            fn next(&mut self) -> Option<Self::Item> {
+        for key in HEADERS {
+            assert_eq!(trie.get(key), Some(key));
+        }
+        for key in ["x-", "x-cloud", "x-cloudflare-re", "x-forwarded-forx", "y"]
+        {
+            assert_eq!(trie.get(key), None);
+        }
+        assert_eq!(
+            trie.prefix_search("x-cloudfl")
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>(),
+            ["x-cloudflare-ray-id", "x-cloudflare-request-id"]
+        );
+        assert_eq!(trie.prefix_search("x-cloudflx").count(), 0);
+    }
 
-                use TrieState as T;
-                use TrieNodeIterStage as S;
+    #[rstest]
+    #[case("x-")]
+    #[case("x-cl")]
+    #[case("x-cloudflare-")]
+    #[case("x-clo")]
+    #[case("x-cloudflare-rz")]
+    #[case("x-forwarded-fo")]
+    #[case("x-forwarded-form")]
+    #[case("x-forwardeq")]
+    #[case("x")]
+    fn test_insert_splits_run(#[case] key: &'static str) {
+        let mut trie = HEADERS.into_iter().collect::<TrieHard<'_, _>>();
 
-                while let Some((node, node_index, stage)) = self.stack.pop()
-                    .and_then(|TrieNodeIter { node_index, stage }| {
-                        self.trie.nodes.get(node_index).map(|node| (node, node_index, stage))
-                    })
-                {
-                    match (node, stage) {
-                        (T::Leaf(key, value), S::Inner) => return Some((*key, *value)),
-                        (T::SearchOrLeaf(key, value, search), S::Inner) => {
-                            self.stack.push(TrieNodeIter {
-                                node_index,
-                                stage: TrieNodeIterStage::Child(0, search.mask.count_ones() as usize)
-                            });
-                            self.stack.push(TrieNodeIter {
-                                node_index: search.edge_start,
-                                stage: Default::default()
-                            });
-                            return Some((*key, *value));
-                        }
-                        (T::Search(search), S::Inner) => {
-                            self.stack.push(TrieNodeIter {
-                                node_index,
-                                stage: TrieNodeIterStage::Child(0, search.mask.count_ones() as usize)
-                            });
-                            self.stack.push(TrieNodeIter {
-                                node_index: search.edge_start,
-                                stage: Default::default()
-                            });
-                        }
-                        (
-                            T::SearchOrLeaf(_, _, search) | T::Search(search),
-                            S::Child(mut child, child_count)
-                        ) => {
-                            child += 1;
-                            if child < child_count {
-                                self.stack.push(TrieNodeIter {
-                                    node_index,
-                                    stage: TrieNodeIterStage::Child(child, child_count)
-                                });
-                                self.stack.push(TrieNodeIter {
-                                    node_index: search.edge_start + child,
-                                    stage: Default::default()
-                                });
-                            }
-                        }
-                        _ => unreachable!()
-                    }
-                }
+        assert_eq!(trie.insert(key, key), None);
 
-                None
-            }
+        let mut expected = HEADERS.to_vec();
+        expected.push(key);
+        expected.sort();
+        for key in &expected {
+            assert_eq!(trie.get(key), Some(*key));
         }
+        assert_eq!(trie.iter().map(|(_, v)| v).collect::<Vec<_>>(), expected);
     }
-}
 
-trie_impls! {u8, u16, u32, u64, u128, U256}
+    #[rstest]
+    #[case("x-cloudflare-request-id")]
+    #[case("x-forwarded-for")]
+    fn test_remove_through_run(#[case] key: &str) {
+        let mut trie = HEADERS.into_iter().collect::<TrieHard<'_, _>>();
 
-#[cfg(test)]
-mod tests {
-    use rstest::rstest;
+        assert_eq!(trie.remove(key), Some(key));
+        assert_eq!(trie.get(key), None);
 
-    use super::*;
+        let expected = HEADERS
+            .into_iter()
+            .filter(|k| *k != key)
+            .collect::<BTreeSet<_>>();
+        for key in &expected {
+            assert_eq!(trie.get(key), Some(*key));
+        }
+        assert!(trie.iter().map(|(_, v)| v).eq(expected));
+    }
 
     #[test]
-    fn test_trivial() {
-        let empty: Vec<&str> = vec![];
-        let empty_trie = empty.iter().collect::<TrieHard<'_, _>>();
+    fn test_remove_counts_dead_slots() {
+        let mut trie = HEADERS.into_iter().collect::<TrieHard<'_, _>>();
 
-        assert_eq!(None, empty_trie.get("anything"));
+        // The second removal empties the search node below the
+        // "loudflare-r" run, so the run goes with it
+        for key in ["x-cloudflare-request-id", "x-cloudflare-ray-id"] {
+            assert_eq!(trie.remove(key), Some(key));
+
+            let TrieHard::U32(sized) = &trie else {
+                unreachable!()
+            };
+            let mut reachable = vec![0];
+            let mut next = 0;
+            while let Some(&node_index) = reachable.get(next) {
+                next += 1;
+                reachable.extend(sized.nodes[node_index].children());
+            }
+            assert_eq!(sized.nodes.len() - sized.dead, reachable.len());
+        }
     }
 
     #[rstest]
-    #[case("", Some(""))]
-    #[case("a", Some("a"))]
-    #[case("ab", Some("ab"))]
-    #[case("abc", None)]
-    #[case("aac", Some("aac"))]
-    #[case("aa", None)]
-    #[case("aab", None)]
-    #[case("adddd", Some("adddd"))]
-    fn test_small_get(#[case] key: &str, #[case] expected: Option<&str>) {
-        let trie = ["", "a", "ab", "aac", "adddd", "addde"]
-            .into_iter()
-            .collect::<TrieHard<'_, _>>();
-        assert_eq!(expected, trie.get(key));
+    #[case(&["and", "ant"], "an")]
+    #[case(&["and", "ant"], "ants")]
+    #[case(&["and", "ant"], "b")]
+    #[case(&["and", "ant"], "")]
+    #[case(&[], "")]
+    fn test_remove_missing(#[case] input: &[&str], #[case] key: &str) {
+        let mut trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+
+        assert_eq!(trie.remove(key), None);
+        assert_eq!(trie.iter().map(|(_, v)| v).collect::<Vec<_>>(), input);
     }
 
     #[test]
-    fn test_skip_to_leaf() {
-        let trie = ["a", "aa", "aaa"].into_iter().collect::<TrieHard<'_, _>>();
+    fn test_incremental_matches_bulk() {
+        let words = include_str!("../data/sun-rising.txt")
+            .split(|c: char| c.is_whitespace())
+            .map(str::as_bytes)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
 
-        assert_eq!(trie.get("aa"), Some("aa"))
+        let mut trie = TrieHard::default();
+        let mut expected = BTreeMap::new();
+
+        // Deterministic shuffle of inserts and removes
+        let mut seed = 7_usize;
+        for step in 0..words.len() * 3 {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            let key = words[(seed >> 33) % words.len()];
+
+            if step % 3 == 2 {
+                assert_eq!(trie.remove(key), expected.remove(key));
+            } else {
+                assert_eq!(trie.insert(key, step), expected.insert(key, step));
+            }
+
+            if step % 50 == 0 {
+                assert_entries(&trie, &expected, &words);
+            }
+        }
+
+        assert_entries(&trie, &expected, &words);
+
+        for key in &words {
+            assert_eq!(trie.remove(key), expected.remove(key));
+        }
+        assert_entries(&trie, &expected, &words);
     }
 
     #[rstest]
@@ -902,6 +3712,53 @@ mod tests {
         assert_eq!(emitted, output);
     }
 
+    #[rstest]
+    #[case(&[], "abc", None)]
+    #[case(&[""], "abc", Some(""))]
+    #[case(&["a", "aaa"], "", None)]
+    #[case(&["a", "aaa"], "a", Some("a"))]
+    #[case(&["a", "aaa"], "aa", Some("a"))]
+    #[case(&["a", "aaa"], "aaa", Some("aaa"))]
+    #[case(&["a", "aaa"], "aaaa", Some("aaa"))]
+    #[case(&["a", "aaa"], "aab", Some("a"))]
+    #[case(&["a", "aaa"], "b", None)]
+    #[case(&["abc"], "abd", None)]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "adddx", Some("a"))]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "xyz", Some(""))]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "adddde", Some("adddd"))]
+    fn test_longest_prefix(
+        #[case] input: &[&str],
+        #[case] key: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let found = trie.longest_prefix(key);
+
+        assert_eq!(found.map(|(_, v)| v), expected);
+        assert_eq!(found.map(|(k, _)| k), expected.map(str::as_bytes));
+    }
+
+    #[rstest]
+    #[case(&[], "abc", &[])]
+    #[case(&[""], "abc", &[""])]
+    #[case(&["a", "aaa"], "", &[])]
+    #[case(&["a", "aaa"], "aa", &["a"])]
+    #[case(&["a", "aaa"], "aaaa", &["a", "aaa"])]
+    #[case(&["a", "aaa"], "aab", &["a"])]
+    #[case(&["abc"], "abd", &[])]
+    #[case(&["abc"], "abcd", &["abc"])]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "adddde", &["", "a", "adddd"])]
+    #[case(&["/", "/api", "/api/v1", "/apis"], "/api/v1/users/42", &["/", "/api", "/api/v1"])]
+    fn test_prefixes_of(
+        #[case] input: &[&str],
+        #[case] key: &str,
+        #[case] output: &[&str],
+    ) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let emitted = trie.prefixes_of(key).map(|(_, v)| v).collect::<Vec<_>>();
+        assert_eq!(emitted, output);
+    }
+
     #[rstest]
     #[case(&[], "", &[])]
     #[case(&[""], "", &[""])]
@@ -911,6 +3768,7 @@ mod tests {
     #[case(&["aaa", "a", ""], "aab", &[])]
     #[case(&["aaa", "a", ""], "aaa", &["aaa"])]
     #[case(&["aaa", "a", ""], "b", &[])]
+    #[case(&["abc", "x"], "ab", &["abc"])]
     #[case(&["dad", "ant", "and", "dot", "do"], "d", &["dad", "do", "dot"])]
     fn test_prefix_search(
         #[case] input: &[&str],
@@ -924,4 +3782,501 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(emitted, output);
     }
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&[""], "")]
+    #[case(&["aaa", "a", ""], "")]
+    #[case(&["aaa", "a", ""], "a")]
+    #[case(&["aaa", "a", ""], "aab")]
+    #[case(&["abc", "x"], "ab")]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "a")]
+    #[case(&["dad", "ant", "and", "dot", "do"], "d")]
+    fn test_prefix_search_rev(#[case] input: &[&str], #[case] prefix: &str) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let mut expected = trie
+            .prefix_search(prefix)
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>();
+        expected.reverse();
+        let emitted = trie
+            .prefix_search(prefix)
+            .rev()
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, expected);
+    }
+
+    #[rstest]
+    #[case(&[], 0)]
+    #[case(&[""], 1)]
+    #[case(&["aaa", "a", ""], 1)]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], 2)]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], 3)]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], 6)]
+    fn test_iter_both_ends(#[case] input: &[&str], #[case] front: usize) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let words = trie.iter().map(|(_, v)| v).collect::<Vec<_>>();
+
+        let mut iter = trie.iter().map(|(_, v)| v);
+        let head = iter.by_ref().take(front).collect::<Vec<_>>();
+        let mut tail = iter.rev().collect::<Vec<_>>();
+        tail.reverse();
+
+        assert_eq!(head, words[..front.min(words.len())]);
+        assert_eq!([head, tail].concat(), words);
+    }
+
+    #[test]
+    fn test_iter_alternating() {
+        let trie = ["", "a", "ab", "aac", "adddd", "addde", "b"]
+            .into_iter()
+            .collect::<TrieHard<'_, _>>();
+        let mut iter = trie.iter().map(|(_, v)| v);
+
+        assert_eq!(iter.next(), Some(""));
+        assert_eq!(iter.next_back(), Some("b"));
+        assert_eq!(iter.next_back(), Some("addde"));
+        assert_eq!(iter.next(), Some("a"));
+        assert_eq!(iter.next(), Some("aac"));
+        assert_eq!(iter.next_back(), Some("adddd"));
+        assert_eq!(iter.next(), Some("ab"));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    fn folded<'a>(keys: &[&'a str]) -> TrieHard<'a, &'a str> {
+        TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(keys.iter().map(|k| (k.as_bytes(), *k)).collect())
+    }
+
+    #[rstest]
+    #[case("content-type", Some("Content-Type"))]
+    #[case("CONTENT-TYPE", Some("Content-Type"))]
+    #[case("cOnTeNt-LeNgTh", Some("content-length"))]
+    #[case("host", Some("HOST"))]
+    #[case("content", None)]
+    #[case("content-typo", None)]
+    fn test_ascii_case_insensitive_get(
+        #[case] key: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let trie = folded(&["Content-Type", "content-length", "HOST"]);
+        assert_eq!(trie.get(key), expected);
+    }
+
+    #[test]
+    fn test_ascii_case_insensitive_width() {
+        let keys = ["ab", "AB", "Ab"];
+        assert!(matches!(folded(&keys), TrieHard::U8(_)));
+
+        let lower = (b'a'..=b'z').map(|b| b as char).collect::<String>();
+        let upper = lower.to_ascii_uppercase();
+        let words = lower
+            .split("")
+            .chain(upper.split(""))
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>();
+        assert!(matches!(folded(&words), TrieHard::U32(_)));
+        assert!(matches!(
+            words.iter().copied().collect::<TrieHard<'_, _>>(),
+            TrieHard::U64(_)
+        ));
+    }
+
+    #[rstest]
+    #[case("Co", &["content-length", "Content-Type"], None)]
+    #[case("CONTENT-T", &["Content-Type"], None)]
+    #[case("CONTENT-typeX", &[], Some("Content-Type"))]
+    #[case("h", &["HOST"], None)]
+    #[case("x", &[], None)]
+    fn test_ascii_case_insensitive_prefixes(
+        #[case] key: &str,
+        #[case] search: &[&str],
+        #[case] longest: Option<&str>,
+    ) {
+        let trie = folded(&["Content-Type", "content-length", "HOST"]);
+        let found = trie.prefix_search(key).map(|(_, v)| v).collect::<Vec<_>>();
+        assert_eq!(found, search);
+        assert_eq!(trie.longest_prefix(key).map(|(_, v)| v), longest);
+    }
+
+    #[test]
+    fn test_ascii_case_insensitive_insert_remove() {
+        let mut trie = folded(&["Accept", "age"]);
+
+        assert_eq!(trie.insert("ACCEPT", "ACCEPT"), Some("Accept"));
+        assert_eq!(trie.get("accept"), Some("ACCEPT"));
+        assert_eq!(
+            trie.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            [&b"ACCEPT"[..], b"age"]
+        );
+
+        // Z is not used by any key yet, so the trie is rebuilt
+        assert_eq!(trie.insert("Zone", "Zone"), None);
+        assert_eq!(trie.get("zONE"), Some("Zone"));
+        assert_eq!(trie.get("AGE"), Some("age"));
+
+        assert_eq!(trie.remove("aGe"), Some("age"));
+        assert_eq!(trie.get("age"), None);
+        assert_eq!(
+            trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            ["ACCEPT", "Zone"]
+        );
+    }
+
+    fn dash_table() -> [u8; 256] {
+        let mut table: [u8; 256] = std::array::from_fn(|c| c as u8);
+        table[b'_' as usize] = b'-';
+        table
+    }
+
+    #[rstest]
+    #[case("x-real-ip", Some(0))]
+    #[case("x_real_ip", Some(0))]
+    #[case("x_real-ip", Some(0))]
+    #[case("X-Real-IP", None)]
+    #[case("x-real_", None)]
+    fn test_normalization_table_get(
+        #[case] key: &str,
+        #[case] expected: Option<usize>,
+    ) {
+        let trie = TrieHardBuilder::new()
+            .normalization_table(dash_table())
+            .build(vec![(&b"x-real-ip"[..], 0), (b"x_real_id", 1)]);
+        assert_eq!(trie.get(key), expected);
+        assert_eq!(trie.get("x-real-id"), Some(1));
+    }
+
+    #[test]
+    fn test_normalization_table_width() {
+        // Map all 256 bytes onto 8 classes
+        let table = std::array::from_fn(|c| (c % 8) as u8);
+        let keys = (0..=255).map(|b| [b]).collect::<Vec<_>>();
+        let trie = TrieHardBuilder::new()
+            .normalization_table(table)
+            .build(keys.iter().map(|k| (&k[..], k[0])).collect());
+
+        assert!(matches!(trie, TrieHard::U8(_)));
+        assert_eq!(trie.iter().count(), 8);
+        for b in 0..=255u8 {
+            assert_eq!(trie.get([b]).map(|v| v % 8), Some(b % 8));
+        }
+    }
+
+    #[test]
+    fn test_normalization_table_insert() {
+        let mut trie = TrieHardBuilder::new()
+            .normalization_table(dash_table())
+            .build(vec![(&b"a-b"[..], 0)]);
+
+        assert_eq!(trie.insert("a_b", 1), Some(0));
+        assert_eq!(trie.insert("c_d", 2), None);
+        assert_eq!(trie.get("c-d"), Some(2));
+        assert_eq!(trie.remove("a-b"), Some(1));
+        assert_eq!(trie.iter().map(|(k, _)| k).collect::<Vec<_>>(), [b"c_d"]);
+    }
+
+    /// Edit distance computed over the full table, for checking fuzzy search
+    fn edit_distance(a: &[u8], b: &[u8], transpositions: bool) -> usize {
+        let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+        for (i, row) in d.iter_mut().enumerate() {
+            row[0] = i;
+        }
+        d[0] = (0..=b.len()).collect();
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                let cost = usize::from(a[i - 1] != b[j - 1]);
+                d[i][j] = (d[i - 1][j] + 1)
+                    .min(d[i][j - 1] + 1)
+                    .min(d[i - 1][j - 1] + cost);
+                if transpositions
+                    && i > 1
+                    && j > 1
+                    && a[i - 1] == b[j - 2]
+                    && a[i - 2] == b[j - 1]
+                {
+                    d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+                }
+            }
+        }
+        d[a.len()][b.len()]
+    }
+
+    #[rstest]
+    #[case("", 0)]
+    #[case("", 2)]
+    #[case("the", 1)]
+    #[case("teh", 1)]
+    #[case("ebcause", 2)]
+    #[case("rtuh", 2)]
+    #[case("xxxxxxxxxxxxxxxxxxxx", 3)]
+    fn test_fuzzy_search(#[case] key: &str, #[case] max_distance: usize) {
+        let text = include_str!("../data/sun-rising.txt");
+        let trie = text.split_whitespace().collect::<TrieHard<'_, _>>();
+
+        for transpositions in [false, true] {
+            let expected = trie
+                .iter()
+                .map(|(k, v)| {
+                    (k, v, edit_distance(k, key.as_bytes(), transpositions))
+                })
+                .filter(|(_, _, d)| *d <= max_distance)
+                .collect::<Vec<_>>();
+            let found = if transpositions {
+                trie.fuzzy_search_damerau(key, max_distance)
+            } else {
+                trie.fuzzy_search(key, max_distance)
+            };
+            assert_eq!(found, expected, "{transpositions}");
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_search_normalized() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"Content-Type"[..], 0)]);
+
+        assert_eq!(
+            trie.fuzzy_search("CONTENT_TYPE", 1),
+            [(&b"Content-Type"[..], 0, 1)]
+        );
+        assert!(TrieHard::new(Vec::<(&[u8], ())>::new())
+            .fuzzy_search("", 3)
+            .is_empty());
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("a")]
+    #[case("and")]
+    #[case("andy")]
+    #[case("b")]
+    #[case("do")]
+    #[case("doe")]
+    #[case("dot")]
+    #[case("dots")]
+    #[case("z")]
+    fn test_ordered_navigation(#[case] key: &str) {
+        let words = ["", "an", "and", "ant", "dad", "do", "dot", "dz"];
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let value =
+            |entry: Option<(&[u8], &'static str)>| entry.map(|(_, v)| v);
+
+        assert_eq!(
+            value(trie.lower_bound(key)),
+            words.iter().copied().find(|w| *w >= key)
+        );
+        assert_eq!(
+            value(trie.successor(key)),
+            words.iter().copied().find(|w| *w > key)
+        );
+        assert_eq!(
+            value(trie.predecessor(key)),
+            words.iter().copied().rev().find(|w| *w < key)
+        );
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/1984.txt"))]
+    fn test_range_full_text(#[case] text: &str) {
+        let trie = text.split_whitespace().collect::<TrieHard<'_, _>>();
+        let words = trie.iter().map(|(_, v)| v).collect::<Vec<_>>();
+
+        for (i, start) in words.iter().enumerate().step_by(37) {
+            let end = words[(i * 7) % words.len()];
+            let expected = words
+                .iter()
+                .copied()
+                .filter(|w| w >= start && *w < end)
+                .collect::<Vec<_>>();
+            let found =
+                trie.range(*start..end).map(|(_, v)| v).collect::<Vec<_>>();
+            assert_eq!(found, expected);
+
+            let found = trie
+                .range(*start..=end)
+                .rev()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>();
+            let expected = words
+                .iter()
+                .copied()
+                .filter(|w| w >= start && *w <= end)
+                .rev()
+                .collect::<Vec<_>>();
+            assert_eq!(found, expected);
+
+            let shifted = format!("{start}\0");
+            assert_eq!(
+                trie.range(shifted.as_str()..).map(|(_, v)| v).next(),
+                words.get(i + 1).copied()
+            );
+            assert_eq!(
+                trie.successor(start).map(|(_, v)| v),
+                words.get(i + 1).copied()
+            );
+            assert_eq!(
+                trie.predecessor(start).map(|(_, v)| v),
+                i.checked_sub(1).map(|i| words[i])
+            );
+        }
+    }
+
+    #[test]
+    fn test_range_normalized() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"Alpha"[..], 0), (b"beta", 1), (b"GAMMA", 2)]);
+
+        assert_eq!(
+            trie.range("ALPHA".."Gamma")
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>(),
+            [0, 1]
+        );
+        assert_eq!(
+            trie.range("B"..="gamma")
+                .rev()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>(),
+            [2, 1]
+        );
+        assert_eq!(trie.lower_bound("B").map(|(_, v)| v), Some(1));
+        assert_eq!(trie.predecessor("c").map(|(_, v)| v), Some(1));
+    }
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&[""])]
+    #[case(&["aaa", "a", ""])]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"])]
+    #[case(&["dad", "ant", "and", "dot", "do"])]
+    fn test_rank_select(#[case] input: &[&str]) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let words = trie.iter().map(|(_, v)| v).collect::<Vec<_>>();
+
+        assert_eq!(trie.len(), words.len());
+        assert_eq!(trie.is_empty(), words.is_empty());
+        for (i, word) in words.iter().enumerate() {
+            assert_eq!(trie.rank(word), Some(i));
+            assert_eq!(trie.select(i).map(|(_, v)| v), Some(*word));
+        }
+        assert_eq!(trie.select(words.len()), None);
+        assert_eq!(trie.rank("aa"), None);
+        assert_eq!(trie.rank("aaaa"), None);
+        assert_eq!(trie.rank("x"), None);
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/1984.txt"))]
+    fn test_rank_select_full_text(#[case] text: &str) {
+        let mut trie = text.split_whitespace().collect::<TrieHard<'_, _>>();
+        let mut words = trie.iter().map(|(_, v)| v).collect::<Vec<_>>();
+
+        for (i, word) in words.iter().enumerate().step_by(13) {
+            assert_eq!(trie.rank(word), Some(i));
+            assert_eq!(trie.select(i).map(|(_, v)| v), Some(*word));
+        }
+
+        // The counts have to follow changes to the trie
+        let removed = words.iter().step_by(3).copied().collect::<BTreeSet<_>>();
+        for word in &removed {
+            trie.remove(word);
+        }
+        trie.insert("zzz-added", "zzz-added");
+        words.retain(|word| !removed.contains(word));
+        words.push("zzz-added");
+        words.sort();
+
+        assert_eq!(trie.len(), words.len());
+        for (i, word) in words.iter().enumerate().step_by(7) {
+            assert_eq!(trie.rank(word), Some(i));
+            assert_eq!(trie.select(i).map(|(_, v)| v), Some(*word));
+        }
+    }
+
+    #[rstest]
+    #[case(&[], "")]
+    #[case(&[""], "")]
+    #[case(&[""], "a")]
+    #[case(&["aaa", "a", ""], "")]
+    #[case(&["aaa", "a", ""], "a")]
+    #[case(&["aaa", "a", ""], "aa")]
+    #[case(&["aaa", "a", ""], "aab")]
+    #[case(&["aaa", "a", ""], "aaaa")]
+    #[case(&["abc", "x"], "ab")]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], "ad")]
+    #[case(&["dad", "ant", "and", "dot", "do"], "d")]
+    #[case(&["x-forwarded-for", "x-forwarded-proto", "x-real-ip"], "x-forw")]
+    #[case(&["x-forwarded-for", "x-forwarded-proto", "x-real-ip"], "x-forwarded-")]
+    fn test_count_prefix(#[case] input: &[&str], #[case] prefix: &str) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        assert_eq!(
+            trie.count_prefix(prefix),
+            trie.prefix_search(prefix).count()
+        );
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_counts_follow_updates(#[case] text: &'static str) {
+        let words = text.split_whitespace().collect::<BTreeSet<_>>();
+        let mut trie = words
+            .iter()
+            .copied()
+            .step_by(2)
+            .collect::<TrieHard<'_, _>>();
+
+        let check = |trie: &TrieHard<'_, &str>| {
+            let keys = trie.iter().map(|(_, v)| v).collect::<Vec<_>>();
+            assert_eq!(trie.len(), keys.len());
+            for (i, key) in keys.iter().enumerate() {
+                assert_eq!(trie.rank(key), Some(i));
+                assert_eq!(trie.select(i).map(|(_, v)| v), Some(*key));
+
+                let prefix = &key.as_bytes()[..key.len() / 2];
+                assert_eq!(
+                    trie.count_prefix(prefix),
+                    trie.prefix_search(prefix).count()
+                );
+            }
+        };
+
+        for word in words.iter().skip(1).step_by(2) {
+            trie.insert(*word, *word);
+            check(&trie);
+        }
+        for word in words.iter().step_by(3) {
+            trie.remove(word);
+            check(&trie);
+        }
+    }
+
+    #[test]
+    fn test_runs_normalized() {
+        let mut trie = folded(&["X-Forwarded-For", "X-Forwarded-Proto"]);
+
+        assert_eq!(trie.get("x-forwarded-for"), Some("X-Forwarded-For"));
+        assert_eq!(trie.prefix_search("x-FORW").count(), 2);
+
+        trie.insert(b"x-forwarded-host", "x-forwarded-host");
+        assert_eq!(trie.get("X-FORWARDED-HOST"), Some("x-forwarded-host"));
+        assert_eq!(trie.get("X-Forwarded-Proto"), Some("X-Forwarded-Proto"));
+    }
+
+    #[test]
+    fn test_rank_normalized() {
+        let trie = folded(&["Alpha", "beta", "GAMMA"]);
+
+        assert_eq!(trie.rank("BETA"), Some(1));
+        assert_eq!(trie.rank("gamma"), Some(2));
+        assert_eq!(trie.count_prefix("gA"), 1);
+        assert_eq!(trie.select(0).map(|(k, _)| k), Some(&b"Alpha"[..]));
+    }
 }
@@ -0,0 +1,991 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem::size_of,
+    ops::Range,
+};
+
+use crate::{
+    u256::U256,
+    walk::{walk, Target, TrieNodes, WalkNode},
+    MasksByByteSized, Normalization, SearchNode, TrieHard, TrieHardSized,
+    TrieState,
+};
+
+/// Enumeration of all the possible sizes of minimized trie-hard tries.
+///
+/// A minimized trie is built like a [`TrieHard`], then every set of subtrees
+/// that hold the same key suffixes is merged into one, turning the tree into
+/// a directed acyclic word graph. Word lists with many shared endings (plurals,
+/// verb forms, top level domains) need far fewer nodes this way. Nodes of the
+/// graph cannot hold keys or values, since a node is reached by several keys,
+/// so entries are kept in a table ordered by key and a lookup finds the
+/// position of its key in the table while walking the graph.
+///
+/// Minimized tries are read-only. [`TrieHardMinimized::report`] compares the
+/// memory used with that of a regular trie for the same entries.
+///
+/// ```
+/// # use trie_hard::TrieHardMinimized;
+/// let trie = ["walk", "walked", "walks", "talk", "talked", "talks"]
+///     .into_iter()
+///     .collect::<TrieHardMinimized<'_, _>>();
+///
+/// assert_eq!(trie.get("talked"), Some("talked"));
+/// assert!(trie.get("stalked").is_none());
+/// assert_eq!(
+///     trie.prefix_search("walk").map(|(_, v)| v).collect::<Vec<_>>(),
+///     ["walk", "walked", "walks"]
+/// );
+///
+/// let report = trie.report();
+/// assert!(report.minimized_nodes < report.trie_nodes);
+/// ```
+///
+/// _Note_: Like [`TrieHard`], this enum is dominated by its largest variant.
+/// Extract the inner [`TrieHardMinimizedSized`] if the extra space matters.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum TrieHardMinimized<'a, T> {
+    /// Minimized trie-hard using u8s for storage. For sets with 1..=8 unique
+    /// bytes
+    U8(TrieHardMinimizedSized<'a, T, u8>),
+    /// Minimized trie-hard using u16s for storage. For sets with 9..=16
+    /// unique bytes
+    U16(TrieHardMinimizedSized<'a, T, u16>),
+    /// Minimized trie-hard using u32s for storage. For sets with 17..=32
+    /// unique bytes
+    U32(TrieHardMinimizedSized<'a, T, u32>),
+    /// Minimized trie-hard using u64s for storage. For sets with 33..=64
+    /// unique bytes
+    U64(TrieHardMinimizedSized<'a, T, u64>),
+    /// Minimized trie-hard using u128s for storage. For sets with 65..=128
+    /// unique bytes
+    U128(TrieHardMinimizedSized<'a, T, u128>),
+    /// Minimized trie-hard using U256s for storage. For sets with 129..
+    /// unique bytes
+    U256(TrieHardMinimizedSized<'a, T, U256>),
+}
+
+/// Inner representation of a minimized trie-hard trie that is generic to a
+/// specific size of integer.
+#[derive(Debug, Clone)]
+pub struct TrieHardMinimizedSized<'a, T, I> {
+    masks: MasksByByteSized<I>,
+    nodes: Vec<MinimizedNode<'a, I>>,
+    /// Entries ordered by their normalized keys, which is the order in which
+    /// they are reached in the graph
+    entries: Vec<(&'a [u8], T)>,
+    normalization: Normalization,
+    report: MinimizeReport,
+}
+
+/// Node of a minimized trie. The children of a search node and the node after
+/// a run are stored together in a block, and blocks are shared between every
+/// node whose subtrees are the same.
+#[derive(Debug, Clone)]
+struct MinimizedNode<'a, I> {
+    state: MinimizedState<'a, I>,
+    /// Number of entries below the nodes before this one in its block
+    before: usize,
+}
+
+#[derive(Debug, Clone)]
+enum MinimizedState<'a, I> {
+    /// End of a key with no longer keys below it
+    Leaf,
+    Search(SearchNode<I>),
+    /// End of a key with longer keys below it
+    SearchOrLeaf(SearchNode<I>),
+    Run(&'a [u8], usize),
+}
+
+/// Memory used by a minimized trie compared with a regular trie holding the
+/// same entries. Sizes count the node arrays and the values stored alongside
+/// them; the mask table and the borrowed keys are the same for both and are
+/// left out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MinimizeReport {
+    /// Number of nodes in a regular trie for the same entries
+    pub trie_nodes: usize,
+    /// Bytes used by the nodes of a regular trie for the same entries
+    pub trie_bytes: usize,
+    /// Number of nodes left after merging identical subtrees
+    pub minimized_nodes: usize,
+    /// Bytes used by the nodes and the entry table of the minimized trie
+    pub minimized_bytes: usize,
+}
+
+impl MinimizeReport {
+    /// Number of bytes saved by minimizing, which is zero if the minimized
+    /// trie turned out to be larger
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let words = include_str!("../data/1984.txt")
+    ///     .split(|c: char| c.is_whitespace())
+    ///     .collect::<Vec<_>>();
+    /// let trie = words.iter().copied().collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// let report = trie.report();
+    /// assert_eq!(
+    ///     report.bytes_saved(),
+    ///     report.trie_bytes - report.minimized_bytes
+    /// );
+    /// ```
+    pub fn bytes_saved(&self) -> usize {
+        self.trie_bytes.saturating_sub(self.minimized_bytes)
+    }
+}
+
+impl<'a, T> Default for TrieHardMinimized<'a, T> {
+    fn default() -> Self {
+        TrieHardMinimized::U8(TrieHardMinimizedSized {
+            masks: MasksByByteSized::default(),
+            nodes: Vec::new(),
+            entries: Vec::new(),
+            normalization: Normalization::None,
+            report: MinimizeReport::default(),
+        })
+    }
+}
+
+impl<'a, T> TrieHardMinimized<'a, T> {
+    /// Create a minimized trie with the given keys and values. If a key
+    /// appears more than once, the last value given for it is kept.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = TrieHardMinimized::new(vec![(b"and", 0), (b"ant", 1)]);
+    ///
+    /// assert_eq!(trie.get("and"), Some(0));
+    /// assert_eq!(trie.get("ant"), Some(1));
+    /// ```
+    pub fn new(values: Vec<(&'a [u8], T)>) -> Self {
+        Self::build(values, Normalization::None)
+    }
+
+    pub(crate) fn build(
+        values: Vec<(&'a [u8], T)>,
+        normalization: Normalization,
+    ) -> Self {
+        let sorted = values
+            .into_iter()
+            .map(|(k, v)| {
+                let normalized = k
+                    .iter()
+                    .map(|c| normalization.byte(*c))
+                    .collect::<Vec<_>>();
+                (normalized, (k, v))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let entries = sorted.into_values().collect::<Vec<_>>();
+
+        // The value of each entry in the regular trie is its position in the
+        // entry table
+        let trie = TrieHard::build(
+            entries
+                .iter()
+                .enumerate()
+                .map(|(i, (k, _))| (*k, i))
+                .collect(),
+            normalization,
+        );
+
+        match trie {
+            TrieHard::U8(trie) => TrieHardMinimized::U8(
+                TrieHardMinimizedSized::<'_, _, u8>::from_sized(trie, entries),
+            ),
+            TrieHard::U16(trie) => TrieHardMinimized::U16(
+                TrieHardMinimizedSized::<'_, _, u16>::from_sized(trie, entries),
+            ),
+            TrieHard::U32(trie) => TrieHardMinimized::U32(
+                TrieHardMinimizedSized::<'_, _, u32>::from_sized(trie, entries),
+            ),
+            TrieHard::U64(trie) => TrieHardMinimized::U64(
+                TrieHardMinimizedSized::<'_, _, u64>::from_sized(trie, entries),
+            ),
+            TrieHard::U128(trie) => TrieHardMinimized::U128(
+                TrieHardMinimizedSized::<'_, _, u128>::from_sized(
+                    trie, entries,
+                ),
+            ),
+            TrieHard::U256(trie) => TrieHardMinimized::U256(
+                TrieHardMinimizedSized::<'_, _, U256>::from_sized(
+                    trie, entries,
+                ),
+            ),
+        }
+    }
+
+    /// Get a reference to the value stored for the given key. Unlike
+    /// [`TrieHardMinimized::get`], this does not require the values to be
+    /// `Copy`.
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = TrieHardMinimized::new(vec![
+    ///     (b"and", String::from("first")),
+    ///     (b"ant", String::from("second")),
+    /// ]);
+    ///
+    /// assert_eq!(trie.get_ref("ant").map(String::as_str), Some("second"));
+    /// assert!(trie.get_ref("an").is_none());
+    /// ```
+    pub fn get_ref<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<&T> {
+        match self {
+            TrieHardMinimized::U8(trie) => trie.get_ref(raw_key),
+            TrieHardMinimized::U16(trie) => trie.get_ref(raw_key),
+            TrieHardMinimized::U32(trie) => trie.get_ref(raw_key),
+            TrieHardMinimized::U64(trie) => trie.get_ref(raw_key),
+            TrieHardMinimized::U128(trie) => trie.get_ref(raw_key),
+            TrieHardMinimized::U256(trie) => trie.get_ref(raw_key),
+        }
+    }
+
+    /// Number of entries in the trie
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["and", "ant", "and"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// assert_eq!(trie.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        match self {
+            TrieHardMinimized::U8(trie) => trie.len(),
+            TrieHardMinimized::U16(trie) => trie.len(),
+            TrieHardMinimized::U32(trie) => trie.len(),
+            TrieHardMinimized::U64(trie) => trie.len(),
+            TrieHardMinimized::U128(trie) => trie.len(),
+            TrieHardMinimized::U256(trie) => trie.len(),
+        }
+    }
+
+    /// Check if the trie has no entries
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// assert!(TrieHardMinimized::<'_, ()>::default().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Memory used by the trie compared with a regular [`TrieHard`] holding
+    /// the same entries
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["walk", "walked", "walks", "talk", "talked", "talks"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// let report = trie.report();
+    /// println!(
+    ///     "{} nodes instead of {}, saving {} bytes",
+    ///     report.minimized_nodes,
+    ///     report.trie_nodes,
+    ///     report.bytes_saved()
+    /// );
+    /// ```
+    pub fn report(&self) -> MinimizeReport {
+        match self {
+            TrieHardMinimized::U8(trie) => trie.report(),
+            TrieHardMinimized::U16(trie) => trie.report(),
+            TrieHardMinimized::U32(trie) => trie.report(),
+            TrieHardMinimized::U64(trie) => trie.report(),
+            TrieHardMinimized::U128(trie) => trie.report(),
+            TrieHardMinimized::U256(trie) => trie.report(),
+        }
+    }
+}
+
+impl<'a, T> TrieHardMinimized<'a, T>
+where
+    T: Copy,
+{
+    /// Get the value stored for the given key. Any key type can be used here as
+    /// long as the type implements `AsRef<[u8]>`. The byte slice referenced
+    /// will serve as the actual key.
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// assert!(trie.get("dad".to_owned()).is_some());
+    /// assert!(trie.get(b"do").is_some());
+    /// assert!(trie.get(b"don't".to_vec()).is_none());
+    /// ```
+    pub fn get<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<T> {
+        self.get_from_bytes(raw_key.as_ref())
+    }
+
+    /// Get the value stored for the given byte-slice key
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// assert!(trie.get_from_bytes(b"dad").is_some());
+    /// assert!(trie.get_from_bytes(b"don't").is_none());
+    /// ```
+    pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+        self.get_ref(key).copied()
+    }
+
+    /// Create an iterator over the entire trie. Emitted items will be ordered
+    /// by their keys
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["dad", "ant", "and", "dot", "do"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["and", "ant", "dad", "do", "dot"]
+    /// );
+    /// ```
+    pub fn iter(&self) -> MinimizedIter<'_, 'a, T> {
+        match self {
+            TrieHardMinimized::U8(trie) => trie.iter(),
+            TrieHardMinimized::U16(trie) => trie.iter(),
+            TrieHardMinimized::U32(trie) => trie.iter(),
+            TrieHardMinimized::U64(trie) => trie.iter(),
+            TrieHardMinimized::U128(trie) => trie.iter(),
+            TrieHardMinimized::U256(trie) => trie.iter(),
+        }
+    }
+
+    /// Create an iterator over the portion of the trie starting with the given
+    /// prefix
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardMinimized;
+    /// let trie = ["dad", "ant", "and", "dot", "do"]
+    ///     .into_iter()
+    ///     .collect::<TrieHardMinimized<'_, _>>();
+    ///
+    /// assert_eq!(
+    ///     trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["dad", "do", "dot"]
+    /// );
+    /// ```
+    pub fn prefix_search<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+    ) -> MinimizedIter<'_, 'a, T> {
+        match self {
+            TrieHardMinimized::U8(trie) => trie.prefix_search(prefix),
+            TrieHardMinimized::U16(trie) => trie.prefix_search(prefix),
+            TrieHardMinimized::U32(trie) => trie.prefix_search(prefix),
+            TrieHardMinimized::U64(trie) => trie.prefix_search(prefix),
+            TrieHardMinimized::U128(trie) => trie.prefix_search(prefix),
+            TrieHardMinimized::U256(trie) => trie.prefix_search(prefix),
+        }
+    }
+}
+
+impl<'a, T> FromIterator<&'a T> for TrieHardMinimized<'a, &'a T>
+where
+    T: 'a + AsRef<[u8]> + ?Sized,
+{
+    fn from_iter<I: IntoIterator<Item = &'a T>>(values: I) -> Self {
+        let values = values
+            .into_iter()
+            .map(|v| (v.as_ref(), v))
+            .collect::<Vec<_>>();
+
+        Self::new(values)
+    }
+}
+
+impl<'a, T, I> TrieHardMinimizedSized<'a, T, I> {
+    /// Number of entries in the trie
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the trie has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Memory used by the trie compared with a regular trie holding the same
+    /// entries
+    pub fn report(&self) -> MinimizeReport {
+        self.report
+    }
+}
+
+/// Structure used for iterating over the contents of a minimized trie. Entries
+/// are read straight from the entry table, so the iterator is the same for
+/// every integer size.
+#[derive(Debug, Clone)]
+pub struct MinimizedIter<'b, 'a, T> {
+    entries: std::slice::Iter<'b, (&'a [u8], T)>,
+}
+
+impl<'b, 'a, T> Iterator for MinimizedIter<'b, 'a, T>
+where
+    T: Copy,
+{
+    type Item = (&'a [u8], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.next().copied()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+impl<'b, 'a, T> DoubleEndedIterator for MinimizedIter<'b, 'a, T>
+where
+    T: Copy,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.entries.next_back().copied()
+    }
+}
+
+impl<'b, 'a, T> ExactSizeIterator for MinimizedIter<'b, 'a, T> where T: Copy {}
+
+macro_rules! minimized_impls {
+    ($($int_type:ty),+) => {
+        $(
+            minimized_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieNodes for TrieHardMinimizedSized<'a, T, $int_type> {
+            fn normalization(&self) -> &Normalization {
+                &self.normalization
+            }
+
+            fn walk_node(&self, index: usize) -> Option<WalkNode<'_>> {
+                Some(match &self.nodes.get(index)?.state {
+                    // Leaves of the graph are shared by many keys, so the
+                    // whole key is only stored in the entry table
+                    MinimizedState::Leaf => WalkNode::Leaf(None),
+                    MinimizedState::Search(search) => WalkNode::Search {
+                        entry: false,
+                        children: search.children(),
+                    },
+                    MinimizedState::SearchOrLeaf(search) => WalkNode::Search {
+                        entry: true,
+                        children: search.children(),
+                    },
+                    MinimizedState::Run(run, next) => WalkNode::Run(run, *next),
+                })
+            }
+
+            fn child(&self, index: usize, c: u8) -> Option<usize> {
+                match &self.nodes[index].state {
+                    MinimizedState::Search(search)
+                    | MinimizedState::SearchOrLeaf(search) => {
+                        search.evaluate(c, &self.masks)
+                    }
+                    MinimizedState::Leaf | MinimizedState::Run(..) => None,
+                }
+            }
+        }
+
+        impl<'a, T> TrieHardMinimizedSized<'a, T, $int_type> {
+            /// Merge the identical subtrees of a freshly built trie whose
+            /// values are the positions of the entries in `entries`
+            fn from_sized(
+                trie: TrieHardSized<'a, usize, $int_type>,
+                entries: Vec<(&'a [u8], T)>,
+            ) -> Self {
+                let trie_nodes = trie.nodes.len();
+                let mut nodes = Vec::new();
+
+                if trie_nodes > 0 {
+                    // Keys are only stored in full by leaves, so the depth of
+                    // each node is needed to find the tail of a leaf key.
+                    // Children always come after their parent in a freshly
+                    // built trie.
+                    let mut depths = vec![0; trie_nodes];
+                    for (index, state) in trie.nodes.iter().enumerate() {
+                        let child_depth = match state {
+                            TrieState::Run(run, _) => depths[index] + run.len(),
+                            _ => depths[index] + 1,
+                        };
+                        for child in state.children() {
+                            depths[child] = child_depth;
+                        }
+                    }
+
+                    let mut builder = GraphBuilder::<'_, 'a, $int_type> {
+                        nodes: &mut nodes,
+                        blocks: HashMap::new(),
+                        normalization: &trie.normalization,
+                    };
+                    // The root is the only node that is not part of a block
+                    builder.nodes.push(MinimizedNode {
+                        state: MinimizedState::Leaf,
+                        before: 0,
+                    });
+
+                    // Each node of the trie is replaced by a node of the graph
+                    // and the number of entries below it, from the leaves up
+                    let mut merged = Vec::with_capacity(trie_nodes);
+                    merged.resize_with(trie_nodes, || None);
+                    for index in (0..trie_nodes).rev() {
+                        let state = &trie.nodes[index];
+                        let mut take_children = |range: Range<usize>| {
+                            range
+                                .map(|child| {
+                                    // Safety: Children come after their
+                                    // parent, so they have been merged
+                                    merged[child].take().unwrap()
+                                })
+                                .collect::<Vec<_>>()
+                        };
+
+                        merged[index] = Some(match state {
+                            TrieState::Leaf(key, _) => {
+                                let leaf = (MinimizedState::Leaf, 1);
+                                builder.chain(&key[depths[index]..], leaf, &trie.masks)
+                            }
+                            TrieState::Search(search) => {
+                                let children = take_children(state.children());
+                                let (edge_start, count) = builder.block(children);
+                                (
+                                    MinimizedState::Search(SearchNode {
+                                        mask: search.mask,
+                                        edge_start,
+                                    }),
+                                    count,
+                                )
+                            }
+                            TrieState::SearchOrLeaf(_, _, search) => {
+                                let children = take_children(state.children());
+                                let (edge_start, count) = builder.block(children);
+                                (
+                                    MinimizedState::SearchOrLeaf(SearchNode {
+                                        mask: search.mask,
+                                        edge_start,
+                                    }),
+                                    count + 1,
+                                )
+                            }
+                            TrieState::Run(run, _) => {
+                                let children = take_children(state.children());
+                                let (next, count) = builder.block(children);
+                                (MinimizedState::Run(run, next), count)
+                            }
+                        });
+                    }
+
+                    // Safety: The root was merged last
+                    builder.nodes[0].state = merged[0].take().unwrap().0;
+                }
+
+                let report = MinimizeReport {
+                    trie_nodes,
+                    trie_bytes: trie_nodes * size_of::<TrieState<'a, T, $int_type>>(),
+                    minimized_nodes: nodes.len(),
+                    minimized_bytes: nodes.len() * size_of::<MinimizedNode<'a, $int_type>>()
+                        + entries.len() * size_of::<(&'a [u8], T)>(),
+                };
+
+                TrieHardMinimizedSized {
+                    masks: trie.masks,
+                    nodes,
+                    entries,
+                    normalization: trie.normalization,
+                    report,
+                }
+            }
+
+            /// Position in the entry table of the entry for the given key
+            fn rank_of(&self, key: &[u8]) -> Option<usize> {
+                let mut rank = 0;
+                walk(self, key, Target::Entry, |parent, child| {
+                    rank += self.rank_step(parent, child);
+                })?;
+                Some(rank)
+            }
+
+            /// Positions in the entry table of the entries starting with the
+            /// given prefix
+            fn prefix_range(&self, prefix: &[u8]) -> Range<usize> {
+                let mut rank = 0;
+                let Some(node_index) = walk(self, prefix, Target::Prefix, |parent, child| {
+                    rank += self.rank_step(parent, child);
+                }) else {
+                    return 0..0;
+                };
+
+                rank..rank + self.count(&self.nodes[node_index].state)
+            }
+
+            /// Number of entries that come before `child` but not before
+            /// `parent`: the entry of the parent itself and the entries below
+            /// the earlier nodes of the block
+            fn rank_step(&self, parent: usize, child: usize) -> usize {
+                let parent_entry = matches!(
+                    self.nodes[parent].state,
+                    MinimizedState::SearchOrLeaf(_)
+                );
+                usize::from(parent_entry) + self.nodes[child].before
+            }
+
+            /// Number of entries below a node, found by following its last
+            /// children
+            fn count<'s>(&'s self, mut state: &'s MinimizedState<'a, $int_type>) -> usize {
+                let mut count = 0;
+                loop {
+                    match state {
+                        MinimizedState::Leaf => return count + 1,
+                        MinimizedState::Search(search)
+                        | MinimizedState::SearchOrLeaf(search) => {
+                            if matches!(state, MinimizedState::SearchOrLeaf(_)) {
+                                count += 1;
+                            }
+                            let last = search.edge_start
+                                + search.mask.count_ones() as usize
+                                - 1;
+                            count += self.nodes[last].before;
+                            state = &self.nodes[last].state;
+                        }
+                        MinimizedState::Run(_, next) => {
+                            state = &self.nodes[*next].state;
+                        }
+                    }
+                }
+            }
+
+            /// Get a reference to the value stored for the given key. Unlike
+            /// `get`, this does not require the values to be `Copy`.
+            /// ```
+            /// # use trie_hard::TrieHardMinimized;
+            /// let trie = TrieHardMinimized::new(vec![
+            ///     (b"and", String::from("first")),
+            ///     (b"ant", String::from("second")),
+            /// ]);
+            ///
+            /// let TrieHardMinimized::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.get_ref("ant").map(String::as_str),
+            ///     Some("second")
+            /// );
+            /// assert!(sized_trie.get_ref("an").is_none());
+            /// ```
+            pub fn get_ref<K: AsRef<[u8]>>(&self, key: K) -> Option<&T> {
+                let index = self.rank_of(key.as_ref())?;
+                Some(&self.entries[index].1)
+            }
+        }
+
+        impl<'a, T> TrieHardMinimizedSized<'a, T, $int_type>
+        where
+            T: Copy
+        {
+            /// Get the value stored for the given key. Any key type can be used
+            /// here as long as the type implements `AsRef<[u8]>`. The byte slice
+            /// referenced will serve as the actual key.
+            /// ```
+            /// # use trie_hard::TrieHardMinimized;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHardMinimized<'_, _>>();
+            ///
+            /// let TrieHardMinimized::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.get("dad".to_owned()).is_some());
+            /// assert!(sized_trie.get(b"do").is_some());
+            /// assert!(sized_trie.get(b"don't".to_vec()).is_none());
+            /// ```
+            pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<T> {
+                self.get_from_bytes(key.as_ref())
+            }
+
+            /// Get the value stored for the given byte-slice key.
+            /// ```
+            /// # use trie_hard::TrieHardMinimized;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHardMinimized<'_, _>>();
+            ///
+            /// let TrieHardMinimized::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_trie.get_from_bytes(b"dad").is_some());
+            /// assert!(sized_trie.get_from_bytes(b"don't").is_none());
+            /// ```
+            pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+                self.get_ref(key).copied()
+            }
+
+            /// Create an iterator over the entire trie. Emitted items will be
+            /// ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::TrieHardMinimized;
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHardMinimized<'_, _>>();
+            ///
+            /// let TrieHardMinimized::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["and", "ant", "dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn iter(&self) -> MinimizedIter<'_, 'a, T> {
+                MinimizedIter { entries: self.entries.iter() }
+            }
+
+            /// Create an iterator over the portion of the trie starting with the given
+            /// prefix
+            ///
+            /// ```
+            /// # use trie_hard::TrieHardMinimized;
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHardMinimized<'_, _>>();
+            ///
+            /// let TrieHardMinimized::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn prefix_search<K: AsRef<[u8]>>(&self, prefix: K) -> MinimizedIter<'_, 'a, T> {
+                let range = self.prefix_range(prefix.as_ref());
+                MinimizedIter { entries: self.entries[range].iter() }
+            }
+        }
+
+        impl<'n, 'a> GraphBuilder<'n, 'a, $int_type> {
+            /// Add a block holding the given nodes and the number of entries
+            /// below each, or find an identical block that was added before.
+            /// Returns the index of the block and the number of entries below
+            /// all of its nodes.
+            fn block(
+                &mut self,
+                children: Vec<(MinimizedState<'a, $int_type>, usize)>,
+            ) -> (usize, usize) {
+                let mut signature = Vec::new();
+                let mut block = Vec::with_capacity(children.len());
+                let mut before = 0;
+
+                for (state, count) in children {
+                    match &state {
+                        MinimizedState::Leaf => signature.push(0),
+                        MinimizedState::Search(search) => {
+                            signature.push(1);
+                            signature.extend_from_slice(&search.mask.to_le_bytes());
+                            signature.extend_from_slice(&search.edge_start.to_le_bytes());
+                        }
+                        MinimizedState::SearchOrLeaf(search) => {
+                            signature.push(2);
+                            signature.extend_from_slice(&search.mask.to_le_bytes());
+                            signature.extend_from_slice(&search.edge_start.to_le_bytes());
+                        }
+                        MinimizedState::Run(run, next) => {
+                            signature.push(3);
+                            signature.extend_from_slice(&run.len().to_le_bytes());
+                            signature.extend(run.iter().map(|c| self.normalization.byte(*c)));
+                            signature.extend_from_slice(&next.to_le_bytes());
+                        }
+                    }
+                    block.push(MinimizedNode { state, before });
+                    before += count;
+                }
+
+                let nodes = &mut *self.nodes;
+                let index = *self.blocks.entry(signature).or_insert_with(|| {
+                    let index = nodes.len();
+                    nodes.extend(block);
+                    index
+                });
+                (index, before)
+            }
+
+            /// Node leading through the given bytes to `end`, along with the
+            /// number of entries below it
+            fn chain(
+                &mut self,
+                bytes: &'a [u8],
+                end: (MinimizedState<'a, $int_type>, usize),
+                masks: &MasksByByteSized<$int_type>,
+            ) -> (MinimizedState<'a, $int_type>, usize) {
+                match bytes {
+                    [] => end,
+                    [c] => {
+                        let (edge_start, count) = self.block(vec![end]);
+                        let search = SearchNode {
+                            mask: masks.by_byte[*c as usize],
+                            edge_start,
+                        };
+                        (MinimizedState::Search(search), count)
+                    }
+                    _ => {
+                        let (next, count) = self.block(vec![end]);
+                        (MinimizedState::Run(bytes, next), count)
+                    }
+                }
+            }
+        }
+    };
+}
+
+minimized_impls! {u8, u16, u32, u64, u128, U256}
+
+/// Shared state while merging the nodes of a trie into a graph
+struct GraphBuilder<'n, 'a, I> {
+    nodes: &'n mut Vec<MinimizedNode<'a, I>>,
+    /// Index of every block added so far by the nodes it holds
+    blocks: HashMap<Vec<u8>, usize>,
+    normalization: &'n Normalization,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rstest::rstest;
+
+    use crate::{TrieHard, TrieHardBuilder, TrieHardMinimized};
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split(|c: char| c.is_whitespace())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/1984.txt"))]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_matches_trie(#[case] text: &str) {
+        let words = words(text);
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let minimized =
+            words.iter().copied().collect::<TrieHardMinimized<'_, _>>();
+
+        assert!(matches!(
+            (&trie, &minimized),
+            (TrieHard::U8(_), TrieHardMinimized::U8(_))
+                | (TrieHard::U16(_), TrieHardMinimized::U16(_))
+                | (TrieHard::U32(_), TrieHardMinimized::U32(_))
+                | (TrieHard::U64(_), TrieHardMinimized::U64(_))
+                | (TrieHard::U128(_), TrieHardMinimized::U128(_))
+                | (TrieHard::U256(_), TrieHardMinimized::U256(_))
+        ));
+        for word in &words {
+            assert_eq!(minimized.get(word), Some(*word));
+            for missing in [format!("{word}~"), format!("~{word}")] {
+                assert_eq!(minimized.get(&missing), trie.get(&missing));
+            }
+        }
+        assert!(minimized.iter().eq(trie.iter()));
+        assert!(minimized.iter().rev().eq(trie.iter().rev()));
+        assert_eq!(minimized.len(), words.len());
+
+        for word in words.iter().step_by(7) {
+            for end in 0..=word.len() {
+                let prefix = &word.as_bytes()[..end];
+                assert!(minimized
+                    .prefix_search(prefix)
+                    .eq(trie.prefix_search(prefix)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_merges_suffixes() {
+        let words = words(include_str!("../data/1984.txt"));
+        let minimized =
+            words.iter().copied().collect::<TrieHardMinimized<'_, _>>();
+
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let TrieHard::U128(sized) = &trie else {
+            unreachable!()
+        };
+
+        let report = minimized.report();
+        assert_eq!(report.trie_nodes, sized.nodes.len());
+        assert!(report.minimized_nodes < report.trie_nodes);
+        assert!(report.bytes_saved() > 0);
+    }
+
+    #[rstest]
+    #[case(&[], "", &[])]
+    #[case(&[""], "", &[""])]
+    #[case(&["aaa", "aab", "aac", "ab"], "aa", &["aaa", "aab", "aac"])]
+    #[case(&["aaa", "aab", "aac", "ab"], "ab", &["ab"])]
+    #[case(&["abc", "x"], "ab", &["abc"])]
+    #[case(&["abcdx", "abcdy"], "abc", &["abcdx", "abcdy"])]
+    #[case(&["abcdx", "abcdy"], "abd", &[])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "z", &[])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "do", &["do", "dot"])]
+    fn test_prefix_search(
+        #[case] input: &[&str],
+        #[case] prefix: &str,
+        #[case] output: &[&str],
+    ) {
+        let trie = input.iter().copied().collect::<TrieHardMinimized<'_, _>>();
+
+        let emitted = trie
+            .prefix_search(prefix)
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, output);
+    }
+
+    #[test]
+    fn test_ascii_case_insensitive() {
+        let trie = TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build_minimized(vec![
+                (&b"X-Forwarded-For"[..], 0),
+                (b"x-forwarded-proto", 1),
+                (b"X-FORWARDED-FOR", 2),
+            ]);
+
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get("x-forwarded-for"), Some(2));
+        assert_eq!(trie.get("X-Forwarded-Proto"), Some(1));
+        assert_eq!(trie.prefix_search("x-FORWARDED-").count(), 2);
+    }
+}
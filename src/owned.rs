@@ -0,0 +1,845 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use crate::{
+    u256::U256,
+    walk::{self, walk, Target, TrieNodes, WalkNode},
+    MasksByByteSized, Normalization, SearchNode, TrieHard, TrieHardSized,
+    TrieNodeIter, TrieState,
+};
+
+/// Location of a key inside the byte arena of an owned trie
+#[derive(Debug, Clone, Copy)]
+struct KeySpan {
+    start: usize,
+    end: usize,
+}
+
+#[derive(Debug, Clone)]
+enum OwnedState<T, I> {
+    Leaf(KeySpan, T),
+    Search(SearchNode<I>),
+    SearchOrLeaf(KeySpan, T, SearchNode<I>),
+    Run(KeySpan, usize),
+}
+
+/// Inner representation of an owned trie-hard trie that is generic to a
+/// specific size of integer.
+#[derive(Debug, Clone)]
+pub struct TrieHardOwnedSized<T, I> {
+    keys: Box<[u8]>,
+    masks: MasksByByteSized<I>,
+    nodes: Vec<OwnedState<T, I>>,
+}
+
+impl<T, I> Default for TrieHardOwnedSized<T, I>
+where
+    I: Default + Copy,
+{
+    fn default() -> Self {
+        Self {
+            keys: Default::default(),
+            masks: MasksByByteSized::default(),
+            nodes: Default::default(),
+        }
+    }
+}
+
+impl<T, I> TrieHardOwnedSized<T, I> {
+    fn key(&self, span: KeySpan) -> &[u8] {
+        &self.keys[span.start..span.end]
+    }
+
+    fn from_sized(
+        trie: TrieHardSized<'_, usize, I>,
+        keys: &[u8],
+        spans: &[KeySpan],
+        values: &mut [Option<T>],
+    ) -> (MasksByByteSized<I>, Vec<OwnedState<T, I>>) {
+        // Safety: Every entry index is emitted exactly once by the borrowed
+        //         trie, so each value is still present when it is taken
+        let mut take = |index: usize| values[index].take().unwrap();
+
+        let nodes = trie
+            .nodes
+            .into_vec()
+            .into_iter()
+            .map(|state| match state {
+                TrieState::Leaf(_, index) => {
+                    OwnedState::Leaf(spans[index], take(index))
+                }
+                TrieState::Search(search) => OwnedState::Search(search),
+                TrieState::SearchOrLeaf(_, index, search) => {
+                    OwnedState::SearchOrLeaf(spans[index], take(index), search)
+                }
+                TrieState::Run(run, next) => {
+                    // The bytes of a run are borrowed from one of the keys in
+                    // the arena
+                    let start = run.as_ptr() as usize - keys.as_ptr() as usize;
+                    let end = start + run.len();
+                    OwnedState::Run(KeySpan { start, end }, next)
+                }
+            })
+            .collect();
+
+        (trie.masks, nodes)
+    }
+}
+
+/// Enumeration of all the possible sizes of owned trie-hard tries. Unlike
+/// [`TrieHard`], an owned trie copies every key into a single contiguous
+/// arena, so it does not borrow from its input and can be stored for as long
+/// as needed.
+///
+/// ```
+/// # use trie_hard::TrieHardOwned;
+/// let trie = {
+///     let words = vec!["and".to_owned(), "ant".to_owned(), "dad".to_owned()];
+///     words
+///         .into_iter()
+///         .enumerate()
+///         .map(|(i, word)| (word, i))
+///         .collect::<TrieHardOwned<_>>()
+/// };
+///
+/// assert_eq!(trie.get("ant"), Some(1));
+/// assert_eq!(trie.get("don't"), None);
+/// ```
+///
+/// _Note_: Like [`TrieHard`], this enum is dominated by its largest variant.
+/// Extract the inner [`TrieHardOwnedSized`] if the extra space matters.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum TrieHardOwned<T> {
+    /// Owned trie-hard using u8s for storage. For sets with 1..=8 unique bytes
+    U8(TrieHardOwnedSized<T, u8>),
+    /// Owned trie-hard using u16s for storage. For sets with 9..=16 unique
+    /// bytes
+    U16(TrieHardOwnedSized<T, u16>),
+    /// Owned trie-hard using u32s for storage. For sets with 17..=32 unique
+    /// bytes
+    U32(TrieHardOwnedSized<T, u32>),
+    /// Owned trie-hard using u64s for storage. For sets with 33..=64 unique
+    /// bytes
+    U64(TrieHardOwnedSized<T, u64>),
+    /// Owned trie-hard using u128s for storage. For sets with 65..=128 unique
+    /// bytes
+    U128(TrieHardOwnedSized<T, u128>),
+    /// Owned trie-hard using U256s for storage. For sets with 129.. unique
+    /// bytes
+    U256(TrieHardOwnedSized<T, U256>),
+}
+
+impl<T> Default for TrieHardOwned<T> {
+    fn default() -> Self {
+        TrieHardOwned::U8(TrieHardOwnedSized::default())
+    }
+}
+
+impl<T> TrieHardOwned<T> {
+    /// Create an instance of an owned trie-hard trie with the given keys and
+    /// values. All key bytes are copied into the trie, so the keys can be
+    /// dropped once this returns. If a key appears more than once, the last
+    /// value given for it is kept.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let keys = vec![String::from("and"), String::from("ant")];
+    /// let trie = TrieHardOwned::new(keys.iter().zip([0, 1]).collect());
+    /// drop(keys);
+    ///
+    /// assert!(matches!(trie, TrieHardOwned::U8(_)));
+    /// assert_eq!(trie.get("and"), Some(0));
+    /// assert_eq!(trie.get("ant"), Some(1));
+    /// ```
+    pub fn new<K: AsRef<[u8]>>(values: Vec<(K, T)>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+
+        let sorted = values
+            .iter()
+            .enumerate()
+            .map(|(i, (k, _))| (k.as_ref(), i))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut keys = Vec::with_capacity(sorted.keys().map(|k| k.len()).sum());
+        let mut order = Vec::with_capacity(sorted.len());
+        let mut spans = Vec::with_capacity(sorted.len());
+
+        for (key, index) in sorted {
+            let start = keys.len();
+            keys.extend_from_slice(key);
+            spans.push(KeySpan {
+                start,
+                end: keys.len(),
+            });
+            order.push(index);
+        }
+
+        let mut slots =
+            values.into_iter().map(|(_, v)| Some(v)).collect::<Vec<_>>();
+        let mut values = order
+            .into_iter()
+            .map(|i| slots[i].take())
+            .collect::<Vec<_>>();
+
+        let keys = keys.into_boxed_slice();
+        let trie = TrieHard::new(
+            spans
+                .iter()
+                .enumerate()
+                .map(|(i, span)| (&keys[span.start..span.end], i))
+                .collect(),
+        );
+
+        match trie {
+            TrieHard::U8(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U8(TrieHardOwnedSized { keys, masks, nodes })
+            }
+            TrieHard::U16(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U16(TrieHardOwnedSized { keys, masks, nodes })
+            }
+            TrieHard::U32(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U32(TrieHardOwnedSized { keys, masks, nodes })
+            }
+            TrieHard::U64(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U64(TrieHardOwnedSized { keys, masks, nodes })
+            }
+            TrieHard::U128(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U128(TrieHardOwnedSized { keys, masks, nodes })
+            }
+            TrieHard::U256(trie) => {
+                let (masks, nodes) = TrieHardOwnedSized::from_sized(
+                    trie,
+                    &keys,
+                    &spans,
+                    &mut values,
+                );
+                TrieHardOwned::U256(TrieHardOwnedSized { keys, masks, nodes })
+            }
+        }
+    }
+
+    /// Get a reference to the value stored for the given key. Unlike
+    /// [`TrieHardOwned::get`], this does not require the values to be `Copy`.
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let trie = [("and", String::from("first")), ("ant", String::from("second"))]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// assert_eq!(trie.get_ref("ant").map(String::as_str), Some("second"));
+    /// assert!(trie.get_ref("an").is_none());
+    /// ```
+    pub fn get_ref<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<&T> {
+        match self {
+            TrieHardOwned::U8(trie) => trie.get_ref(raw_key),
+            TrieHardOwned::U16(trie) => trie.get_ref(raw_key),
+            TrieHardOwned::U32(trie) => trie.get_ref(raw_key),
+            TrieHardOwned::U64(trie) => trie.get_ref(raw_key),
+            TrieHardOwned::U128(trie) => trie.get_ref(raw_key),
+            TrieHardOwned::U256(trie) => trie.get_ref(raw_key),
+        }
+    }
+
+    /// Get a mutable reference to the value stored for the given key. The set
+    /// of keys in the trie cannot be changed this way, only their values.
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let mut trie = [("and", vec![0]), ("ant", vec![1])]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// trie.get_mut("and").unwrap().push(2);
+    ///
+    /// assert_eq!(trie.get_ref("and"), Some(&vec![0, 2]));
+    /// assert!(trie.get_mut("dad").is_none());
+    /// ```
+    pub fn get_mut<K: AsRef<[u8]>>(&mut self, raw_key: K) -> Option<&mut T> {
+        match self {
+            TrieHardOwned::U8(trie) => trie.get_mut(raw_key),
+            TrieHardOwned::U16(trie) => trie.get_mut(raw_key),
+            TrieHardOwned::U32(trie) => trie.get_mut(raw_key),
+            TrieHardOwned::U64(trie) => trie.get_mut(raw_key),
+            TrieHardOwned::U128(trie) => trie.get_mut(raw_key),
+            TrieHardOwned::U256(trie) => trie.get_mut(raw_key),
+        }
+    }
+}
+
+impl<T> TrieHardOwned<T>
+where
+    T: Copy,
+{
+    /// Get the value stored for the given key. Any key type can be used here as
+    /// long as the type implements `AsRef<[u8]>`. The byte slice referenced
+    /// will serve as the actual key.
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let trie = [("and", 0), ("ant", 1), ("dad", 2), ("do", 3)]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// assert_eq!(trie.get("dad".to_owned()), Some(2));
+    /// assert_eq!(trie.get(b"do"), Some(3));
+    /// assert!(trie.get(b"don't".to_vec()).is_none());
+    /// ```
+    pub fn get<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<T> {
+        match self {
+            TrieHardOwned::U8(trie) => trie.get(raw_key),
+            TrieHardOwned::U16(trie) => trie.get(raw_key),
+            TrieHardOwned::U32(trie) => trie.get(raw_key),
+            TrieHardOwned::U64(trie) => trie.get(raw_key),
+            TrieHardOwned::U128(trie) => trie.get(raw_key),
+            TrieHardOwned::U256(trie) => trie.get(raw_key),
+        }
+    }
+
+    /// Get the value stored for the given byte-slice key
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let trie = [("and", 0), ("ant", 1), ("dad", 2), ("do", 3)]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// assert_eq!(trie.get_from_bytes(b"dad"), Some(2));
+    /// assert_eq!(trie.get_from_bytes(b"do"), Some(3));
+    /// assert!(trie.get_from_bytes(b"don't").is_none());
+    /// ```
+    pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+        match self {
+            TrieHardOwned::U8(trie) => trie.get_from_bytes(key),
+            TrieHardOwned::U16(trie) => trie.get_from_bytes(key),
+            TrieHardOwned::U32(trie) => trie.get_from_bytes(key),
+            TrieHardOwned::U64(trie) => trie.get_from_bytes(key),
+            TrieHardOwned::U128(trie) => trie.get_from_bytes(key),
+            TrieHardOwned::U256(trie) => trie.get_from_bytes(key),
+        }
+    }
+
+    /// Create an iterator over the entire trie. Emitted items will be ordered
+    /// by their keys
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let trie = [("dad", 0), ("ant", 1), ("and", 2), ("dot", 3), ("do", 4)]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// assert_eq!(
+    ///     trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     [2, 1, 0, 4, 3]
+    /// );
+    /// ```
+    pub fn iter(&self) -> TrieOwnedIter<'_, T> {
+        match self {
+            TrieHardOwned::U8(trie) => TrieOwnedIter::U8(trie.iter()),
+            TrieHardOwned::U16(trie) => TrieOwnedIter::U16(trie.iter()),
+            TrieHardOwned::U32(trie) => TrieOwnedIter::U32(trie.iter()),
+            TrieHardOwned::U64(trie) => TrieOwnedIter::U64(trie.iter()),
+            TrieHardOwned::U128(trie) => TrieOwnedIter::U128(trie.iter()),
+            TrieHardOwned::U256(trie) => TrieOwnedIter::U256(trie.iter()),
+        }
+    }
+
+    /// Create an iterator over the portion of the trie starting with the given
+    /// prefix
+    ///
+    /// ```
+    /// # use trie_hard::TrieHardOwned;
+    /// let trie = [("dad", 0), ("ant", 1), ("and", 2), ("dot", 3), ("do", 4)]
+    ///     .into_iter()
+    ///     .collect::<TrieHardOwned<_>>();
+    ///
+    /// assert_eq!(
+    ///     trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     [0, 4, 3]
+    /// );
+    /// ```
+    pub fn prefix_search<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+    ) -> TrieOwnedIter<'_, T> {
+        match self {
+            TrieHardOwned::U8(trie) => {
+                TrieOwnedIter::U8(trie.prefix_search(prefix))
+            }
+            TrieHardOwned::U16(trie) => {
+                TrieOwnedIter::U16(trie.prefix_search(prefix))
+            }
+            TrieHardOwned::U32(trie) => {
+                TrieOwnedIter::U32(trie.prefix_search(prefix))
+            }
+            TrieHardOwned::U64(trie) => {
+                TrieOwnedIter::U64(trie.prefix_search(prefix))
+            }
+            TrieHardOwned::U128(trie) => {
+                TrieOwnedIter::U128(trie.prefix_search(prefix))
+            }
+            TrieHardOwned::U256(trie) => {
+                TrieOwnedIter::U256(trie.prefix_search(prefix))
+            }
+        }
+    }
+}
+
+impl<K, T> FromIterator<(K, T)> for TrieHardOwned<T>
+where
+    K: AsRef<[u8]>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, T)>>(values: I) -> Self {
+        Self::new(values.into_iter().collect())
+    }
+}
+
+/// Structure used for iterating over the contents of an owned trie
+#[derive(Debug)]
+pub enum TrieOwnedIter<'b, T> {
+    /// Variant for iterating over owned tries built on u8
+    U8(TrieOwnedIterSized<'b, T, u8>),
+    /// Variant for iterating over owned tries built on u16
+    U16(TrieOwnedIterSized<'b, T, u16>),
+    /// Variant for iterating over owned tries built on u32
+    U32(TrieOwnedIterSized<'b, T, u32>),
+    /// Variant for iterating over owned tries built on u64
+    U64(TrieOwnedIterSized<'b, T, u64>),
+    /// Variant for iterating over owned tries built on u128
+    U128(TrieOwnedIterSized<'b, T, u128>),
+    /// Variant for iterating over owned tries built on u256
+    U256(TrieOwnedIterSized<'b, T, U256>),
+}
+
+/// Structure for iterating over an owned trie-hard trie built on a specific
+/// integer size
+#[derive(Debug)]
+pub struct TrieOwnedIterSized<'b, T, I> {
+    stack: Vec<TrieNodeIter>,
+    trie: &'b TrieHardOwnedSized<T, I>,
+}
+
+impl<'b, T, I> TrieOwnedIterSized<'b, T, I> {
+    fn empty(trie: &'b TrieHardOwnedSized<T, I>) -> Self {
+        Self {
+            stack: Default::default(),
+            trie,
+        }
+    }
+
+    fn new(trie: &'b TrieHardOwnedSized<T, I>, node_index: usize) -> Self {
+        Self {
+            stack: vec![TrieNodeIter {
+                node_index,
+                stage: Default::default(),
+            }],
+            trie,
+        }
+    }
+}
+
+impl<'b, T> Iterator for TrieOwnedIter<'b, T>
+where
+    T: Copy,
+{
+    type Item = (&'b [u8], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            TrieOwnedIter::U8(iter) => iter.next(),
+            TrieOwnedIter::U16(iter) => iter.next(),
+            TrieOwnedIter::U32(iter) => iter.next(),
+            TrieOwnedIter::U64(iter) => iter.next(),
+            TrieOwnedIter::U128(iter) => iter.next(),
+            TrieOwnedIter::U256(iter) => iter.next(),
+        }
+    }
+}
+
+macro_rules! owned_impls {
+    ($($int_type:ty),+) => {
+        $(
+            owned_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<T> TrieNodes for TrieHardOwnedSized<T, $int_type> {
+            fn normalization(&self) -> &Normalization {
+                &Normalization::None
+            }
+
+            fn walk_node(&self, index: usize) -> Option<WalkNode<'_>> {
+                Some(match self.nodes.get(index)? {
+                    OwnedState::Leaf(span, _) => {
+                        WalkNode::Leaf(Some(self.key(*span)))
+                    }
+                    OwnedState::Search(search) => WalkNode::Search {
+                        entry: false,
+                        children: search.children(),
+                    },
+                    OwnedState::SearchOrLeaf(_, _, search) => WalkNode::Search {
+                        entry: true,
+                        children: search.children(),
+                    },
+                    OwnedState::Run(span, next) => {
+                        WalkNode::Run(self.key(*span), *next)
+                    }
+                })
+            }
+
+            fn child(&self, index: usize, c: u8) -> Option<usize> {
+                match &self.nodes[index] {
+                    OwnedState::Search(search)
+                    | OwnedState::SearchOrLeaf(_, _, search) => {
+                        search.evaluate(c, &self.masks)
+                    }
+                    OwnedState::Leaf(..) | OwnedState::Run(..) => None,
+                }
+            }
+        }
+
+        impl<T> TrieHardOwnedSized<T, $int_type> {
+            /// Get a reference to the value stored for the given key. Unlike
+            /// `get`, this does not require the values to be `Copy`.
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("and", String::from("first")), ("ant", String::from("second"))]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.get_ref("ant").map(String::as_str),
+            ///     Some("second")
+            /// );
+            /// assert!(sized_trie.get_ref("an").is_none());
+            /// ```
+            pub fn get_ref<K: AsRef<[u8]>>(&self, key: K) -> Option<&T> {
+                let index = self.find_index(key.as_ref())?;
+                match &self.nodes[index] {
+                    OwnedState::Leaf(_, value)
+                    | OwnedState::SearchOrLeaf(_, value, _) => Some(value),
+                    OwnedState::Search(_) | OwnedState::Run(..) => None,
+                }
+            }
+
+            /// Get a mutable reference to the value stored for the given key.
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("and", vec![0]), ("ant", vec![1])]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(mut sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// sized_trie.get_mut("and").unwrap().push(2);
+            ///
+            /// assert_eq!(sized_trie.get_ref("and"), Some(&vec![0, 2]));
+            /// ```
+            pub fn get_mut<K: AsRef<[u8]>>(&mut self, key: K) -> Option<&mut T> {
+                let index = self.find_index(key.as_ref())?;
+                match &mut self.nodes[index] {
+                    OwnedState::Leaf(_, value)
+                    | OwnedState::SearchOrLeaf(_, value, _) => Some(value),
+                    OwnedState::Search(_) | OwnedState::Run(..) => None,
+                }
+            }
+
+            /// Find the index of the node holding the entry for the given key
+            fn find_index(&self, key: &[u8]) -> Option<usize> {
+                walk(self, key, Target::Entry, |_, _| ())
+            }
+        }
+
+        impl<T> TrieHardOwnedSized<T, $int_type>
+        where
+            T: Copy
+        {
+            /// Get the value stored for the given key. Any key type can be used
+            /// here as long as the type implements `AsRef<[u8]>`. The byte slice
+            /// referenced will serve as the actual key.
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("and", 0), ("ant", 1), ("dad", 2), ("do", 3)]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.get("dad".to_owned()), Some(2));
+            /// assert_eq!(sized_trie.get(b"do"), Some(3));
+            /// assert!(sized_trie.get(b"don't".to_vec()).is_none());
+            /// ```
+            pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<T> {
+                self.get_from_bytes(key.as_ref())
+            }
+
+            /// Get the value stored for the given byte-slice key.
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("and", 0), ("ant", 1), ("dad", 2), ("do", 3)]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_trie.get_from_bytes(b"dad"), Some(2));
+            /// assert_eq!(sized_trie.get_from_bytes(b"do"), Some(3));
+            /// assert!(sized_trie.get_from_bytes(b"don't").is_none());
+            /// ```
+            pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+                self.get_ref(key).copied()
+            }
+
+            /// Create an iterator over the entire trie. Emitted items will be
+            /// ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("dad", 0), ("ant", 1), ("and", 2), ("do", 3)]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     [2, 1, 0, 3]
+            /// );
+            /// ```
+            pub fn iter(&self) -> TrieOwnedIterSized<'_, T, $int_type> {
+                TrieOwnedIterSized {
+                    stack: vec![TrieNodeIter::default()],
+                    trie: self
+                }
+            }
+
+            /// Create an iterator over the portion of the trie starting with the given
+            /// prefix
+            ///
+            /// ```
+            /// # use trie_hard::TrieHardOwned;
+            /// let trie = [("dad", 0), ("ant", 1), ("and", 2), ("do", 3)]
+            ///     .into_iter()
+            ///     .collect::<TrieHardOwned<_>>();
+            ///
+            /// let TrieHardOwned::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_trie.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     [0, 3]
+            /// );
+            /// ```
+            pub fn prefix_search<K: AsRef<[u8]>>(&self, prefix: K) -> TrieOwnedIterSized<'_, T, $int_type> {
+                match walk(self, prefix.as_ref(), Target::Prefix, |_, _| ()) {
+                    Some(node_index) => TrieOwnedIterSized::new(self, node_index),
+                    None => TrieOwnedIterSized::empty(self),
+                }
+            }
+        }
+
+        impl <'b, T> Iterator for TrieOwnedIterSized<'b, T, $int_type>
+        where
+            T: Copy
+        {
+            type Item = (&'b [u8], T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let trie = self.trie;
+                let node_index = walk::next_entry(trie, &mut self.stack)?;
+                match &trie.nodes[node_index] {
+                    OwnedState::Leaf(span, value)
+                    | OwnedState::SearchOrLeaf(span, value, _) => {
+                        Some((trie.key(*span), *value))
+                    }
+                    OwnedState::Search(_) | OwnedState::Run(..) => None,
+                }
+            }
+        }
+    }
+}
+
+owned_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn owned_trie(input: &[&str]) -> TrieHardOwned<usize> {
+        input
+            .iter()
+            .map(|s| s.to_string())
+            .enumerate()
+            .map(|(i, s)| (s, i))
+            .collect()
+    }
+
+    #[test]
+    fn test_trivial() {
+        let empty = TrieHardOwned::<usize>::new(Vec::<(&str, _)>::new());
+
+        assert_eq!(None, empty.get("anything"));
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[rstest]
+    #[case("", Some(0))]
+    #[case("a", Some(1))]
+    #[case("ab", Some(2))]
+    #[case("abc", None)]
+    #[case("aac", Some(3))]
+    #[case("aa", None)]
+    #[case("aab", None)]
+    #[case("adddd", Some(4))]
+    fn test_small_get(#[case] key: &str, #[case] expected: Option<usize>) {
+        let trie = owned_trie(&["", "a", "ab", "aac", "adddd", "addde"]);
+        assert_eq!(expected, trie.get(key));
+    }
+
+    #[test]
+    fn test_outlives_input() {
+        let trie = {
+            let keys = vec![String::from("bär"), String::from("bären")];
+            TrieHardOwned::new(keys.into_iter().zip(["b", "B"]).collect())
+        };
+
+        assert_eq!(trie.get("bär"), Some("b"));
+        assert_eq!(trie.get("bä"), None);
+        assert_eq!(trie.get("bären"), Some("B"));
+    }
+
+    #[test]
+    fn test_duplicate_keys_keep_last() {
+        let trie = TrieHardOwned::new(vec![("a", 0), ("b", 1), ("a", 2)]);
+
+        assert_eq!(trie.get("a"), Some(2));
+        assert_eq!(
+            trie.iter().collect::<Vec<_>>(),
+            [(&b"a"[..], 2), (&b"b"[..], 1)]
+        );
+    }
+
+    #[rstest]
+    #[case(8)]
+    #[case(16)]
+    #[case(32)]
+    #[case(64)]
+    #[case(128)]
+    #[case(256)]
+    fn test_sizes(#[case] bits: usize) {
+        let trie = (0..bits)
+            .map(|b| (vec![b as u8], b))
+            .collect::<TrieHardOwned<_>>();
+
+        use TrieHardOwned as T;
+
+        match (bits, &trie) {
+            (8, T::U8(_)) => (),
+            (16, T::U16(_)) => (),
+            (32, T::U32(_)) => (),
+            (64, T::U64(_)) => (),
+            (128, T::U128(_)) => (),
+            (256, T::U256(_)) => (),
+            _ => panic!("Mismatched trie sizes"),
+        }
+
+        for b in 0..bits {
+            assert_eq!(trie.get([b as u8]), Some(b));
+        }
+    }
+
+    #[rstest]
+    #[case(&[], &[])]
+    #[case(&[""], &[""])]
+    #[case(&["aaa", "a", ""], &["", "a", "aaa"])]
+    #[case(&["", "a", "ab", "aac", "adddd", "addde"], &["", "a", "aac", "ab", "adddd", "addde"])]
+    fn test_iter(#[case] input: &[&str], #[case] output: &[&str]) {
+        let trie = owned_trie(input);
+        let emitted = trie
+            .iter()
+            .map(|(k, _)| std::str::from_utf8(k).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, output);
+    }
+
+    #[rstest]
+    #[case(&[], "", &[])]
+    #[case(&[""], "", &[""])]
+    #[case(&["aaa", "a", ""], "a", &["a", "aaa"])]
+    #[case(&["aaa", "a", ""], "aa", &["aaa"])]
+    #[case(&["aaa", "a", ""], "aab", &[])]
+    #[case(&["aaa", "a", ""], "b", &[])]
+    #[case(&["abc", "x"], "ab", &["abc"])]
+    #[case(&["dad", "ant", "and", "dot", "do"], "d", &["dad", "do", "dot"])]
+    fn test_prefix_search(
+        #[case] input: &[&str],
+        #[case] prefix: &str,
+        #[case] output: &[&str],
+    ) {
+        let trie = owned_trie(input);
+        let emitted = trie
+            .prefix_search(prefix)
+            .map(|(k, _)| std::str::from_utf8(k).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, output);
+    }
+}
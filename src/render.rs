@@ -0,0 +1,283 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Write;
+
+use crate::{u256::U256, TrieHard, TrieHardSized, TrieState};
+
+/// Mask of one of the integer sizes used by tries, as four little-endian
+/// 64-bit parts
+pub type MaskParts = [u64; 4];
+
+trait ToMaskParts {
+    fn to_mask_parts(&self) -> MaskParts;
+}
+
+macro_rules! mask_parts_impls {
+    ($($int_type:ty),+) => {
+        $(
+            impl ToMaskParts for $int_type {
+                fn to_mask_parts(&self) -> MaskParts {
+                    let mask = *self as u128;
+                    [mask as u64, (mask >> 64) as u64, 0, 0]
+                }
+            }
+        )+
+    };
+}
+
+mask_parts_impls! {u8, u16, u32, u64, u128}
+
+impl ToMaskParts for U256 {
+    fn to_mask_parts(&self) -> MaskParts {
+        let mut parts = [0; 4];
+        for (part, bytes) in
+            parts.iter_mut().zip(self.to_le_bytes().chunks_exact(8))
+        {
+            // Safety: chunks_exact only yields slices of 8 bytes
+            *part = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        parts
+    }
+}
+
+/// Layout of a trie as plain data, for generating code that builds the same
+/// trie in a `const` context. Values are indices into the caller's list of
+/// value expressions.
+#[derive(Debug)]
+pub struct Skeleton<'a> {
+    /// Name of the `TrieHard` variant
+    pub variant: &'static str,
+    /// Number of bits in the masks of the variant
+    pub bits: u32,
+    /// Mask of every byte that appears in the keys
+    pub masks: Vec<(u8, MaskParts)>,
+    /// Nodes of the trie in order
+    pub nodes: Vec<SkeletonNode<'a>>,
+    /// Number of entries in the subtree of each node
+    pub below: &'a [usize],
+}
+
+/// A node of a [`Skeleton`], matching the `const` constructor of the same name
+#[derive(Debug)]
+pub enum SkeletonNode<'a> {
+    /// Key and value index of a leaf
+    Leaf(&'a [u8], usize),
+    /// Mask and edge start of a search node
+    Search(MaskParts, usize),
+    /// Key, value index, mask and edge start of a search node with an entry
+    SearchOrLeaf(&'a [u8], usize, MaskParts, usize),
+    /// Bytes of a run and the index of the node at its end
+    Run(&'a [u8], usize),
+}
+
+/// Describe the layout of a trie whose values are indices into a list of
+/// value expressions
+pub fn skeleton<'a>(trie: &'a TrieHard<'_, usize>) -> Skeleton<'a> {
+    match trie {
+        TrieHard::U8(trie) => skeleton_sized("U8", 8, trie),
+        TrieHard::U16(trie) => skeleton_sized("U16", 16, trie),
+        TrieHard::U32(trie) => skeleton_sized("U32", 32, trie),
+        TrieHard::U64(trie) => skeleton_sized("U64", 64, trie),
+        TrieHard::U128(trie) => skeleton_sized("U128", 128, trie),
+        TrieHard::U256(trie) => skeleton_sized("U256", 256, trie),
+    }
+}
+
+fn skeleton_sized<'a, I>(
+    variant: &'static str,
+    bits: u32,
+    trie: &'a TrieHardSized<'_, usize, I>,
+) -> Skeleton<'a>
+where
+    I: ToMaskParts,
+{
+    let masks = trie
+        .masks
+        .by_byte
+        .iter()
+        .enumerate()
+        .map(|(byte, mask)| (byte as u8, mask.to_mask_parts()))
+        .filter(|(_, mask)| *mask != [0; 4])
+        .collect();
+
+    let nodes = trie
+        .nodes
+        .iter()
+        .map(|node| match node {
+            TrieState::Leaf(key, value) => SkeletonNode::Leaf(key, *value),
+            TrieState::Search(search) => SkeletonNode::Search(
+                search.mask.to_mask_parts(),
+                search.edge_start,
+            ),
+            TrieState::SearchOrLeaf(key, value, search) => {
+                SkeletonNode::SearchOrLeaf(
+                    key,
+                    *value,
+                    search.mask.to_mask_parts(),
+                    search.edge_start,
+                )
+            }
+            TrieState::Run(run, next) => SkeletonNode::Run(run, *next),
+        })
+        .collect();
+
+    Skeleton {
+        variant,
+        bits,
+        masks,
+        nodes,
+        below: &trie.below,
+    }
+}
+
+/// Rust source for a mask of the given number of bits
+fn mask_literal(bits: u32, mask: &MaskParts, krate: &str) -> String {
+    if bits == 256 {
+        let parts = mask.map(|part| format!("{part:#x}"));
+        format!("{krate}::__private::u256([{}])", parts.join(", "))
+    } else {
+        let mask = mask[0] as u128 | (mask[1] as u128) << 64;
+        format!("{mask:#x}_u{bits}")
+    }
+}
+
+/// Render a trie as a Rust expression that builds the same trie in a `const`
+/// context. The values of the trie are indices into `values`, which holds the
+/// source of each value expression, and `krate` is the path that the
+/// generated code uses to refer to this crate.
+pub fn render(
+    trie: &TrieHard<'_, usize>,
+    values: &[String],
+    krate: &str,
+) -> String {
+    let Skeleton {
+        variant,
+        bits,
+        masks,
+        nodes,
+        below,
+    } = skeleton(trie);
+    let mut out = String::new();
+
+    // Writing to a `String` cannot fail
+    let _ = writeln!(
+        out,
+        "{krate}::TrieHard::{variant}({krate}::__private::sized("
+    );
+    let _ = writeln!(
+        out,
+        "    {krate}::__private::masks({}, &[",
+        mask_literal(bits, &[0; 4], krate)
+    );
+    for (byte, mask) in &masks {
+        let _ = writeln!(
+            out,
+            "        (b'{}', {}),",
+            byte.escape_ascii(),
+            mask_literal(bits, mask, krate)
+        );
+    }
+    let _ = writeln!(out, "    ]),");
+
+    let _ = writeln!(out, "    &[");
+    for node in &nodes {
+        let _ = match node {
+            SkeletonNode::Leaf(key, value) => writeln!(
+                out,
+                "        {krate}::__private::leaf(b\"{}\", {}),",
+                key.escape_ascii(),
+                values[*value]
+            ),
+            SkeletonNode::Search(mask, edge_start) => writeln!(
+                out,
+                "        {krate}::__private::search({}, {edge_start}),",
+                mask_literal(bits, mask, krate),
+            ),
+            SkeletonNode::SearchOrLeaf(key, value, mask, edge_start) => {
+                writeln!(
+                    out,
+                    "        {krate}::__private::search_or_leaf(b\"{}\", {}, {}, {edge_start}),",
+                    key.escape_ascii(),
+                    values[*value],
+                    mask_literal(bits, mask, krate),
+                )
+            }
+            SkeletonNode::Run(run, next) => writeln!(
+                out,
+                "        {krate}::__private::run(b\"{}\", {next}),",
+                run.escape_ascii(),
+            ),
+        };
+    }
+    let _ = writeln!(out, "    ],");
+    let below = below.iter().map(usize::to_string).collect::<Vec<_>>();
+    let _ = writeln!(out, "    &[{}],", below.join(", "));
+    let _ = write!(out, "))");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case(8, [0x80, 0, 0, 0], "0x80_u8")]
+    #[case(128, [0x1, 0x2, 0, 0], "0x20000000000000001_u128")]
+    #[case(
+        256,
+        [0x1, 0, 0, 0x3],
+        "::trie_hard::__private::u256([0x1, 0x0, 0x0, 0x3])"
+    )]
+    fn test_mask_literal(
+        #[case] bits: u32,
+        #[case] mask: MaskParts,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(mask_literal(bits, &mask, "::trie_hard"), expected);
+    }
+
+    #[test]
+    fn test_mask_parts() {
+        let mask = U256::from_parts([0x1, 0x2, 0x3, 0x4]);
+        assert_eq!(mask.to_mask_parts(), [0x1, 0x2, 0x3, 0x4]);
+        assert_eq!(u128::MAX.to_mask_parts(), [u64::MAX, u64::MAX, 0, 0]);
+    }
+
+    #[test]
+    fn test_render() {
+        let trie = TrieHard::new(vec![(&b"a\""[..], 0), (b"ab", 1)]);
+        let values = ["'x'".to_owned(), "'y'".to_owned()];
+
+        assert_eq!(
+            render(&trie, &values, "::trie_hard"),
+            r#"::trie_hard::TrieHard::U8(::trie_hard::__private::sized(
+    ::trie_hard::__private::masks(0x0_u8, &[
+        (b'\"', 0x1_u8),
+        (b'a', 0x2_u8),
+        (b'b', 0x4_u8),
+    ]),
+    &[
+        ::trie_hard::__private::search(0x2_u8, 1),
+        ::trie_hard::__private::search(0x5_u8, 2),
+        ::trie_hard::__private::leaf(b"a\"", 'x'),
+        ::trie_hard::__private::leaf(b"ab", 'y'),
+    ],
+    &[2, 2, 1, 1],
+))"#
+        );
+    }
+}
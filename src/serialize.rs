@@ -0,0 +1,891 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Binary layout of an encoded trie. All integers are little-endian and `W` is
+// the size in bytes of the integer type used for masks.
+//
+// | Field      | Size           | Contents                                   |
+// | ---------- | -------------- | ------------------------------------------ |
+// | magic      | 4              | `b"THRD"`                                  |
+// | version    | 1              | `VERSION`                                  |
+// | width      | 1              | `W`, one of 1, 2, 4, 8, 16 or 32           |
+// | normalize  | 1              | 0 = none, 1 = ASCII case, 2 = table        |
+// | reserved   | 1              | zero                                       |
+// | node count | 4              | number of node records                     |
+// | keys len   | 4              | length of the key section                  |
+// | values len | 4              | length of the value section                |
+// | table      | 256 if table   | normalized value of each byte              |
+// | byte map   | 32             | bitmap of the bytes that have a mask       |
+// | bit index  | 1 per mapped   | index of the mask bit for each mapped byte |
+// | nodes      | count * (21+W) | fixed size node records                    |
+// | keys       | keys len       | key bytes referenced by the node records   |
+// | values     | values len     | encoded values                             |
+//
+// Every node record has the same size so that a node can be found directly
+// from its index:
+//
+// | Field        | Size | Contents                                          |
+// | ------------ | ---- | ------------------------------------------------- |
+// | tag          | 1    | 0 = leaf, 1 = search, 2 = search or leaf, 3 = run |
+// | mask         | W    | `SearchNode::mask`, zero for leaves and runs      |
+// | edge start   | 4    | `SearchNode::edge_start`, or the node after a run |
+// | key offset   | 4    | start of the key in the key section               |
+// | key len      | 4    | length of the key, or of the bytes of a run       |
+// | value offset | 4    | start of the value in the value section           |
+// | value len    | 4    |                                                   |
+
+use std::{fmt, ops::Range};
+
+use crate::{
+    u256::U256, Normalization, TrieHard, TrieHardSized, TrieHardView, TrieState,
+};
+
+const MAGIC: [u8; 4] = *b"THRD";
+/// Bumped whenever a reader of the previous version could misread the
+/// output. Version 2 added run records.
+const VERSION: u8 = 2;
+const BYTE_MAP_LEN: usize = 32;
+
+const NORMALIZE_NONE: u8 = 0;
+const NORMALIZE_ASCII_CASE: u8 = 1;
+const NORMALIZE_TABLE: u8 = 2;
+
+pub(crate) const TAG_LEAF: u8 = 0;
+pub(crate) const TAG_SEARCH: u8 = 1;
+pub(crate) const TAG_SEARCH_OR_LEAF: u8 = 2;
+pub(crate) const TAG_RUN: u8 = 3;
+
+/// Error returned when a byte slice does not hold a valid encoded trie
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The input ended before the end of the encoded trie
+    UnexpectedEnd,
+    /// The input does not start with the trie-hard magic bytes
+    BadMagic,
+    /// The input was encoded with an unsupported version of the format
+    UnsupportedVersion(u8),
+    /// The mask width is not the size of any supported integer type
+    InvalidWidth(u8),
+    /// The trie uses a key normalization that is not supported
+    UnsupportedNormalization(u8),
+    /// The reserved header byte is not zero
+    InvalidReserved(u8),
+    /// The mask for the given byte does not fit in the mask width
+    InvalidMask(u8),
+    /// The node with the given index is malformed or not part of a valid trie
+    InvalidNode(usize),
+    /// The value of the node with the given index could not be decoded
+    InvalidValue(usize),
+    /// There is data left over after the end of the encoded trie
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::BadMagic => write!(f, "input is not an encoded trie"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported format version {version}")
+            }
+            DecodeError::UnsupportedNormalization(normalization) => {
+                write!(f, "unsupported key normalization {normalization}")
+            }
+            DecodeError::InvalidReserved(reserved) => {
+                write!(f, "invalid reserved header byte {reserved:#04x}")
+            }
+            DecodeError::InvalidWidth(width) => {
+                write!(f, "invalid mask width {width}")
+            }
+            DecodeError::InvalidMask(byte) => {
+                write!(f, "invalid mask for byte {byte:#04x}")
+            }
+            DecodeError::InvalidNode(index) => {
+                write!(f, "invalid node at index {index}")
+            }
+            DecodeError::InvalidValue(index) => {
+                write!(f, "invalid value for node at index {index}")
+            }
+            DecodeError::TrailingBytes => {
+                write!(f, "unexpected data after the encoded trie")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Values that can be written into the binary encoding of a trie
+pub trait EncodeValue {
+    /// Append the encoded form of this value to `buf`
+    fn encode(&self, buf: &mut Vec<u8>);
+}
+
+/// Values that can be read from the binary encoding of a trie. The bytes
+/// passed to [`DecodeValue::decode`] are exactly the bytes written by
+/// [`EncodeValue::encode`], and they live as long as the encoded trie, so
+/// decoded values can borrow from them.
+pub trait DecodeValue<'a>: Sized {
+    /// Decode a value, returning `None` if the bytes are not a valid encoding
+    fn decode(bytes: &'a [u8]) -> Option<Self>;
+}
+
+impl<T> EncodeValue for &T
+where
+    T: EncodeValue + ?Sized,
+{
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (**self).encode(buf)
+    }
+}
+
+macro_rules! value_int_impls {
+    ($($int_type:ty),+) => {
+        $(
+            impl EncodeValue for $int_type {
+                fn encode(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes())
+                }
+            }
+
+            impl<'a> DecodeValue<'a> for $int_type {
+                fn decode(bytes: &'a [u8]) -> Option<Self> {
+                    Some(Self::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )+
+    };
+}
+
+value_int_impls! {u8, u16, u32, u64, u128, i8, i16, i32, i64, i128}
+
+impl EncodeValue for usize {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as u64).encode(buf)
+    }
+}
+
+impl<'a> DecodeValue<'a> for usize {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        u64::decode(bytes)?.try_into().ok()
+    }
+}
+
+impl EncodeValue for isize {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        (*self as i64).encode(buf)
+    }
+}
+
+impl<'a> DecodeValue<'a> for isize {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        i64::decode(bytes)?.try_into().ok()
+    }
+}
+
+impl EncodeValue for bool {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8)
+    }
+}
+
+impl<'a> DecodeValue<'a> for bool {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl EncodeValue for () {
+    fn encode(&self, _buf: &mut Vec<u8>) {}
+}
+
+impl<'a> DecodeValue<'a> for () {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        bytes.is_empty().then_some(())
+    }
+}
+
+impl EncodeValue for [u8] {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self)
+    }
+}
+
+impl<'a> DecodeValue<'a> for &'a [u8] {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        Some(bytes)
+    }
+}
+
+impl EncodeValue for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self)
+    }
+}
+
+impl<'a> DecodeValue<'a> for Vec<u8> {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl EncodeValue for str {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes())
+    }
+}
+
+impl<'a> DecodeValue<'a> for &'a str {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        std::str::from_utf8(bytes).ok()
+    }
+}
+
+impl EncodeValue for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes())
+    }
+}
+
+impl<'a> DecodeValue<'a> for String {
+    fn decode(bytes: &'a [u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+/// Size in bytes of the fields following the mask in a node record
+const FIELDS_LEN: usize = 4 * 5;
+
+/// Size in bytes of a node record for the given mask width
+const fn record_len(width: usize) -> usize {
+    1 + width + FIELDS_LEN
+}
+
+fn push_u32(buf: &mut Vec<u8>, value: usize) {
+    let value = u32::try_from(value)
+        .expect("Encoded tries are limited to 4 GiB of keys and values");
+    buf.extend_from_slice(&value.to_le_bytes())
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> usize {
+    // Safety: Callers only read from within fixed size records and headers
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<usize, DecodeError> {
+        Ok(read_u32(self.take(4)?, 0))
+    }
+}
+
+/// The sections of an encoded trie. Parsing only checks that the sections fit
+/// the input; the node records themselves are checked when a view is created
+/// over them.
+#[derive(Debug, Clone)]
+pub(crate) struct Layout<'a> {
+    pub(crate) width: usize,
+    pub(crate) normalization: Normalization,
+    pub(crate) bit_indices: [Option<u8>; 256],
+    pub(crate) node_count: usize,
+    records: &'a [u8],
+    keys: &'a [u8],
+    values: &'a [u8],
+}
+
+/// A single node record in an encoded trie. Fields are read on demand so
+/// that walking the trie only touches the tag, mask and edge start.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Record<'a>(&'a [u8]);
+
+impl<'a> Record<'a> {
+    pub(crate) fn tag(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub(crate) fn mask(&self) -> &'a [u8] {
+        &self.0[1..self.0.len() - FIELDS_LEN]
+    }
+
+    pub(crate) fn edge_start(&self) -> usize {
+        read_u32(self.0, self.0.len() - FIELDS_LEN)
+    }
+
+    fn key_span(&self) -> Option<Range<usize>> {
+        self.span(self.0.len() - FIELDS_LEN + 4)
+    }
+
+    fn value_span(&self) -> Option<Range<usize>> {
+        self.span(self.0.len() - FIELDS_LEN + 12)
+    }
+
+    fn span(&self, at: usize) -> Option<Range<usize>> {
+        let start = read_u32(self.0, at);
+        Some(start..start.checked_add(read_u32(self.0, at + 4))?)
+    }
+}
+
+impl<'a> Layout<'a> {
+    pub(crate) fn parse(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader(bytes);
+
+        if reader
+            .take(MAGIC.len())
+            .map_err(|_| DecodeError::BadMagic)?
+            != MAGIC
+        {
+            return Err(DecodeError::BadMagic);
+        }
+
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+
+        let width = reader.u8()?;
+        if !matches!(width, 1 | 2 | 4 | 8 | 16 | 32) {
+            return Err(DecodeError::InvalidWidth(width));
+        }
+        let width = width as usize;
+
+        let normalization = reader.u8()?;
+        let reserved = reader.u8()?;
+        if reserved != 0 {
+            return Err(DecodeError::InvalidReserved(reserved));
+        }
+        let node_count = reader.u32()?;
+        let keys_len = reader.u32()?;
+        let values_len = reader.u32()?;
+
+        let normalization = match normalization {
+            NORMALIZE_NONE => Normalization::None,
+            NORMALIZE_ASCII_CASE => Normalization::AsciiCase,
+            NORMALIZE_TABLE => {
+                // Safety: take returns exactly the number of bytes asked for
+                let table = reader.take(256)?.try_into().unwrap();
+                Normalization::Table(Box::new(table))
+            }
+            other => return Err(DecodeError::UnsupportedNormalization(other)),
+        };
+
+        let byte_map = reader.take(BYTE_MAP_LEN)?;
+        let mut bit_indices = [None; 256];
+        for (byte, bit_index) in bit_indices.iter_mut().enumerate() {
+            if byte_map[byte / 8] & (1 << (byte % 8)) == 0 {
+                continue;
+            }
+            let bit = reader.u8()?;
+            if bit as usize >= width * 8 {
+                return Err(DecodeError::InvalidMask(byte as u8));
+            }
+            *bit_index = Some(bit);
+        }
+
+        let records_len = node_count
+            .checked_mul(record_len(width))
+            .ok_or(DecodeError::UnexpectedEnd)?;
+        let records = reader.take(records_len)?;
+        let keys = reader.take(keys_len)?;
+        let values = reader.take(values_len)?;
+
+        if !reader.0.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(Self {
+            width,
+            normalization,
+            bit_indices,
+            node_count,
+            records,
+            keys,
+            values,
+        })
+    }
+
+    /// Read the record for the node at `index`, which must be less than the
+    /// node count
+    pub(crate) fn record(&self, index: usize) -> Record<'a> {
+        let len = record_len(self.width);
+        Record(&self.records[index * len..(index + 1) * len])
+    }
+
+    /// The key of a record, or `None` if it lies outside of the key section
+    pub(crate) fn key(&self, record: &Record<'a>) -> Option<&'a [u8]> {
+        self.keys.get(record.key_span()?)
+    }
+
+    /// The value bytes of a record, or `None` if they lie outside of the value
+    /// section
+    pub(crate) fn value(&self, record: &Record<'a>) -> Option<&'a [u8]> {
+        self.values.get(record.value_span()?)
+    }
+}
+
+impl<'a, T> TrieHard<'a, T> {
+    /// Encode the trie into a compact binary format that can be loaded again
+    /// with [`TrieHard::from_bytes`], skipping the cost of building the trie.
+    /// The entries are laid out as building a trie from them would, so the
+    /// output only depends on the keys and values and not on how they were
+    /// inserted or removed. The encoding records the integer size of that
+    /// layout, which may be narrower than the trie itself after removals.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys or the encoded values add up to 4 GiB or more.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let bytes = trie.to_bytes();
+    /// let loaded = TrieHard::<'_, &str>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(loaded.get("dad"), Some("dad"));
+    /// assert!(loaded.get("don't").is_none());
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8>
+    where
+        T: EncodeValue,
+    {
+        // Incremental updates leave dead slots, chains of single children
+        // that are not merged into runs and masks for bytes no key uses any
+        // more, so the entries are laid out afresh. The values are borrowed.
+        let entries = match self {
+            TrieHard::U8(trie) => trie.entry_refs(),
+            TrieHard::U16(trie) => trie.entry_refs(),
+            TrieHard::U32(trie) => trie.entry_refs(),
+            TrieHard::U64(trie) => trie.entry_refs(),
+            TrieHard::U128(trie) => trie.entry_refs(),
+            TrieHard::U256(trie) => trie.entry_refs(),
+        };
+        let trie = TrieHard::build(entries, self.normalization().clone());
+
+        let mut out = Vec::new();
+        match &trie {
+            TrieHard::U8(trie) => trie.encode(&mut out),
+            TrieHard::U16(trie) => trie.encode(&mut out),
+            TrieHard::U32(trie) => trie.encode(&mut out),
+            TrieHard::U64(trie) => trie.encode(&mut out),
+            TrieHard::U128(trie) => trie.encode(&mut out),
+            TrieHard::U256(trie) => trie.encode(&mut out),
+        }
+        out
+    }
+
+    /// Load a trie encoded with [`TrieHard::to_bytes`]. Keys are borrowed
+    /// from the given bytes rather than copied. The input is fully validated,
+    /// so corrupt or truncated data results in an error rather than a panic
+    /// or a trie that misbehaves.
+    ///
+    /// ```
+    /// # use trie_hard::{DecodeError, TrieHard};
+    /// let trie = TrieHard::new(vec![(b"and", 0_u32), (b"ant", 1)]);
+    /// let bytes = trie.to_bytes();
+    ///
+    /// let loaded = TrieHard::<'_, u32>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(loaded.get("ant"), Some(1));
+    ///
+    /// assert_eq!(
+    ///     TrieHard::<'_, u32>::from_bytes(&bytes[..bytes.len() - 1])
+    ///         .unwrap_err(),
+    ///     DecodeError::UnexpectedEnd
+    /// );
+    /// ```
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DecodeError>
+    where
+        T: DecodeValue<'a>,
+    {
+        Ok(match TrieHardView::from_bytes(bytes)? {
+            TrieHardView::U8(view) => TrieHard::U8(view.into_trie()?),
+            TrieHardView::U16(view) => TrieHard::U16(view.into_trie()?),
+            TrieHardView::U32(view) => TrieHard::U32(view.into_trie()?),
+            TrieHardView::U64(view) => TrieHard::U64(view.into_trie()?),
+            TrieHardView::U128(view) => TrieHard::U128(view.into_trie()?),
+            TrieHardView::U256(view) => TrieHard::U256(view.into_trie()?),
+        })
+    }
+}
+
+macro_rules! serialize_impls {
+    ($($int_type:ty),+) => {
+        $(
+            serialize_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            fn encode(&self, out: &mut Vec<u8>)
+            where
+                T: EncodeValue
+            {
+                const WIDTH: usize = std::mem::size_of::<$int_type>();
+
+                // Write nodes in breadth-first order from the root, which is
+                // the order a freshly built trie already has them in
+                let mut order = Vec::with_capacity(self.nodes.len() - self.dead);
+                let mut edge_starts = Vec::with_capacity(order.capacity());
+                if !self.nodes.is_empty() {
+                    order.push(0);
+                }
+
+                let mut next = 0;
+                while let Some(node_index) = order.get(next).copied() {
+                    match &self.nodes[node_index] {
+                        TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) => {
+                            let child_count = search.mask.count_ones() as usize;
+                            edge_starts.push(order.len());
+                            order.extend(
+                                search.edge_start..search.edge_start + child_count
+                            );
+                        }
+                        TrieState::Run(_, next) => {
+                            edge_starts.push(order.len());
+                            order.push(*next);
+                        }
+                        TrieState::Leaf(..) => edge_starts.push(0),
+                    }
+                    next += 1;
+                }
+
+                let mut records = Vec::with_capacity(order.len() * record_len(WIDTH));
+                let mut keys = Vec::new();
+                let mut values = Vec::new();
+
+                for (node_index, edge_start) in order.iter().zip(edge_starts) {
+                    let (tag, mask, key, value) = match &self.nodes[*node_index] {
+                        TrieState::Leaf(k, v) => {
+                            (TAG_LEAF, Default::default(), Some(k), Some(v))
+                        }
+                        TrieState::Search(search) => {
+                            (TAG_SEARCH, search.mask, None, None)
+                        }
+                        TrieState::SearchOrLeaf(k, v, search) => {
+                            (TAG_SEARCH_OR_LEAF, search.mask, Some(k), Some(v))
+                        }
+                        TrieState::Run(run, _) => {
+                            (TAG_RUN, Default::default(), Some(run), None)
+                        }
+                    };
+
+                    records.push(tag);
+                    records.extend_from_slice(&mask.to_le_bytes());
+                    push_u32(&mut records, edge_start);
+
+                    let (key_start, key_len) = match key {
+                        Some(k) => {
+                            keys.extend_from_slice(k);
+                            (keys.len() - k.len(), k.len())
+                        }
+                        None => (0, 0),
+                    };
+                    let (value_start, value_len) = match value {
+                        Some(v) => {
+                            let value_start = values.len();
+                            v.encode(&mut values);
+                            (value_start, values.len() - value_start)
+                        }
+                        None => (0, 0),
+                    };
+                    push_u32(&mut records, key_start);
+                    push_u32(&mut records, key_len);
+                    push_u32(&mut records, value_start);
+                    push_u32(&mut records, value_len);
+                }
+
+                out.extend_from_slice(&MAGIC);
+                out.push(VERSION);
+                out.push(WIDTH as u8);
+                out.push(match self.normalization {
+                    Normalization::None => NORMALIZE_NONE,
+                    Normalization::AsciiCase => NORMALIZE_ASCII_CASE,
+                    Normalization::Table(_) => NORMALIZE_TABLE,
+                });
+                out.push(0);
+                push_u32(out, order.len());
+                push_u32(out, keys.len());
+                push_u32(out, values.len());
+                if let Normalization::Table(table) = &self.normalization {
+                    out.extend_from_slice(&table[..]);
+                }
+
+                let mut byte_map = [0_u8; BYTE_MAP_LEN];
+                let mut bit_indices = Vec::new();
+                for (byte, mask) in self.masks.by_byte.iter().enumerate() {
+                    if *mask != 0 {
+                        byte_map[byte / 8] |= 1 << (byte % 8);
+                        bit_indices.push(mask.trailing_zeros() as u8);
+                    }
+                }
+                out.extend_from_slice(&byte_map);
+                out.extend_from_slice(&bit_indices);
+
+                out.extend_from_slice(&records);
+                out.extend_from_slice(&keys);
+                out.extend_from_slice(&values);
+            }
+        }
+    };
+}
+
+serialize_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split(|c: char| c.is_whitespace())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/1984.txt"))]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_round_trip(#[case] text: &str) {
+        let words = words(text);
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+
+        let bytes = trie.to_bytes();
+        let loaded = TrieHard::<'_, &str>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            std::mem::discriminant(&trie),
+            std::mem::discriminant(&loaded)
+        );
+        for word in &words {
+            assert_eq!(loaded.get(word), Some(*word));
+        }
+        assert!(trie.iter().eq(loaded.iter()));
+        assert_eq!(loaded.to_bytes(), bytes);
+    }
+
+    #[rstest]
+    #[case(8)]
+    #[case(16)]
+    #[case(32)]
+    #[case(64)]
+    #[case(128)]
+    #[case(256)]
+    fn test_sizes(#[case] bits: usize) {
+        let keys = (0..bits)
+            .map(|b| [b as u8, 0xFF - b as u8])
+            .collect::<Vec<_>>();
+        let trie = TrieHard::new(
+            keys.iter().enumerate().map(|(i, k)| (&k[..], i)).collect(),
+        );
+
+        let bytes = trie.to_bytes();
+        let loaded = TrieHard::<'_, usize>::from_bytes(&bytes).unwrap();
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(loaded.get(key), Some(i));
+        }
+        assert!(trie.iter().eq(loaded.iter()));
+    }
+
+    #[test]
+    fn test_empty() {
+        let trie = TrieHard::<'_, u8>::default();
+        let bytes = trie.to_bytes();
+        let loaded = TrieHard::<'_, u8>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(loaded.iter().count(), 0);
+        assert_eq!(loaded.get(""), None);
+    }
+
+    #[test]
+    fn test_incremental_matches_bulk() {
+        let mut trie = ["and", "ant", "dad", "do", "dot"]
+            .into_iter()
+            .collect::<TrieHard<'_, _>>();
+        trie.insert("dots", "dots");
+        trie.insert("an", "an");
+        trie.remove("dad");
+
+        let bulk = ["an", "and", "ant", "do", "dot", "dots"]
+            .into_iter()
+            .collect::<TrieHard<'_, _>>();
+
+        assert_eq!(trie.to_bytes(), bulk.to_bytes());
+
+        // Emptying a branch leaves a chain of single children that a bulk
+        // build holds in a run, and "zoo" adds masks that outlive it
+        let words = words(include_str!("../data/sun-rising.txt"));
+        let mut trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        trie.insert("zoo", "zoo");
+        let mut kept = Vec::new();
+        for (i, word) in words.iter().enumerate() {
+            if i % 3 == 0 {
+                trie.remove(word);
+            } else {
+                kept.push(*word);
+            }
+        }
+        trie.remove("zoo");
+
+        let bulk = kept.into_iter().collect::<TrieHard<'_, _>>();
+        assert_eq!(trie.to_bytes(), bulk.to_bytes());
+    }
+
+    #[rstest]
+    #[case(b"", DecodeError::BadMagic)]
+    #[case(b"THR", DecodeError::BadMagic)]
+    #[case(b"TRIE\x01\x01\x00\x00", DecodeError::BadMagic)]
+    #[case(b"THRD\x01\x01\x00\x00", DecodeError::UnsupportedVersion(1))]
+    #[case(b"THRD\x03\x01\x00\x00", DecodeError::UnsupportedVersion(3))]
+    #[case(b"THRD\x02\x03\x00\x00", DecodeError::InvalidWidth(3))]
+    #[case(b"THRD\x02\x01\x00\x01", DecodeError::InvalidReserved(1))]
+    #[case(b"THRD\x02\x01\x00\x00", DecodeError::UnexpectedEnd)]
+    fn test_bad_header(#[case] bytes: &[u8], #[case] expected: DecodeError) {
+        assert_eq!(
+            TrieHard::<'_, ()>::from_bytes(bytes).unwrap_err(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_bad_values() {
+        let trie = TrieHard::new(vec![(&b"a"[..], 1_u16), (b"b", 2)]);
+        let bytes = trie.to_bytes();
+
+        assert_eq!(
+            TrieHard::<'_, u32>::from_bytes(&bytes).unwrap_err(),
+            DecodeError::InvalidValue(1)
+        );
+    }
+
+    #[test]
+    fn test_trailing_bytes() {
+        let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+        let mut bytes = trie.to_bytes();
+        bytes.push(0);
+
+        assert_eq!(
+            TrieHard::<'_, &str>::from_bytes(&bytes).unwrap_err(),
+            DecodeError::TrailingBytes
+        );
+    }
+
+    #[test]
+    fn test_corruption_never_panics() {
+        let words = words(include_str!("../data/sun-rising.txt"));
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let bytes = trie.to_bytes();
+
+        for len in 0..bytes.len() {
+            assert!(TrieHard::<'_, &str>::from_bytes(&bytes[..len]).is_err());
+        }
+
+        for at in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xFF] {
+                let mut corrupt = bytes.clone();
+                corrupt[at] ^= flip;
+
+                // Anything that loads must still behave like a trie
+                if let Ok(mut loaded) =
+                    TrieHard::<'_, &str>::from_bytes(&corrupt)
+                {
+                    for word in &words {
+                        loaded.get(word);
+                        loaded.prefix_search(word).count();
+                    }
+                    assert!(loaded.iter().count() <= words.len());
+                    loaded.insert("fresh", "fresh");
+                    assert_eq!(loaded.get("fresh"), Some("fresh"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_ascii_case_insensitive_round_trip() {
+        let trie = crate::TrieHardBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(vec![(&b"Accept"[..], 0u8), (b"host", 1)]);
+        let bytes = trie.to_bytes();
+
+        let decoded = TrieHard::<u8>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get("ACCEPT"), Some(0));
+        assert_eq!(decoded.get("Host"), Some(1));
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        let view = TrieHardView::<u8>::from_bytes(&bytes).unwrap();
+        assert_eq!(view.get("accept"), Some(0));
+        assert_eq!(view.get("HOST"), Some(1));
+    }
+
+    #[test]
+    fn test_unsupported_normalization() {
+        let mut bytes = TrieHard::new(vec![(&b"a"[..], 0u8)]).to_bytes();
+        bytes[6] = 0xff;
+        assert_eq!(
+            TrieHard::<u8>::from_bytes(&bytes).unwrap_err(),
+            DecodeError::UnsupportedNormalization(0xff)
+        );
+    }
+
+    #[test]
+    fn test_normalization_table_round_trip() {
+        let mut table: [u8; 256] = std::array::from_fn(|c| c as u8);
+        table[b'_' as usize] = b'-';
+        let trie = crate::TrieHardBuilder::new()
+            .normalization_table(table)
+            .build(vec![(&b"x-real-ip"[..], 0u8)]);
+        let bytes = trie.to_bytes();
+
+        let decoded = TrieHard::<u8>::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.get("x_real_ip"), Some(0));
+        assert_eq!(decoded.to_bytes(), bytes);
+
+        let view = TrieHardView::<u8>::from_bytes(&bytes).unwrap();
+        assert_eq!(view.get("x_real-ip"), Some(0));
+
+        assert_eq!(
+            TrieHard::<u8>::from_bytes(&bytes[..100]).unwrap_err(),
+            DecodeError::UnexpectedEnd
+        );
+    }
+}
@@ -0,0 +1,469 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::mem::size_of;
+
+use crate::{
+    u256::U256, MasksByByteSized, Normalization, TrieHard, TrieHardSized,
+    TrieState,
+};
+
+/// Number of nodes of one kind in a trie and the bytes they take up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodeUsage {
+    /// Number of nodes
+    pub count: usize,
+    /// Bytes taken up by the nodes in the node array
+    pub bytes: usize,
+}
+
+impl NodeUsage {
+    fn add(&mut self, count: usize, node_size: usize) {
+        self.count += count;
+        self.bytes += count * node_size;
+    }
+}
+
+/// Breakdown of the memory used by a trie, returned by
+/// [`TrieHard::memory_usage`]. Every node takes up the same number of bytes
+/// whatever its kind, since nodes are stored in a single array.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MemoryUsage {
+    /// Bytes of the table holding the mask of every byte, along with the
+    /// byte for every mask bit that is used to walk the edges in order
+    pub masks: usize,
+    /// Leaf nodes, which hold the rest of a key that no other key shares
+    pub leaf: NodeUsage,
+    /// Search nodes that do not hold an entry
+    pub search: NodeUsage,
+    /// Search nodes that also hold the entry for a key ending there
+    pub search_or_leaf: NodeUsage,
+    /// Nodes holding a run of bytes shared by every key below them
+    pub run: NodeUsage,
+    /// Slots of the node array left unreachable by inserts and removals.
+    /// They are reclaimed once they outnumber the live nodes.
+    pub dead: NodeUsage,
+    /// Bytes allocated for the node array beyond its last node
+    pub unused_capacity: usize,
+    /// Bytes of the entry counts kept for [`TrieHard::rank`],
+    /// [`TrieHard::select`] and [`TrieHard::count_prefix`], one per node
+    pub counts: usize,
+    /// Bytes of the normalization table, if the trie was built with one
+    pub normalization: usize,
+    /// Total length of the keys of all entries. Keys are borrowed rather
+    /// than copied, so this memory belongs to whoever owns the keys and is
+    /// not included in [`MemoryUsage::total`].
+    pub key_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// All nodes in the node array, including dead ones
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let usage = trie.memory_usage();
+    /// assert_eq!(
+    ///     usage.nodes().count,
+    ///     usage.leaf.count + usage.search.count + usage.run.count
+    /// );
+    /// ```
+    pub fn nodes(&self) -> NodeUsage {
+        [
+            self.leaf,
+            self.search,
+            self.search_or_leaf,
+            self.run,
+            self.dead,
+        ]
+        .into_iter()
+        .fold(NodeUsage::default(), |total, usage| NodeUsage {
+            count: total.count + usage.count,
+            bytes: total.bytes + usage.bytes,
+        })
+    }
+
+    /// Bytes used by the trie itself: the masks, the node array including its
+    /// unused capacity, the entry counts and the normalization table. Keys
+    /// and any memory owned by the values are not included.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let usage = trie.memory_usage();
+    /// assert_eq!(
+    ///     usage.total(),
+    ///     usage.masks
+    ///         + usage.nodes().bytes
+    ///         + usage.unused_capacity
+    ///         + usage.counts
+    /// );
+    /// ```
+    pub fn total(&self) -> usize {
+        self.masks
+            + self.nodes().bytes
+            + self.unused_capacity
+            + self.counts
+            + self.normalization
+    }
+}
+
+/// Shape of a trie, returned by [`TrieHard::stats`]. Depths count the nodes
+/// visited by a lookup before it reaches the node holding an entry, so an
+/// entry in the root has a depth of zero. Only nodes reachable from the root
+/// are counted.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[non_exhaustive]
+pub struct TrieStats {
+    /// Number of entries in the trie
+    pub entries: usize,
+    /// Depth of the deepest entry
+    pub max_depth: usize,
+    /// Mean depth of all entries, or zero for an empty trie
+    pub average_depth: f64,
+    /// Number of search nodes by their number of children, so `fan_out[2]`
+    /// is the number of search nodes with two children. Search nodes always
+    /// have at least one child.
+    pub fan_out: Vec<usize>,
+    /// Number of leaf nodes
+    pub leaf: usize,
+    /// Number of search nodes that do not hold an entry
+    pub search: usize,
+    /// Number of search nodes that also hold an entry
+    pub search_or_leaf: usize,
+    /// Number of nodes holding a run of bytes shared by every key below them
+    pub run: usize,
+}
+
+impl<'a, T> TrieHard<'a, T> {
+    /// Measure the memory used by the trie, broken down by the kind of node.
+    /// This walks every node, so it takes time proportional to the size of
+    /// the trie.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let mut trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let usage = trie.memory_usage();
+    /// assert_eq!(usage.leaf.count, 4);
+    /// assert_eq!(usage.search_or_leaf.count, 1);
+    /// assert_eq!(usage.dead.count, 0);
+    /// assert_eq!(usage.key_bytes, 14);
+    ///
+    /// trie.remove("dad");
+    /// assert_eq!(trie.memory_usage().dead.count, 1);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        match self {
+            TrieHard::U8(trie) => trie.memory_usage(),
+            TrieHard::U16(trie) => trie.memory_usage(),
+            TrieHard::U32(trie) => trie.memory_usage(),
+            TrieHard::U64(trie) => trie.memory_usage(),
+            TrieHard::U128(trie) => trie.memory_usage(),
+            TrieHard::U256(trie) => trie.memory_usage(),
+        }
+    }
+
+    /// Describe the shape of the trie: how deep its entries are, how many
+    /// children its search nodes have and how many nodes there are of each
+    /// kind. Long chains of single children are stored as runs, so a large
+    /// number of search nodes with one child, or a maximum depth far above
+    /// the average, points to keys that will be slow to look up.
+    ///
+    /// ```
+    /// # use trie_hard::TrieHard;
+    /// let trie = ["and", "ant", "dad", "do", "dot"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    ///
+    /// let stats = trie.stats();
+    /// assert_eq!(stats.entries, 5);
+    /// assert_eq!(stats.max_depth, 3);
+    /// assert_eq!(stats.average_depth, 2.6);
+    /// assert_eq!(stats.fan_out, [0, 2, 3]);
+    /// assert_eq!((stats.leaf, stats.search, stats.search_or_leaf), (4, 4, 1));
+    /// ```
+    pub fn stats(&self) -> TrieStats {
+        match self {
+            TrieHard::U8(trie) => trie.stats(),
+            TrieHard::U16(trie) => trie.stats(),
+            TrieHard::U32(trie) => trie.stats(),
+            TrieHard::U64(trie) => trie.stats(),
+            TrieHard::U128(trie) => trie.stats(),
+            TrieHard::U256(trie) => trie.stats(),
+        }
+    }
+}
+
+macro_rules! stats_impls {
+    ($($int_type:ty),+) => {
+        $(
+            stats_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl<'a, T> TrieHardSized<'a, T, $int_type> {
+            /// Measure the memory used by the trie, broken down by the kind of
+            /// node
+            ///
+            /// ```
+            /// # use std::mem::size_of;
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// let usage = sized_trie.memory_usage();
+            /// assert_eq!(usage.masks, 2 * 256 + size_of::<usize>());
+            /// assert_eq!(usage.nodes().count, 9);
+            /// ```
+            pub fn memory_usage(&self) -> MemoryUsage {
+                let node_size = size_of::<TrieState<'a, T, $int_type>>();
+                let mut usage = MemoryUsage {
+                    masks: size_of::<MasksByByteSized<$int_type>>(),
+                    unused_capacity: (self.nodes.capacity() - self.nodes.len()) * node_size,
+                    counts: self.below.len() * size_of::<usize>(),
+                    normalization: match &self.normalization {
+                        Normalization::Table(table) => size_of_val(&**table),
+                        _ => 0,
+                    },
+                    ..Default::default()
+                };
+
+                // Only nodes reachable from the root are counted by kind
+                let mut reachable = 0;
+                self.walk(|state, _| {
+                    reachable += 1;
+                    match state {
+                        TrieState::Leaf(key, _) => {
+                            usage.leaf.add(1, node_size);
+                            usage.key_bytes += key.len();
+                        }
+                        TrieState::Search(_) => usage.search.add(1, node_size),
+                        TrieState::SearchOrLeaf(key, _, _) => {
+                            usage.search_or_leaf.add(1, node_size);
+                            usage.key_bytes += key.len();
+                        }
+                        TrieState::Run(..) => usage.run.add(1, node_size),
+                    }
+                });
+
+                usage.dead.add(self.nodes.len() - reachable, node_size);
+                usage
+            }
+
+            /// Describe the shape of the trie: how deep its entries are, how
+            /// many children its search nodes have and how many nodes there
+            /// are of each kind
+            ///
+            /// ```
+            /// # use trie_hard::TrieHard;
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            ///
+            /// let TrieHard::U8(sized_trie) = trie else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// let stats = sized_trie.stats();
+            /// assert_eq!(stats.max_depth, 3);
+            /// assert_eq!(stats.fan_out, [0, 2, 3]);
+            /// ```
+            pub fn stats(&self) -> TrieStats {
+                let mut stats = TrieStats::default();
+                let mut total_depth = 0;
+
+                self.walk(|state, depth| {
+                    if state.value().is_some() {
+                        stats.entries += 1;
+                        stats.max_depth = stats.max_depth.max(depth);
+                        total_depth += depth;
+                    }
+
+                    match state {
+                        TrieState::Leaf(..) => stats.leaf += 1,
+                        TrieState::Search(_) => stats.search += 1,
+                        TrieState::SearchOrLeaf(..) => stats.search_or_leaf += 1,
+                        TrieState::Run(..) => stats.run += 1,
+                    }
+
+                    if let TrieState::Search(search)
+                        | TrieState::SearchOrLeaf(_, _, search) = state
+                    {
+                        let children = search.mask.count_ones() as usize;
+                        if stats.fan_out.len() <= children {
+                            stats.fan_out.resize(children + 1, 0);
+                        }
+                        stats.fan_out[children] += 1;
+                    }
+                });
+
+                if stats.entries > 0 {
+                    stats.average_depth = total_depth as f64 / stats.entries as f64;
+                }
+                stats
+            }
+
+            /// Visit every node reachable from the root along with the number
+            /// of nodes above it
+            fn walk(&self, mut visit: impl FnMut(&TrieState<'a, T, $int_type>, usize)) {
+                let mut stack = Vec::new();
+                if !self.nodes.is_empty() {
+                    stack.push((0, 0));
+                }
+
+                while let Some((node_index, depth)) = stack.pop() {
+                    let state = &self.nodes[node_index];
+                    stack.extend(state.children().map(|child| (child, depth + 1)));
+                    visit(state, depth);
+                }
+            }
+        }
+    };
+}
+
+stats_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use std::mem::align_of;
+
+    use crate::{u256::U256, TrieHard, TrieHardBuilder};
+
+    #[rstest]
+    #[case(include_str!("../data/1984.txt"))]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_memory_usage(#[case] text: &str) {
+        let words = text
+            .split(|c: char| c.is_whitespace())
+            .collect::<std::collections::BTreeSet<_>>();
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+
+        let usage = trie.memory_usage();
+        assert_eq!(usage.leaf.count + usage.search_or_leaf.count, words.len());
+        assert_eq!(usage.key_bytes, words.iter().map(|w| w.len()).sum());
+        assert_eq!(usage.dead.count, 0);
+        assert_eq!(
+            usage.counts,
+            usage.nodes().count * std::mem::size_of::<usize>()
+        );
+    }
+
+    #[rstest]
+    #[case(include_str!("../data/1984.txt"))]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_stats(#[case] text: &str) {
+        let words = text
+            .split(|c: char| c.is_whitespace())
+            .collect::<std::collections::BTreeSet<_>>();
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+
+        let stats = trie.stats();
+        let usage = trie.memory_usage();
+        assert_eq!(stats.entries, words.len());
+        assert_eq!(stats.leaf, usage.leaf.count);
+        assert_eq!(stats.search, usage.search.count);
+        assert_eq!(stats.search_or_leaf, usage.search_or_leaf.count);
+        assert_eq!(stats.run, usage.run.count);
+        assert_eq!(
+            stats.fan_out.iter().sum::<usize>(),
+            stats.search + stats.search_or_leaf
+        );
+        assert_eq!(stats.fan_out[0], 0);
+        assert!(stats.fan_out.last().is_some_and(|count| *count > 0));
+        assert!(stats.average_depth > 0.0);
+        assert!(stats.average_depth <= stats.max_depth as f64);
+    }
+
+    #[rstest]
+    #[case(&[], 0, 0.0)]
+    #[case(&[""], 0, 0.0)]
+    #[case(&["abc"], 0, 0.0)]
+    #[case(&["", "a", "aaa"], 2, 1.0)]
+    #[case(&["x-cloudflare-ray-id", "x-cloudflare-request-id"], 2, 2.0)]
+    fn test_stats_depth(
+        #[case] input: &[&str],
+        #[case] max_depth: usize,
+        #[case] average_depth: f64,
+    ) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+
+        let stats = trie.stats();
+        assert_eq!(stats.entries, input.len());
+        assert_eq!(stats.max_depth, max_depth);
+        assert_eq!(stats.average_depth, average_depth);
+    }
+
+    #[test]
+    fn test_memory_usage_widths() {
+        let mut previous = 0;
+        for (bits, align) in [
+            (8, align_of::<u8>()),
+            (16, align_of::<u16>()),
+            (32, align_of::<u32>()),
+            (64, align_of::<u64>()),
+            (128, align_of::<u128>()),
+            (256, align_of::<U256>()),
+        ] {
+            let keys = (0..bits).map(|b| [b as u8]).collect::<Vec<_>>();
+            let trie = keys.iter().collect::<TrieHard<'_, _>>();
+
+            // The masks, one byte per bit and the number of bits in use
+            let table = 256 * bits / 8 + 256 + size_of::<usize>();
+            let usage = trie.memory_usage();
+            assert_eq!(usage.masks, table.next_multiple_of(align.max(8)));
+            assert_eq!(usage.leaf.count, bits);
+            assert_eq!(usage.search.count, 1);
+            assert!(usage.leaf.bytes / usage.leaf.count >= previous);
+            previous = usage.leaf.bytes / usage.leaf.count;
+        }
+    }
+
+    #[test]
+    fn test_memory_usage_dead_and_normalization() {
+        let mut table: [u8; 256] = std::array::from_fn(|c| c as u8);
+        table[b'_' as usize] = b'-';
+        let mut trie = TrieHardBuilder::new()
+            .normalization_table(table)
+            .build(vec![(&b"x-a"[..], 0), (b"x-b", 1), (b"y", 2)]);
+
+        assert_eq!(trie.memory_usage().normalization, 256);
+
+        trie.remove("x_a");
+        let usage = trie.memory_usage();
+        assert_eq!(usage.leaf.count, 2);
+        assert_eq!(usage.dead.count, 1);
+        assert_eq!(usage.key_bytes, 4);
+    }
+}
@@ -15,7 +15,8 @@
 use std::{
     cmp::Ordering,
     ops::{
-        Add, AddAssign, BitAnd, BitOrAssign, Shl, ShlAssign, Sub, SubAssign,
+        Add, AddAssign, BitAnd, BitOrAssign, BitXorAssign, Shl, ShlAssign, Sub,
+        SubAssign,
     },
 };
 
@@ -23,9 +24,41 @@ use std::{
 pub struct U256([u64; 4]);
 
 impl U256 {
+    pub const fn from_parts(parts: [u64; 4]) -> Self {
+        Self(parts)
+    }
+
     pub fn count_ones(&self) -> u32 {
         self.0.iter().cloned().map(u64::count_ones).sum()
     }
+
+    pub fn trailing_zeros(&self) -> u32 {
+        let mut zeros = 0;
+        for p in self.0.iter() {
+            zeros += p.trailing_zeros();
+            if *p != 0 {
+                break;
+            }
+        }
+        zeros
+    }
+
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, p) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+            chunk.copy_from_slice(&p.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_le_bytes(bytes: [u8; 32]) -> Self {
+        let mut parts = [0; 4];
+        for (p, chunk) in parts.iter_mut().zip(bytes.chunks_exact(8)) {
+            // Safety: chunks_exact only yields slices of 8 bytes
+            *p = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self(parts)
+    }
 }
 
 impl BitAnd for U256 {
@@ -48,6 +81,15 @@ impl BitOrAssign for U256 {
     }
 }
 
+impl BitXorAssign for U256 {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0
+            .iter_mut()
+            .zip(rhs.0.iter())
+            .for_each(|(l, r)| *l ^= r);
+    }
+}
+
 impl PartialEq<u64> for U256 {
     fn eq(&self, other: &u64) -> bool {
         self.0[0] == *other && self.0.iter().skip(1).all(|p| *p == 0)
@@ -138,6 +180,15 @@ mod test {
         assert_eq!(left & right, and);
     }
 
+    #[test]
+    fn test_xor() {
+        let mut left = U256([0b0101010, 0b11100111, 0b111111, 0b00000]);
+        let right = U256([0b0011100, 0b10100101, 0b100001, 0b01110]);
+        left ^= right;
+
+        assert_eq!(left, U256([0b0110110, 0b01000010, 0b011110, 0b01110]));
+    }
+
     #[test]
     fn test_add() {
         let left = U256([
@@ -182,6 +233,31 @@ mod test {
         assert_eq!(left << 1, shl);
     }
 
+    #[test]
+    fn test_trailing_zeros() {
+        assert_eq!(U256([0b100, 0, 0, 0]).trailing_zeros(), 2);
+        assert_eq!(U256([0, 0b1000, 0, 1]).trailing_zeros(), 67);
+        assert_eq!(U256([0, 0, 0, 1 << 63]).trailing_zeros(), 255);
+        assert_eq!(U256::default().trailing_zeros(), 256);
+    }
+
+    #[test]
+    fn test_le_bytes() {
+        let value = U256([
+            0x01234567_89ABCDEF,
+            0xFEDCBA98_76543210,
+            0x00000000_000000FF,
+            0x80000000_00000000,
+        ]);
+        let bytes = value.to_le_bytes();
+
+        assert_eq!(bytes[0], 0xEF);
+        assert_eq!(bytes[8], 0x10);
+        assert_eq!(bytes[16], 0xFF);
+        assert_eq!(bytes[31], 0x80);
+        assert_eq!(U256::from_le_bytes(bytes), value);
+    }
+
     #[test]
     fn test_ord() {
         assert_eq!(Some(Ordering::Equal), U256([1, 0, 0, 0]).partial_cmp(&1));
@@ -0,0 +1,842 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use crate::{
+    serialize::{
+        Layout, Record, TAG_LEAF, TAG_RUN, TAG_SEARCH, TAG_SEARCH_OR_LEAF,
+    },
+    u256::U256,
+    walk::{self, walk, Target, TrieNodes, WalkNode},
+    DecodeError, DecodeValue, MasksByByteSized, Normalization, SearchNode,
+    TrieHardSized, TrieNodeIter, TrieState,
+};
+
+/// A read-only trie that answers lookups directly from the bytes written by
+/// [`TrieHard::to_bytes`](crate::TrieHard::to_bytes), without building the
+/// node array in memory. Only the 256 entry mask table is decoded up front;
+/// nodes, keys and values are read from the buffer as they are visited. This
+/// makes it possible to share one large trie between processes by memory
+/// mapping a file.
+///
+/// Creating a view checks every node record in _O(n)_, so that lookups never
+/// panic or loop on corrupt data, but it does not look up each key or decode
+/// each value. [`TrieHardView::validate`] checks those as well. Values are
+/// decoded each time they are returned, and a value that does not decode is
+/// treated as missing.
+///
+/// ```
+/// # use trie_hard::{TrieHard, TrieHardView};
+/// let trie = ["and", "ant", "dad", "do", "dot"]
+///     .into_iter()
+///     .collect::<TrieHard<'_, _>>();
+/// let bytes = trie.to_bytes();
+///
+/// let view = TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap();
+///
+/// assert_eq!(view.get("dad"), Some("dad"));
+/// assert!(view.get("don't").is_none());
+/// ```
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum TrieHardView<'buf, T> {
+    /// View of a trie using u8s for storage
+    U8(TrieHardViewSized<'buf, T, u8>),
+    /// View of a trie using u16s for storage
+    U16(TrieHardViewSized<'buf, T, u16>),
+    /// View of a trie using u32s for storage
+    U32(TrieHardViewSized<'buf, T, u32>),
+    /// View of a trie using u64s for storage
+    U64(TrieHardViewSized<'buf, T, u64>),
+    /// View of a trie using u128s for storage
+    U128(TrieHardViewSized<'buf, T, u128>),
+    /// View of a trie using U256s for storage
+    U256(TrieHardViewSized<'buf, T, U256>),
+}
+
+/// Read-only view of an encoded trie that is generic to a specific size of
+/// integer.
+#[derive(Debug, Clone)]
+pub struct TrieHardViewSized<'buf, T, I> {
+    masks: MasksByByteSized<I>,
+    layout: Layout<'buf>,
+    /// Number of records that are not reachable from the root
+    dead: usize,
+    values: PhantomData<fn() -> T>,
+}
+
+impl<'buf, T> TrieHardView<'buf, T>
+where
+    T: DecodeValue<'buf>,
+{
+    /// Create a view over a buffer written by
+    /// [`TrieHard::to_bytes`](crate::TrieHard::to_bytes). Nothing is copied
+    /// out of the buffer, and only the node records are checked, which takes
+    /// _O(n)_ in the number of nodes regardless of the length of the keys.
+    ///
+    /// ```
+    /// # use trie_hard::{DecodeError, TrieHard, TrieHardView};
+    /// let trie = TrieHard::new(vec![(b"and", 0_u32), (b"ant", 1)]);
+    /// let bytes = trie.to_bytes();
+    ///
+    /// let view = TrieHardView::<'_, u32>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.get("ant"), Some(1));
+    ///
+    /// assert_eq!(
+    ///     TrieHardView::<'_, u32>::from_bytes(&bytes[1..]).unwrap_err(),
+    ///     DecodeError::BadMagic
+    /// );
+    /// ```
+    pub fn from_bytes(bytes: &'buf [u8]) -> Result<Self, DecodeError> {
+        let layout = Layout::parse(bytes)?;
+
+        Ok(match layout.width {
+            1 => {
+                TrieHardView::U8(TrieHardViewSized::<'buf, T, u8>::new(layout)?)
+            }
+            2 => TrieHardView::U16(TrieHardViewSized::<'buf, T, u16>::new(
+                layout,
+            )?),
+            4 => TrieHardView::U32(TrieHardViewSized::<'buf, T, u32>::new(
+                layout,
+            )?),
+            8 => TrieHardView::U64(TrieHardViewSized::<'buf, T, u64>::new(
+                layout,
+            )?),
+            16 => TrieHardView::U128(TrieHardViewSized::<'buf, T, u128>::new(
+                layout,
+            )?),
+            32 => TrieHardView::U256(TrieHardViewSized::<'buf, T, U256>::new(
+                layout,
+            )?),
+            width => return Err(DecodeError::InvalidWidth(width as u8)),
+        })
+    }
+
+    /// Check that every entry in the view is found by looking up its own key
+    /// and that every value decodes, which is the validation done by
+    /// [`TrieHard::from_bytes`](crate::TrieHard::from_bytes). This takes
+    /// _O(total key length)_ plus the cost of decoding the values.
+    ///
+    /// ```
+    /// # use trie_hard::{DecodeError, TrieHard, TrieHardView};
+    /// let trie = TrieHard::new(vec![(b"and", 0_u16), (b"ant", 1)]);
+    /// let bytes = trie.to_bytes();
+    ///
+    /// let view = TrieHardView::<'_, u16>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(view.validate(), Ok(()));
+    ///
+    /// let view = TrieHardView::<'_, u32>::from_bytes(&bytes).unwrap();
+    /// assert!(matches!(view.validate(), Err(DecodeError::InvalidValue(_))));
+    /// assert_eq!(view.get("and"), None);
+    /// ```
+    pub fn validate(&self) -> Result<(), DecodeError> {
+        match self {
+            TrieHardView::U8(view) => view.validate(),
+            TrieHardView::U16(view) => view.validate(),
+            TrieHardView::U32(view) => view.validate(),
+            TrieHardView::U64(view) => view.validate(),
+            TrieHardView::U128(view) => view.validate(),
+            TrieHardView::U256(view) => view.validate(),
+        }
+    }
+
+    /// Get the value stored for the given key. Any key type can be used here
+    /// as long as the type implements `AsRef<[u8]>`. The byte slice
+    /// referenced will serve as the actual key.
+    ///
+    /// ```
+    /// # use trie_hard::{TrieHard, TrieHardView};
+    /// let trie = TrieHard::new(vec![(b"and", 0_u8), (b"ant", 1), (b"do", 2)]);
+    /// let bytes = trie.to_bytes();
+    /// let view = TrieHardView::<'_, u8>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(view.get("do".to_owned()), Some(2));
+    /// assert_eq!(view.get(b"an"), None);
+    /// ```
+    pub fn get<K: AsRef<[u8]>>(&self, raw_key: K) -> Option<T> {
+        self.get_from_bytes(raw_key.as_ref())
+    }
+
+    /// Get the value stored for the given byte-slice key.
+    ///
+    /// ```
+    /// # use trie_hard::{TrieHard, TrieHardView};
+    /// let trie = TrieHard::new(vec![(b"and", 0_u8), (b"ant", 1), (b"do", 2)]);
+    /// let bytes = trie.to_bytes();
+    /// let view = TrieHardView::<'_, u8>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(view.get_from_bytes(b"ant"), Some(1));
+    /// assert_eq!(view.get_from_bytes(b"dot"), None);
+    /// ```
+    pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+        match self {
+            TrieHardView::U8(view) => view.get_from_bytes(key),
+            TrieHardView::U16(view) => view.get_from_bytes(key),
+            TrieHardView::U32(view) => view.get_from_bytes(key),
+            TrieHardView::U64(view) => view.get_from_bytes(key),
+            TrieHardView::U128(view) => view.get_from_bytes(key),
+            TrieHardView::U256(view) => view.get_from_bytes(key),
+        }
+    }
+
+    /// Create an iterator over the entire trie. Emitted items will be
+    /// ordered by their keys
+    ///
+    /// ```
+    /// # use trie_hard::{TrieHard, TrieHardView};
+    /// let trie = ["dad", "ant", "and", "dot", "do"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    /// let bytes = trie.to_bytes();
+    /// let view = TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(
+    ///     view.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["and", "ant", "dad", "do", "dot"]
+    /// );
+    /// ```
+    pub fn iter(&self) -> TrieViewIter<'_, 'buf, T> {
+        match self {
+            TrieHardView::U8(view) => TrieViewIter::U8(view.iter()),
+            TrieHardView::U16(view) => TrieViewIter::U16(view.iter()),
+            TrieHardView::U32(view) => TrieViewIter::U32(view.iter()),
+            TrieHardView::U64(view) => TrieViewIter::U64(view.iter()),
+            TrieHardView::U128(view) => TrieViewIter::U128(view.iter()),
+            TrieHardView::U256(view) => TrieViewIter::U256(view.iter()),
+        }
+    }
+
+    /// Create an iterator over the portion of the trie starting with the given
+    /// prefix
+    ///
+    /// ```
+    /// # use trie_hard::{TrieHard, TrieHardView};
+    /// let trie = ["dad", "ant", "and", "dot", "do"]
+    ///     .into_iter()
+    ///     .collect::<TrieHard<'_, _>>();
+    /// let bytes = trie.to_bytes();
+    /// let view = TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(
+    ///     view.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+    ///     ["dad", "do", "dot"]
+    /// );
+    /// ```
+    pub fn prefix_search<K: AsRef<[u8]>>(
+        &self,
+        prefix: K,
+    ) -> TrieViewIter<'_, 'buf, T> {
+        match self {
+            TrieHardView::U8(view) => {
+                TrieViewIter::U8(view.prefix_search(prefix))
+            }
+            TrieHardView::U16(view) => {
+                TrieViewIter::U16(view.prefix_search(prefix))
+            }
+            TrieHardView::U32(view) => {
+                TrieViewIter::U32(view.prefix_search(prefix))
+            }
+            TrieHardView::U64(view) => {
+                TrieViewIter::U64(view.prefix_search(prefix))
+            }
+            TrieHardView::U128(view) => {
+                TrieViewIter::U128(view.prefix_search(prefix))
+            }
+            TrieHardView::U256(view) => {
+                TrieViewIter::U256(view.prefix_search(prefix))
+            }
+        }
+    }
+}
+
+/// Structure used for iterating over the contents of a trie view
+#[derive(Debug)]
+pub enum TrieViewIter<'b, 'buf, T> {
+    /// Variant for iterating over views of tries built on u8
+    U8(TrieViewIterSized<'b, 'buf, T, u8>),
+    /// Variant for iterating over views of tries built on u16
+    U16(TrieViewIterSized<'b, 'buf, T, u16>),
+    /// Variant for iterating over views of tries built on u32
+    U32(TrieViewIterSized<'b, 'buf, T, u32>),
+    /// Variant for iterating over views of tries built on u64
+    U64(TrieViewIterSized<'b, 'buf, T, u64>),
+    /// Variant for iterating over views of tries built on u128
+    U128(TrieViewIterSized<'b, 'buf, T, u128>),
+    /// Variant for iterating over views of tries built on u256
+    U256(TrieViewIterSized<'b, 'buf, T, U256>),
+}
+
+/// Structure for iterating over a trie view built on a specific integer size
+#[derive(Debug)]
+pub struct TrieViewIterSized<'b, 'buf, T, I> {
+    stack: Vec<TrieNodeIter>,
+    view: &'b TrieHardViewSized<'buf, T, I>,
+}
+
+impl<'b, 'buf, T, I> TrieViewIterSized<'b, 'buf, T, I> {
+    fn empty(view: &'b TrieHardViewSized<'buf, T, I>) -> Self {
+        Self {
+            stack: Default::default(),
+            view,
+        }
+    }
+
+    fn new(view: &'b TrieHardViewSized<'buf, T, I>, node_index: usize) -> Self {
+        Self {
+            stack: vec![TrieNodeIter {
+                node_index,
+                stage: Default::default(),
+            }],
+            view,
+        }
+    }
+}
+
+impl<'b, 'buf, T> Iterator for TrieViewIter<'b, 'buf, T>
+where
+    T: DecodeValue<'buf>,
+{
+    type Item = (&'buf [u8], T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            TrieViewIter::U8(iter) => iter.next(),
+            TrieViewIter::U16(iter) => iter.next(),
+            TrieViewIter::U32(iter) => iter.next(),
+            TrieViewIter::U64(iter) => iter.next(),
+            TrieViewIter::U128(iter) => iter.next(),
+            TrieViewIter::U256(iter) => iter.next(),
+        }
+    }
+}
+
+macro_rules! view_impls {
+    ($($int_type:ty),+) => {
+        $(
+            view_impls!(_impl $int_type);
+        )+
+    };
+
+    (_impl $int_type:ty) => {
+
+        impl MasksByByteSized<$int_type> {
+            fn from_layout(layout: &Layout<'_>) -> Self {
+                let mut by_byte = [<$int_type>::default(); 256];
+                for (mask, bit) in by_byte.iter_mut().zip(layout.bit_indices) {
+                    if let Some(bit) = bit {
+                        let mut bytes = [0; std::mem::size_of::<$int_type>()];
+                        bytes[bit as usize / 8] = 1 << (bit % 8);
+                        *mask = <$int_type>::from_le_bytes(bytes);
+                    }
+                }
+                Self::from_byte_masks(by_byte)
+            }
+        }
+
+        impl SearchNode<$int_type> {
+            fn from_record(record: &Record<'_>) -> Self {
+                // Safety: Records are read with the width of this type
+                let mask = <$int_type>::from_le_bytes(
+                    record.mask().try_into().unwrap()
+                );
+                SearchNode { mask, edge_start: record.edge_start() }
+            }
+        }
+
+        impl<'buf, T> TrieNodes for TrieHardViewSized<'buf, T, $int_type>
+        where
+            T: DecodeValue<'buf>
+        {
+            fn normalization(&self) -> &Normalization {
+                &self.layout.normalization
+            }
+
+            fn walk_node(&self, index: usize) -> Option<WalkNode<'_>> {
+                (index < self.layout.node_count)
+                    .then(|| self.node(index).walk_node())
+            }
+
+            fn child(&self, index: usize, c: u8) -> Option<usize> {
+                SearchNode::<$int_type>::from_record(&self.layout.record(index))
+                    .evaluate(c, &self.masks)
+            }
+        }
+
+        impl<'buf, T> TrieHardViewSized<'buf, T, $int_type>
+        where
+            T: DecodeValue<'buf>
+        {
+            fn new(layout: Layout<'buf>) -> Result<Self, DecodeError> {
+                let mut view = Self {
+                    masks: MasksByByteSized::<$int_type>::from_layout(&layout),
+                    layout,
+                    dead: 0,
+                    values: PhantomData,
+                };
+                view.check_records()?;
+                view.dead = view.check_structure()?;
+                Ok(view)
+            }
+
+            /// Check every record on its own: the tag is known, children are
+            /// in bounds, runs have at least two bytes and the key and value
+            /// of entries lie within their sections
+            fn check_records(&self) -> Result<(), DecodeError> {
+                let node_count = self.layout.node_count;
+
+                for index in 0..node_count {
+                    let record = self.layout.record(index);
+                    let invalid = DecodeError::InvalidNode(index);
+
+                    match record.tag() {
+                        TAG_LEAF => (),
+                        TAG_SEARCH | TAG_SEARCH_OR_LEAF => {
+                            let search = SearchNode::<$int_type>::from_record(&record);
+                            let child_count = search.mask.count_ones() as usize;
+                            match search.edge_start.checked_add(child_count) {
+                                Some(end) if child_count > 0 && end <= node_count => (),
+                                _ => return Err(invalid),
+                            }
+                        }
+                        TAG_RUN => {
+                            let run = self.layout.key(&record).ok_or(invalid.clone())?;
+                            if record.edge_start() >= node_count || run.len() < 2 {
+                                return Err(invalid);
+                            }
+                            continue;
+                        }
+                        _ => return Err(invalid),
+                    }
+
+                    if record.tag() != TAG_SEARCH {
+                        self.layout.key(&record).ok_or(invalid)?;
+                        self.layout
+                            .value(&record)
+                            .ok_or(DecodeError::InvalidValue(index))?;
+                    }
+                }
+
+                Ok(())
+            }
+
+            /// Check that the nodes reachable from the root form a tree, so
+            /// that walking them always ends. Returns the number of nodes
+            /// that are not reachable.
+            fn check_structure(&self) -> Result<usize, DecodeError> {
+                let node_count = self.layout.node_count;
+                if node_count == 0 {
+                    return Ok(0);
+                }
+
+                let mut seen = vec![false; node_count];
+                let mut stack = vec![0];
+                let mut reachable = 0;
+                seen[0] = true;
+
+                while let Some(node_index) = stack.pop() {
+                    reachable += 1;
+                    let record = self.layout.record(node_index);
+
+                    let children = match record.tag() {
+                        TAG_LEAF => 0..0,
+                        TAG_RUN => record.edge_start()..record.edge_start() + 1,
+                        _ => {
+                            let search = SearchNode::<$int_type>::from_record(&record);
+                            let child_count = search.mask.count_ones() as usize;
+                            search.edge_start..search.edge_start + child_count
+                        }
+                    };
+                    for child in children {
+                        if std::mem::replace(&mut seen[child], true) {
+                            return Err(DecodeError::InvalidNode(child));
+                        }
+                        stack.push(child);
+                    }
+                }
+
+                Ok(node_count - reachable)
+            }
+
+            /// Check that every entry reachable from the root is found by
+            /// looking up its own key
+            fn check_entries(&self) -> Result<(), DecodeError> {
+                let mut stack = Vec::new();
+                if self.layout.node_count > 0 {
+                    stack.push(0);
+                }
+
+                while let Some(node_index) = stack.pop() {
+                    let node = self.node(node_index);
+                    if let TrieState::Leaf(key, _)
+                    | TrieState::SearchOrLeaf(key, _, _) = &node
+                    {
+                        if self.find_index(key) != Some(node_index) {
+                            return Err(DecodeError::InvalidNode(node_index));
+                        }
+                    }
+                    stack.extend(node.children());
+                }
+
+                Ok(())
+            }
+
+            /// Check that every entry is found by looking up its own key and
+            /// that every value decodes. See [`TrieHardView::validate`].
+            ///
+            /// ```
+            /// # use trie_hard::{TrieHard, TrieHardView};
+            /// let trie = ["and", "ant"].into_iter().collect::<TrieHard<'_, _>>();
+            /// let bytes = trie.to_bytes();
+            ///
+            /// let TrieHardView::U8(sized_view) =
+            ///     TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap()
+            /// else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(sized_view.validate(), Ok(()));
+            /// ```
+            pub fn validate(&self) -> Result<(), DecodeError> {
+                self.check_entries()?;
+                for index in 0..self.layout.node_count {
+                    if let Some(value) = self.node(index).value() {
+                        T::decode(*value).ok_or(DecodeError::InvalidValue(index))?;
+                    }
+                }
+                Ok(())
+            }
+
+            /// Build an in-memory trie from the view, borrowing keys from the
+            /// buffer. Updates to the trie rely on every entry being where its
+            /// key leads, so this checks the entries and decodes every value.
+            pub(crate) fn into_trie(self) -> Result<TrieHardSized<'buf, T, $int_type>, DecodeError> {
+                self.check_entries()?;
+                let nodes = (0..self.layout.node_count)
+                    .map(|index| {
+                        Ok(match self.node(index) {
+                            TrieState::Leaf(key, value) => TrieState::Leaf(
+                                key,
+                                T::decode(value).ok_or(DecodeError::InvalidValue(index))?,
+                            ),
+                            TrieState::Search(search) => TrieState::Search(search),
+                            TrieState::Run(run, next) => TrieState::Run(run, next),
+                            TrieState::SearchOrLeaf(key, value, search) => {
+                                TrieState::SearchOrLeaf(
+                                    key,
+                                    T::decode(value).ok_or(DecodeError::InvalidValue(index))?,
+                                    search,
+                                )
+                            }
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(TrieHardSized {
+                    masks: self.masks,
+                    below: TrieHardSized::<_, $int_type>::count_below(&nodes).into(),
+                    nodes: nodes.into(),
+                    dead: self.dead,
+                    normalization: self.layout.normalization,
+                })
+            }
+
+            /// Read a whole node from the buffer, leaving its value encoded
+            fn node(&self, index: usize) -> TrieState<'buf, &'buf [u8], $int_type> {
+                let record = self.layout.record(index);
+                let entry = || {
+                    // Safety: Keys and values were checked on construction
+                    (
+                        self.layout.key(&record).unwrap(),
+                        self.layout.value(&record).unwrap(),
+                    )
+                };
+
+                match record.tag() {
+                    TAG_LEAF => {
+                        let (key, value) = entry();
+                        TrieState::Leaf(key, value)
+                    }
+                    TAG_SEARCH_OR_LEAF => {
+                        let (key, value) = entry();
+                        TrieState::SearchOrLeaf(
+                            key,
+                            value,
+                            SearchNode::<$int_type>::from_record(&record),
+                        )
+                    }
+                    TAG_RUN => {
+                        // Safety: Runs were checked on construction
+                        TrieState::Run(
+                            self.layout.key(&record).unwrap(),
+                            record.edge_start(),
+                        )
+                    }
+                    _ => TrieState::Search(SearchNode::<$int_type>::from_record(&record)),
+                }
+            }
+
+            /// Find the index of the node holding the entry for the given key
+            fn find_index(&self, key: &[u8]) -> Option<usize> {
+                walk(self, key, Target::Entry, |_, _| ())
+            }
+
+            /// Get the value stored for the given key. Any key type can be used
+            /// here as long as the type implements `AsRef<[u8]>`. The byte slice
+            /// referenced will serve as the actual key.
+            /// ```
+            /// # use trie_hard::{TrieHard, TrieHardView};
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            /// let bytes = trie.to_bytes();
+            ///
+            /// let TrieHardView::U8(sized_view) =
+            ///     TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap()
+            /// else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_view.get("dad".to_owned()).is_some());
+            /// assert!(sized_view.get(b"do").is_some());
+            /// assert!(sized_view.get(b"don't".to_vec()).is_none());
+            /// ```
+            pub fn get<K: AsRef<[u8]>>(&self, key: K) -> Option<T> {
+                self.get_from_bytes(key.as_ref())
+            }
+
+            /// Get the value stored for the given byte-slice key.
+            /// ```
+            /// # use trie_hard::{TrieHard, TrieHardView};
+            /// let trie = ["and", "ant", "dad", "do", "dot"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            /// let bytes = trie.to_bytes();
+            ///
+            /// let TrieHardView::U8(sized_view) =
+            ///     TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap()
+            /// else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert!(sized_view.get_from_bytes(b"dad").is_some());
+            /// assert!(sized_view.get_from_bytes(b"don't").is_none());
+            /// ```
+            pub fn get_from_bytes(&self, key: &[u8]) -> Option<T> {
+                let index = self.find_index(key)?;
+                let record = self.layout.record(index);
+                self.layout.value(&record).and_then(T::decode)
+            }
+
+            /// Create an iterator over the entire trie. Emitted items will be
+            /// ordered by their keys
+            ///
+            /// ```
+            /// # use trie_hard::{TrieHard, TrieHardView};
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            /// let bytes = trie.to_bytes();
+            ///
+            /// let TrieHardView::U8(sized_view) =
+            ///     TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap()
+            /// else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_view.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["and", "ant", "dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn iter(&self) -> TrieViewIterSized<'_, 'buf, T, $int_type> {
+                if self.layout.node_count == 0 {
+                    return TrieViewIterSized::empty(self);
+                }
+                TrieViewIterSized::new(self, 0)
+            }
+
+            /// Create an iterator over the portion of the trie starting with the given
+            /// prefix
+            ///
+            /// ```
+            /// # use trie_hard::{TrieHard, TrieHardView};
+            /// let trie = ["dad", "ant", "and", "dot", "do"]
+            ///     .into_iter()
+            ///     .collect::<TrieHard<'_, _>>();
+            /// let bytes = trie.to_bytes();
+            ///
+            /// let TrieHardView::U8(sized_view) =
+            ///     TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap()
+            /// else {
+            ///     unreachable!()
+            /// };
+            ///
+            /// assert_eq!(
+            ///     sized_view.prefix_search("d").map(|(_, v)| v).collect::<Vec<_>>(),
+            ///     ["dad", "do", "dot"]
+            /// );
+            /// ```
+            pub fn prefix_search<K: AsRef<[u8]>>(&self, prefix: K) -> TrieViewIterSized<'_, 'buf, T, $int_type> {
+                match walk(self, prefix.as_ref(), Target::Prefix, |_, _| ()) {
+                    Some(node_index) => TrieViewIterSized::new(self, node_index),
+                    None => TrieViewIterSized::empty(self),
+                }
+            }
+        }
+
+        impl <'b, 'buf, T> Iterator for TrieViewIterSized<'b, 'buf, T, $int_type>
+        where
+            T: DecodeValue<'buf>
+        {
+            type Item = (&'buf [u8], T);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                while let Some(node_index) = walk::next_entry(self.view, &mut self.stack) {
+                    if let TrieState::Leaf(key, value)
+                    | TrieState::SearchOrLeaf(key, value, _) = self.view.node(node_index)
+                    {
+                        if let Some(value) = T::decode(value) {
+                            return Some((key, value));
+                        }
+                    }
+                }
+
+                None
+            }
+        }
+    };
+}
+
+view_impls! {u8, u16, u32, u64, u128, U256}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rstest::rstest;
+
+    use crate::{TrieHard, TrieHardView};
+
+    #[rstest]
+    #[case(include_str!("../data/1984.txt"))]
+    #[case(include_str!("../data/sun-rising.txt"))]
+    #[case(include_str!("../data/headers.txt"))]
+    fn test_matches_trie(#[case] text: &str) {
+        let words = text
+            .split(|c: char| c.is_whitespace())
+            .collect::<BTreeSet<_>>();
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let bytes = trie.to_bytes();
+        let view = TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap();
+
+        for word in &words {
+            assert_eq!(view.get(word), Some(*word));
+            assert_eq!(
+                view.get(format!("{word}~")),
+                trie.get(format!("{word}~"))
+            );
+        }
+        assert!(view.iter().eq(trie.iter()));
+    }
+
+    #[rstest]
+    #[case(&[], "", &[])]
+    #[case(&[""], "", &[""])]
+    #[case(&["aaa", "aab", "aac", "ab"], "aa", &["aaa", "aab", "aac"])]
+    #[case(&["aaa", "aab", "aac", "ab"], "ab", &["ab"])]
+    #[case(&["abc", "x"], "ab", &["abc"])]
+    #[case(&["and", "ant", "dad", "do", "dot"], "z", &[])]
+    #[case(&["abcdx", "abcdy"], "abc", &["abcdx", "abcdy"])]
+    #[case(&["abcdx", "abcdy"], "abcdy", &["abcdy"])]
+    #[case(&["abcdx", "abcdy"], "abd", &[])]
+    fn test_prefix_search(
+        #[case] input: &[&str],
+        #[case] prefix: &str,
+        #[case] output: &[&str],
+    ) {
+        let trie = input.iter().copied().collect::<TrieHard<'_, _>>();
+        let bytes = trie.to_bytes();
+        let view = TrieHardView::<'_, &str>::from_bytes(&bytes).unwrap();
+
+        let emitted = view
+            .prefix_search(prefix)
+            .map(|(_, v)| v)
+            .collect::<Vec<_>>();
+        assert_eq!(emitted, output);
+    }
+
+    #[rstest]
+    #[case(8)]
+    #[case(16)]
+    #[case(32)]
+    #[case(64)]
+    #[case(128)]
+    #[case(256)]
+    fn test_sizes(#[case] bits: usize) {
+        let keys = (0..bits).map(|b| [b as u8, 0x7F]).collect::<Vec<_>>();
+        let trie = TrieHard::new(
+            keys.iter().enumerate().map(|(i, k)| (&k[..], i)).collect(),
+        );
+        let bytes = trie.to_bytes();
+        let view = TrieHardView::<'_, usize>::from_bytes(&bytes).unwrap();
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(view.get(key), Some(i));
+        }
+        assert!(view.iter().eq(trie.iter()));
+    }
+
+    #[test]
+    fn test_undecodable_values_are_missing() {
+        let trie = TrieHard::new(vec![(&b"and"[..], 0_u16), (b"ant", 1)]);
+        let bytes = trie.to_bytes();
+        let view = TrieHardView::<'_, u32>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(view.get("and"), None);
+        assert_eq!(view.iter().count(), 0);
+        assert!(view.validate().is_err());
+    }
+
+    #[test]
+    fn test_corruption_never_panics() {
+        let text = include_str!("../data/sun-rising.txt");
+        let words = text.split_whitespace().collect::<BTreeSet<_>>();
+        let trie = words.iter().copied().collect::<TrieHard<'_, _>>();
+        let bytes = trie.to_bytes();
+
+        for at in 0..bytes.len() {
+            for flip in [0x01, 0x80, 0xFF] {
+                let mut corrupt = bytes.clone();
+                corrupt[at] ^= flip;
+
+                // Anything that loads must be safe to query, and only pass
+                // validation if it also loads as a trie
+                if let Ok(view) = TrieHardView::<'_, &str>::from_bytes(&corrupt)
+                {
+                    for word in &words {
+                        view.get(word);
+                        view.prefix_search(word).count();
+                    }
+                    view.iter().count();
+                    assert_eq!(
+                        view.validate(),
+                        TrieHard::<'_, &str>::from_bytes(&corrupt).map(|_| ())
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Walks over the nodes of a trie. Borrowed, owned, encoded and minimized
+//! tries store their keys and values differently, but they all lay out their
+//! nodes the same way, so each of them describes its nodes through
+//! [`TrieNodes`] and shares the lookup and iteration code in here.
+
+use std::ops::Range;
+
+use crate::{Normalization, TrieNodeIter, TrieNodeIterStage};
+
+/// A node reduced to what walking the trie needs
+#[derive(Debug, Clone)]
+pub(crate) enum WalkNode<'n> {
+    /// End of a key with nothing below it, along with the whole key if the
+    /// node stores it
+    Leaf(Option<&'n [u8]>),
+    /// Node that picks a child by the next byte of the key. `entry` is set
+    /// when a key also ends here.
+    Search { entry: bool, children: Range<usize> },
+    /// Bytes shared by every key below the node, and the node after them
+    Run(&'n [u8], usize),
+}
+
+/// Node storage that the shared walks can follow
+pub(crate) trait TrieNodes {
+    /// Normalization applied to keys before they are compared
+    fn normalization(&self) -> &Normalization;
+
+    /// The node at `index`, or `None` if there is no such node
+    fn walk_node(&self, index: usize) -> Option<WalkNode<'_>>;
+
+    /// Index of the child that the byte `c` leads to from the search node at
+    /// `index`
+    fn child(&self, index: usize, c: u8) -> Option<usize>;
+}
+
+/// What a walk down the trie is looking for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Target {
+    /// The node holding the entry for the key
+    Entry,
+    /// The highest node below which every key starts with the key
+    Prefix,
+}
+
+/// Follow `key` down from the root, returning the node that `target` asks
+/// for. `enter` is called with the parent and child of every edge followed.
+pub(crate) fn walk<N>(
+    nodes: &N,
+    key: &[u8],
+    target: Target,
+    mut enter: impl FnMut(usize, usize),
+) -> Option<usize>
+where
+    N: TrieNodes + ?Sized,
+{
+    let normalization = nodes.normalization();
+    let mut node_index = 0;
+    let mut node = nodes.walk_node(node_index)?;
+
+    let mut depth = 0;
+    while let Some(c) = key.get(depth) {
+        let next_index = match node {
+            WalkNode::Leaf(stored) => {
+                let found = stored.is_some_and(|k| match target {
+                    Target::Entry => {
+                        k.len() == key.len()
+                            && normalization.eq(&k[depth..], &key[depth..])
+                    }
+                    Target::Prefix => normalization.starts_with(k, key),
+                });
+                return found.then_some(node_index);
+            }
+            WalkNode::Search { .. } => {
+                depth += 1;
+                nodes.child(node_index, *c)?
+            }
+            WalkNode::Run(run, next) => {
+                // Every key below the run starts with the prefix if the
+                // prefix ends inside of it
+                if target == Target::Prefix
+                    && normalization.starts_with(run, &key[depth..])
+                {
+                    return Some(node_index);
+                }
+                if !normalization.starts_with(&key[depth..], run) {
+                    return None;
+                }
+                depth += run.len();
+                next
+            }
+        };
+
+        enter(node_index, next_index);
+        node_index = next_index;
+        node = nodes.walk_node(node_index)?;
+    }
+
+    match (target, node) {
+        (Target::Prefix, _) => Some(node_index),
+        (Target::Entry, WalkNode::Leaf(stored)) => match stored {
+            Some(k) if k.len() != key.len() => None,
+            _ => Some(node_index),
+        },
+        (Target::Entry, WalkNode::Search { entry, .. }) => {
+            entry.then_some(node_index)
+        }
+        (Target::Entry, WalkNode::Run(..)) => None,
+    }
+}
+
+/// Index of the next node holding an entry, visiting the nodes on `stack`
+/// depth first with children in order so that entries come out ordered by
+/// key. The stage of a search node on the stack is the child being visited.
+pub(crate) fn next_entry<N>(
+    nodes: &N,
+    stack: &mut Vec<TrieNodeIter>,
+) -> Option<usize>
+where
+    N: TrieNodes + ?Sized,
+{
+    use TrieNodeIterStage as S;
+    use WalkNode as W;
+
+    while let Some(TrieNodeIter { node_index, stage }) = stack.pop() {
+        let Some(node) = nodes.walk_node(node_index) else {
+            continue;
+        };
+        match (node, stage) {
+            (W::Leaf(_), S::Inner) => return Some(node_index),
+            (W::Run(_, next), S::Inner) => stack.push(TrieNodeIter {
+                node_index: next,
+                stage: S::Inner,
+            }),
+            (W::Search { entry, children }, S::Inner) => {
+                if !children.is_empty() {
+                    stack.push(TrieNodeIter {
+                        node_index,
+                        stage: S::Child(0, children.len()),
+                    });
+                    stack.push(TrieNodeIter {
+                        node_index: children.start,
+                        stage: S::Inner,
+                    });
+                }
+                if entry {
+                    return Some(node_index);
+                }
+            }
+            (W::Search { children, .. }, S::Child(child, child_count)) => {
+                let child = child + 1;
+                if child < child_count {
+                    stack.push(TrieNodeIter {
+                        node_index,
+                        stage: S::Child(child, child_count),
+                    });
+                    stack.push(TrieNodeIter {
+                        node_index: children.start + child,
+                        stage: S::Inner,
+                    });
+                }
+            }
+            (W::Leaf(_) | W::Run(..), S::Child(..)) => unreachable!(),
+        }
+    }
+
+    None
+}
+
+/// Index of the next node holding an entry from the back, visiting the
+/// nodes on `stack` depth first with children from the last one down and
+/// the entry of a node after its children. The stage of a search node on the
+/// stack is the number of children that have not been visited yet.
+pub(crate) fn next_back_entry<N>(
+    nodes: &N,
+    stack: &mut Vec<TrieNodeIter>,
+) -> Option<usize>
+where
+    N: TrieNodes + ?Sized,
+{
+    use TrieNodeIterStage as S;
+    use WalkNode as W;
+
+    while let Some(TrieNodeIter { node_index, stage }) = stack.pop() {
+        let Some(node) = nodes.walk_node(node_index) else {
+            continue;
+        };
+        match (node, stage) {
+            (W::Leaf(_), S::Inner) => return Some(node_index),
+            (W::Run(_, next), S::Inner) => stack.push(TrieNodeIter {
+                node_index: next,
+                stage: S::Inner,
+            }),
+            (W::Search { children, .. }, S::Inner) => {
+                stack.push(TrieNodeIter {
+                    node_index,
+                    stage: S::Child(children.len(), children.len()),
+                });
+            }
+            (W::Search { children, .. }, S::Child(remaining, child_count))
+                if remaining > 0 =>
+            {
+                stack.push(TrieNodeIter {
+                    node_index,
+                    stage: S::Child(remaining - 1, child_count),
+                });
+                stack.push(TrieNodeIter {
+                    node_index: children.start + remaining - 1,
+                    stage: S::Inner,
+                });
+            }
+            (W::Search { entry, .. }, S::Child(..)) => {
+                if entry {
+                    return Some(node_index);
+                }
+            }
+            (W::Leaf(_) | W::Run(..), S::Child(..)) => unreachable!(),
+        }
+    }
+
+    None
+}
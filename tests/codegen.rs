@@ -0,0 +1,64 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::BTreeSet, fs};
+
+use trie_hard::{codegen::StaticTrie, TrieHard};
+
+mod fixture {
+    include!("fixtures/headers.rs");
+}
+
+const FIXTURE: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/headers.rs");
+
+fn headers() -> StaticTrie {
+    let mut trie = StaticTrie::new("HEADERS", "usize");
+    trie.words(include_str!("../data/headers.txt"), |word| {
+        format!("{word:?}.len()")
+    });
+    trie
+}
+
+#[test]
+fn test_fixture_is_current() {
+    // Set `UPDATE_FIXTURES` to regenerate the fixture after changing how
+    // tries are laid out or rendered
+    if std::env::var_os("UPDATE_FIXTURES").is_some() {
+        headers().write_to_file(FIXTURE).unwrap();
+    }
+
+    assert_eq!(
+        fs::read_to_string(FIXTURE).unwrap(),
+        headers().to_source(),
+        "tests/fixtures/headers.rs is out of date, rerun the tests with \
+        UPDATE_FIXTURES=1 to regenerate it"
+    );
+}
+
+#[test]
+fn test_fixture_matches_runtime() {
+    let words = include_str!("../data/headers.txt")
+        .split_whitespace()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|word| (word.as_bytes(), word.len()))
+        .collect::<Vec<_>>();
+    let trie = TrieHard::new(words.clone());
+
+    assert_eq!(format!("{:?}", fixture::HEADERS), format!("{trie:?}"));
+    for (word, len) in words {
+        assert_eq!(fixture::HEADERS.get(word), Some(len));
+    }
+}
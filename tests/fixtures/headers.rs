@@ -0,0 +1,231 @@
+// Generated by trie-hard. Do not edit.
+
+pub static HEADERS: ::trie_hard::TrieHard<'static, usize> =
+::trie_hard::TrieHard::U32(::trie_hard::__private::sized(
+    ::trie_hard::__private::masks(0x0_u32, &[
+        (b'-', 0x1_u32),
+        (b'2', 0x2_u32),
+        (b'[', 0x4_u32),
+        (b']', 0x8_u32),
+        (b'a', 0x10_u32),
+        (b'b', 0x20_u32),
+        (b'c', 0x40_u32),
+        (b'd', 0x80_u32),
+        (b'e', 0x100_u32),
+        (b'f', 0x200_u32),
+        (b'g', 0x400_u32),
+        (b'h', 0x800_u32),
+        (b'i', 0x1000_u32),
+        (b'k', 0x2000_u32),
+        (b'l', 0x4000_u32),
+        (b'm', 0x8000_u32),
+        (b'n', 0x10000_u32),
+        (b'o', 0x20000_u32),
+        (b'p', 0x40000_u32),
+        (b'q', 0x80000_u32),
+        (b'r', 0x100000_u32),
+        (b's', 0x200000_u32),
+        (b't', 0x400000_u32),
+        (b'u', 0x800000_u32),
+        (b'v', 0x1000000_u32),
+        (b'w', 0x2000000_u32),
+        (b'x', 0x4000000_u32),
+        (b'y', 0x8000000_u32),
+        (b'z', 0x10000000_u32),
+    ]),
+    &[
+        ::trie_hard::__private::search(0x7f6ffd0_u32, 1),
+        ::trie_hard::__private::search(0x808040_u32, 21),
+        ::trie_hard::__private::search(0x124191_u32, 24),
+        ::trie_hard::__private::search(0x21100_u32, 31),
+        ::trie_hard::__private::search(0x4000090_u32, 34),
+        ::trie_hard::__private::leaf(b"feature-policy", "feature-policy".len()),
+        ::trie_hard::__private::leaf(b"getprofile", "getprofile".len()),
+        ::trie_hard::__private::leaf(b"host", "host".len()),
+        ::trie_hard::__private::search(0x10200_u32, 37),
+        ::trie_hard::__private::leaf(b"keep-alive", "keep-alive".len()),
+        ::trie_hard::__private::search(0x20010_u32, 39),
+        ::trie_hard::__private::search(0x1100_u32, 41),
+        ::trie_hard::__private::search(0x1340080_u32, 43),
+        ::trie_hard::__private::search(0x921100_u32, 48),
+        ::trie_hard::__private::search(0x110_u32, 53),
+        ::trie_hard::__private::search(0xc20140_u32, 55),
+        ::trie_hard::__private::search(0x101000_u32, 60),
+        ::trie_hard::__private::leaf(b"upgrade-insecure-requests", "upgrade-insecure-requests".len()),
+        ::trie_hard::__private::leaf(b"variant-vary", "variant-vary".len()),
+        ::trie_hard::__private::leaf(b"want-digest", "want-digest".len()),
+        ::trie_hard::__private::search(0x1_u32, 62),
+        ::trie_hard::__private::run(b"ce", 63),
+        ::trie_hard::__private::leaf(b"amp-cache-transform", "amp-cache-transform".len()),
+        ::trie_hard::__private::run(b"th", 64),
+        ::trie_hard::__private::search(0x48000_u32, 65),
+        ::trie_hard::__private::search(0x44040_u32, 67),
+        ::trie_hard::__private::leaf(b"cdn-loop", "cdn-loop".len()),
+        ::trie_hard::__private::leaf(b"cert-not-before", "cert-not-before".len()),
+        ::trie_hard::__private::leaf(b"close", "close".len()),
+        ::trie_hard::__private::search(0x30000_u32, 70),
+        ::trie_hard::__private::run(b"oss-origin-", 72),
+        ::trie_hard::__private::search(0x1104000_u32, 73),
+        ::trie_hard::__private::leaf(b"digest", "digest".len()),
+        ::trie_hard::__private::leaf(b"downlink", "downlink".len()),
+        ::trie_hard::__private::leaf(b"early-data", "early-data".len()),
+        ::trie_hard::__private::leaf(b"ediint-features", "ediint-features".len()),
+        ::trie_hard::__private::search(0x40000_u32, 76),
+        ::trie_hard::__private::search(0x1_u32, 77),
+        ::trie_hard::__private::leaf(b"include-referred-token-binding-id", "include-referred-token-binding-id".len()),
+        ::trie_hard::__private::search(0x300000_u32, 78),
+        ::trie_hard::__private::leaf(b"location", "location".len()),
+        ::trie_hard::__private::search(0x408000_u32, 80),
+        ::trie_hard::__private::leaf(b"mime-version", "mime-version".len()),
+        ::trie_hard::__private::run(b"ata-", 82),
+        ::trie_hard::__private::leaf(b"optional-www-authenticate", "optional-www-authenticate".len()),
+        ::trie_hard::__private::leaf(b"origin", "origin".len()),
+        ::trie_hard::__private::leaf(b"oscore", "oscore".len()),
+        ::trie_hard::__private::leaf(b"overwrite", "overwrite".len()),
+        ::trie_hard::__private::leaf(b"permissions-policy", "permissions-policy".len()),
+        ::trie_hard::__private::leaf(b"ping-from", "ping-from".len()),
+        ::trie_hard::__private::leaf(b"position", "position".len()),
+        ::trie_hard::__private::search(0x21100_u32, 83),
+        ::trie_hard::__private::leaf(b"public-key-pins", "public-key-pins".len()),
+        ::trie_hard::__private::leaf(b"range", "range".len()),
+        ::trie_hard::__private::search(0x440200_u32, 86),
+        ::trie_hard::__private::leaf(b"schedule-reply", "schedule-reply".len()),
+        ::trie_hard::__private::search(0x500040_u32, 89),
+        ::trie_hard::__private::leaf(b"soapaction", "soapaction".len()),
+        ::trie_hard::__private::search(0x100010_u32, 92),
+        ::trie_hard::__private::leaf(b"surrogate-capability", "surrogate-capability".len()),
+        ::trie_hard::__private::leaf(b"timeout", "timeout".len()),
+        ::trie_hard::__private::search(0x10_u32, 94),
+        ::trie_hard::__private::search(0x69402d0_u32, 95),
+        ::trie_hard::__private::search(0x240000_u32, 104),
+        ::trie_hard::__private::search(0x20100_u32, 106),
+        ::trie_hard::__private::leaf(b"c-man", "c-man".len()),
+        ::trie_hard::__private::leaf(b"c-pep", "c-pep".len()),
+        ::trie_hard::__private::leaf(b"cache-control", "cache-control".len()),
+        ::trie_hard::__private::leaf(b"cal-managed-id", "cal-managed-id".len()),
+        ::trie_hard::__private::leaf(b"capsule-protocol", "capsule-protocol".len()),
+        ::trie_hard::__private::search(0x410000_u32, 108),
+        ::trie_hard::__private::leaf(b"cookie2", "cookie2".len()),
+        ::trie_hard::__private::search(0x20100_u32, 110),
+        ::trie_hard::__private::leaf(b"delta-base", "delta-base".len()),
+        ::trie_hard::__private::leaf(b"derived-from", "derived-from".len()),
+        ::trie_hard::__private::leaf(b"device-memory", "device-memory".len()),
+        ::trie_hard::__private::search(0x1100_u32, 112),
+        ::trie_hard::__private::search(0x908000_u32, 114),
+        ::trie_hard::__private::leaf(b"large-allocation", "large-allocation".len()),
+        ::trie_hard::__private::leaf(b"last-modified", "last-modified".len()),
+        ::trie_hard::__private::leaf(b"memento-datetime", "memento-datetime".len()),
+        ::trie_hard::__private::leaf(b"method-check", "method-check".len()),
+        ::trie_hard::__private::search(0x1001000_u32, 117),
+        ::trie_hard::__private::leaf(b"prefer", "prefer".len()),
+        ::trie_hard::__private::leaf(b"priority", "priority".len()),
+        ::trie_hard::__private::search(0x4400000_u32, 119),
+        ::trie_hard::__private::run(b"er", 121),
+        ::trie_hard::__private::search(0x4100_u32, 122),
+        ::trie_hard::__private::leaf(b"retry-after", "retry-after".len()),
+        ::trie_hard::__private::search(0x800001_u32, 124),
+        ::trie_hard::__private::leaf(b"server-timing", "server-timing".len()),
+        ::trie_hard::__private::search(0x40001_u32, 126),
+        ::trie_hard::__private::leaf(b"status", "status".len()),
+        ::trie_hard::__private::leaf(b"strict-transport-security", "strict-transport-security".len()),
+        ::trie_hard::__private::search(0x1040_u32, 128),
+        ::trie_hard::__private::leaf(b"x-att-deviceid", "x-att-deviceid".len()),
+        ::trie_hard::__private::search(0x20000_u32, 130),
+        ::trie_hard::__private::leaf(b"x-dns-prefetch-control", "x-dns-prefetch-control".len()),
+        ::trie_hard::__private::search(0x120000_u32, 131),
+        ::trie_hard::__private::leaf(b"x-powered-by", "x-powered-by".len()),
+        ::trie_hard::__private::leaf(b"x-request-id", "x-request-id".len()),
+        ::trie_hard::__private::leaf(b"x-ua-compatible", "x-ua-compatible".len()),
+        ::trie_hard::__private::leaf(b"x-wap-profile", "x-wap-profile".len()),
+        ::trie_hard::__private::leaf(b"x-xss-protection", "x-xss-protection".len()),
+        ::trie_hard::__private::run(b"t-", 133),
+        ::trie_hard::__private::run(b"s-control-", 134),
+        ::trie_hard::__private::leaf(b"authentication-control", "authentication-control".len()),
+        ::trie_hard::__private::leaf(b"authorization", "authorization".len()),
+        ::trie_hard::__private::leaf(b"connection", "connection".len()),
+        ::trie_hard::__private::run(b"ent-", 135),
+        ::trie_hard::__private::leaf(b"cross-origin-embedder-policy-report-only", "cross-origin-embedder-policy-report-only".len()),
+        ::trie_hard::__private::leaf(b"cross-origin-opener-policy-report-only", "cross-origin-opener-policy-report-only".len()),
+        ::trie_hard::__private::leaf(b"expect", "expect".len()),
+        ::trie_hard::__private::leaf(b"expires", "expires".len()),
+        ::trie_hard::__private::leaf(b"if-modified-since", "if-modified-since".len()),
+        ::trie_hard::__private::leaf(b"if-range", "if-range".len()),
+        ::trie_hard::__private::leaf(b"if-unmodified-since", "if-unmodified-since".len()),
+        ::trie_hard::__private::leaf(b"odata-isolation", "odata-isolation".len()),
+        ::trie_hard::__private::leaf(b"odata-version", "odata-version".len()),
+        ::trie_hard::__private::run(b"ocol", 136),
+        ::trie_hard::__private::run(b"y-", 137),
+        ::trie_hard::__private::search(0x100100_u32, 138),
+        ::trie_hard::__private::run(b"atability-", 140),
+        ::trie_hard::__private::leaf(b"replay-nonce", "replay-nonce".len()),
+        ::trie_hard::__private::search(0x2400240_u32, 141),
+        ::trie_hard::__private::leaf(b"security-scheme", "security-scheme".len()),
+        ::trie_hard::__private::leaf(b"set-cookie", "set-cookie".len()),
+        ::trie_hard::__private::leaf(b"setprofile", "setprofile".len()),
+        ::trie_hard::__private::leaf(b"traceparent", "traceparent".len()),
+        ::trie_hard::__private::leaf(b"trailer", "trailer".len()),
+        ::trie_hard::__private::search(0x110000_u32, 145),
+        ::trie_hard::__private::leaf(b"x-forwarded-host", "x-forwarded-host".len()),
+        ::trie_hard::__private::leaf(b"x-frame-options", "x-frame-options".len()),
+        ::trie_hard::__private::search(0x1402d0_u32, 147),
+        ::trie_hard::__private::search(0x100110_u32, 153),
+        ::trie_hard::__private::search(0x1304180_u32, 156),
+        ::trie_hard::__private::search_or_leaf(b"protocol", "protocol".len(), 0x1_u32, 162),
+        ::trie_hard::__private::search(0x200210_u32, 163),
+        ::trie_hard::__private::search(0x100000_u32, 166),
+        ::trie_hard::__private::leaf(b"referrer-policy", "referrer-policy".len()),
+        ::trie_hard::__private::search(0x100040_u32, 167),
+        ::trie_hard::__private::run(b"h-ua", 169),
+        ::trie_hard::__private::run(b"etch-", 170),
+        ::trie_hard::__private::leaf(b"sec-token-binding", "sec-token-binding".len()),
+        ::trie_hard::__private::run(b"ebsocket-", 171),
+        ::trie_hard::__private::leaf(b"x-content-security-policy", "x-content-security-policy".len()),
+        ::trie_hard::__private::leaf(b"x-correlation-id", "x-correlation-id".len()),
+        ::trie_hard::__private::leaf(b"accept-additions", "accept-additions".len()),
+        ::trie_hard::__private::leaf(b"accept-ch-lifetime", "accept-ch-lifetime".len()),
+        ::trie_hard::__private::leaf(b"accept-datetime", "accept-datetime".len()),
+        ::trie_hard::__private::leaf(b"accept-features", "accept-features".len()),
+        ::trie_hard::__private::leaf(b"accept-patch", "accept-patch".len()),
+        ::trie_hard::__private::leaf(b"accept-ranges", "accept-ranges".len()),
+        ::trie_hard::__private::run(b"llow-", 172),
+        ::trie_hard::__private::leaf(b"access-control-expose-headers", "access-control-expose-headers".len()),
+        ::trie_hard::__private::leaf(b"access-control-request-headers", "access-control-request-headers".len()),
+        ::trie_hard::__private::leaf(b"content-disposition", "content-disposition".len()),
+        ::trie_hard::__private::leaf(b"content-encoding", "content-encoding".len()),
+        ::trie_hard::__private::search(0x20010_u32, 173),
+        ::trie_hard::__private::leaf(b"content-range", "content-range".len()),
+        ::trie_hard::__private::search(0x400100_u32, 175),
+        ::trie_hard::__private::leaf(b"content-version", "content-version".len()),
+        ::trie_hard::__private::leaf(b"protocol-query", "protocol-query".len()),
+        ::trie_hard::__private::run(b"uth", 177),
+        ::trie_hard::__private::leaf(b"proxy-features", "proxy-features".len()),
+        ::trie_hard::__private::leaf(b"proxy-status", "proxy-status".len()),
+        ::trie_hard::__private::search_or_leaf(b"referer", "referer".len(), 0x4_u32, 178),
+        ::trie_hard::__private::leaf(b"repeatability-client-id", "repeatability-client-id".len()),
+        ::trie_hard::__private::leaf(b"repeatability-request-id", "repeatability-request-id".len()),
+        ::trie_hard::__private::search_or_leaf(b"sec-ch-ua", "sec-ch-ua".len(), 0x1_u32, 179),
+        ::trie_hard::__private::search(0x808000_u32, 180),
+        ::trie_hard::__private::search(0x40100_u32, 182),
+        ::trie_hard::__private::search(0x8040_u32, 184),
+        ::trie_hard::__private::leaf(b"content-language", "content-language".len()),
+        ::trie_hard::__private::leaf(b"content-location", "content-location".len()),
+        ::trie_hard::__private::leaf(b"content-security-policy", "content-security-policy".len()),
+        ::trie_hard::__private::leaf(b"content-style-type", "content-style-type".len()),
+        ::trie_hard::__private::search(0x20100_u32, 186),
+        ::trie_hard::__private::leaf(b"referer[sic]", "referer[sic]".len()),
+        ::trie_hard::__private::search(0x48220_u32, 188),
+        ::trie_hard::__private::leaf(b"sec-fetch-mode", "sec-fetch-mode".len()),
+        ::trie_hard::__private::leaf(b"sec-fetch-user", "sec-fetch-user".len()),
+        ::trie_hard::__private::leaf(b"sec-websocket-extensions", "sec-websocket-extensions".len()),
+        ::trie_hard::__private::leaf(b"sec-websocket-protocol", "sec-websocket-protocol".len()),
+        ::trie_hard::__private::leaf(b"access-control-allow-credentials", "access-control-allow-credentials".len()),
+        ::trie_hard::__private::leaf(b"access-control-allow-methods", "access-control-allow-methods".len()),
+        ::trie_hard::__private::leaf(b"proxy-authenticate", "proxy-authenticate".len()),
+        ::trie_hard::__private::leaf(b"proxy-authorization", "proxy-authorization".len()),
+        ::trie_hard::__private::leaf(b"sec-ch-ua-bitness", "sec-ch-ua-bitness".len()),
+        ::trie_hard::__private::leaf(b"sec-ch-ua-full-version-list", "sec-ch-ua-full-version-list".len()),
+        ::trie_hard::__private::leaf(b"sec-ch-ua-model", "sec-ch-ua-model".len()),
+        ::trie_hard::__private::leaf(b"sec-ch-ua-platform-version", "sec-ch-ua-platform-version".len()),
+    ],
+    &[118, 13, 20, 5, 4, 1, 1, 1, 4, 1, 3, 3, 6, 12, 8, 19, 3, 1, 1, 1, 11, 10, 1, 2, 2, 3, 1, 1, 1, 10, 2, 3, 1, 1, 1, 1, 2, 3, 1, 2, 1, 2, 1, 2, 1, 1, 1, 1, 1, 1, 1, 8, 1, 1, 7, 1, 14, 1, 2, 1, 1, 2, 11, 10, 2, 1, 1, 1, 1, 1, 9, 1, 2, 1, 1, 1, 2, 3, 1, 1, 1, 1, 2, 1, 1, 6, 3, 3, 1, 11, 1, 2, 1, 1, 2, 1, 2, 1, 2, 1, 1, 1, 1, 1, 6, 4, 1, 1, 1, 8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 4, 3, 2, 1, 10, 1, 1, 1, 1, 1, 2, 1, 1, 6, 4, 8, 2, 4, 2, 1, 2, 5, 2, 1, 2, 1, 1, 1, 1, 1, 1, 1, 1, 2, 1, 1, 1, 1, 2, 1, 2, 1, 1, 2, 1, 1, 2, 1, 1, 5, 2, 2, 2, 1, 1, 1, 1, 2, 1, 4, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+));
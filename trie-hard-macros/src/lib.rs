@@ -0,0 +1,185 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedural macros for building [`trie_hard`] tries at compile time. See
+//! [`trie_hard!`] for details.
+
+#![deny(
+    missing_docs,
+    missing_debug_implementations,
+    unreachable_pub,
+    rustdoc::broken_intra_doc_links,
+    unsafe_code
+)]
+#![warn(rust_2018_idioms)]
+
+use std::collections::BTreeMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    Expr, Lit, LitByte, LitByteStr, LitInt, Token,
+};
+use trie_hard::{
+    __private::{skeleton, MaskParts, SkeletonNode},
+    TrieHard,
+};
+
+struct Entry {
+    key: Lit,
+    value: Expr,
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let key = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let value = input.parse()?;
+        Ok(Entry { key, value })
+    }
+}
+
+/// Build a [`TrieHard`] at compile time. Keys are string or byte string
+/// literals and values are any expressions that can be evaluated in a
+/// `const` context. The trie is laid out by the same algorithm as
+/// [`TrieHard::new`] and the integer size is picked the same way, so the
+/// result can be stored in a `static` with no construction cost at runtime.
+///
+/// ```
+/// use trie_hard::TrieHard;
+/// use trie_hard_macros::trie_hard;
+///
+/// static HEADERS: TrieHard<'static, u8> = trie_hard! {
+///     "connection" => 0,
+///     "keep-alive" => 1,
+///     b"upgrade" => 2,
+/// };
+///
+/// assert_eq!(HEADERS.get("keep-alive"), Some(1));
+/// assert!(HEADERS.get("host").is_none());
+/// ```
+///
+/// A key given more than once takes the last value given for it, as with
+/// [`TrieHard::new`]. Values must not need to be dropped, since they are
+/// stored in a static array, and must implement `Clone`. The nodes of a trie built this way stay
+/// in static memory until it is first modified, at which point they are
+/// copied, so a `const` trie or a clone of a `static` one can be changed like
+/// any other.
+#[proc_macro]
+pub fn trie_hard(input: TokenStream) -> TokenStream {
+    let entries = parse_macro_input!(
+        input with Punctuated::<Entry, Token![,]>::parse_terminated
+    );
+
+    let mut keys = Vec::with_capacity(entries.len());
+    let mut values = Vec::with_capacity(entries.len());
+    let mut seen = BTreeMap::new();
+
+    for Entry { key, value } in entries {
+        let bytes = match &key {
+            Lit::Str(key) => key.value().into_bytes(),
+            Lit::ByteStr(key) => key.value(),
+            _ => {
+                return syn::Error::new_spanned(
+                    key,
+                    "expected a string or byte string literal",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+
+        // A repeated key keeps its place and takes the later value, the same
+        // as when a trie is built at runtime
+        if let Some(index) = seen.get(&bytes) {
+            values[*index] = value;
+            continue;
+        }
+
+        seen.insert(bytes.clone(), keys.len());
+        keys.push(bytes);
+        values.push(value);
+    }
+
+    let trie = TrieHard::new(
+        keys.iter()
+            .enumerate()
+            .map(|(index, key)| (key.as_slice(), index))
+            .collect(),
+    );
+
+    let skeleton = skeleton(&trie);
+    let mask = |mask: &MaskParts| mask_tokens(skeleton.bits, mask);
+    let variant = format_ident!("{}", skeleton.variant);
+    let zero = mask(&[0; 4]);
+
+    let masks = skeleton.masks.iter().map(|(byte, bits)| {
+        let byte = LitByte::new(*byte, Span::call_site());
+        let bits = mask(bits);
+        quote!((#byte, #bits))
+    });
+
+    let nodes = skeleton.nodes.iter().map(|node| match node {
+        SkeletonNode::Leaf(key, value) => {
+            let key = LitByteStr::new(key, Span::call_site());
+            let value = &values[*value];
+            quote!(::trie_hard::__private::leaf(#key, #value))
+        }
+        SkeletonNode::Search(bits, edge_start) => {
+            let bits = mask(bits);
+            quote!(::trie_hard::__private::search(#bits, #edge_start))
+        }
+        SkeletonNode::SearchOrLeaf(key, value, bits, edge_start) => {
+            let key = LitByteStr::new(key, Span::call_site());
+            let value = &values[*value];
+            let bits = mask(bits);
+            quote!(::trie_hard::__private::search_or_leaf(
+                #key, #value, #bits, #edge_start
+            ))
+        }
+        SkeletonNode::Run(run, next) => {
+            let run = LitByteStr::new(run, Span::call_site());
+            quote!(::trie_hard::__private::run(#run, #next))
+        }
+    });
+
+    let below = skeleton.below;
+
+    quote!(
+        ::trie_hard::TrieHard::#variant(::trie_hard::__private::sized(
+            ::trie_hard::__private::masks(#zero, &[#(#masks),*]),
+            &[#(#nodes),*],
+            &[#(#below),*],
+        ))
+    )
+    .into()
+}
+
+/// Tokens for a mask of the given number of bits
+fn mask_tokens(bits: u32, mask: &MaskParts) -> TokenStream2 {
+    if bits == 256 {
+        let parts = mask
+            .iter()
+            .map(|part| LitInt::new(&format!("{part:#x}"), Span::call_site()));
+        quote!(::trie_hard::__private::u256([#(#parts),*]))
+    } else {
+        let mask = mask[0] as u128 | (mask[1] as u128) << 64;
+        LitInt::new(&format!("{mask:#x}_u{bits}"), Span::call_site())
+            .into_token_stream()
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2024 Cloudflare, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rstest::rstest;
+use trie_hard::TrieHard;
+use trie_hard_macros::trie_hard;
+
+static SMALL: TrieHard<'static, &str> = trie_hard! {
+    "and" => "and",
+    "ant" => "ant",
+    "dad" => "dad",
+    "do" => "do",
+    "dot" => "dot",
+};
+
+static ALL_BYTES: TrieHard<'static, usize> = trie_hard! {
+    b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f\x10\x11\x12\x13\x14\x15\x16\x17\x18\x19\x1a\x1b\x1c\x1d\x1e\x1f\
+    \x20\x21\x22\x23\x24\x25\x26\x27\x28\x29\x2a\x2b\x2c\x2d\x2e\x2f\x30\x31\x32\x33\x34\x35\x36\x37\x38\x39\x3a\x3b\x3c\x3d\x3e\x3f\
+    \x40\x41\x42\x43\x44\x45\x46\x47\x48\x49\x4a\x4b\x4c\x4d\x4e\x4f\x50\x51\x52\x53\x54\x55\x56\x57\x58\x59\x5a\x5b\x5c\x5d\x5e\x5f\
+    \x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f\x70\x71\x72\x73\x74\x75\x76\x77\x78\x79\x7a\x7b\x7c\x7d\x7e\x7f\
+    \x80\x81\x82\x83\x84\x85\x86\x87\x88\x89\x8a\x8b\x8c\x8d\x8e\x8f\x90\x91\x92\x93\x94\x95\x96\x97\x98\x99\x9a\x9b\x9c\x9d\x9e\x9f\
+    \xa0\xa1\xa2\xa3\xa4\xa5\xa6\xa7\xa8\xa9\xaa\xab\xac\xad\xae\xaf\xb0\xb1\xb2\xb3\xb4\xb5\xb6\xb7\xb8\xb9\xba\xbb\xbc\xbd\xbe\xbf\
+    \xc0\xc1\xc2\xc3\xc4\xc5\xc6\xc7\xc8\xc9\xca\xcb\xcc\xcd\xce\xcf\xd0\xd1\xd2\xd3\xd4\xd5\xd6\xd7\xd8\xd9\xda\xdb\xdc\xdd\xde\xdf\
+    \xe0\xe1\xe2\xe3\xe4\xe5\xe6\xe7\xe8\xe9\xea\xeb\xec\xed\xee\xef\xf0\xf1\xf2\xf3\xf4\xf5\xf6\xf7\xf8\xf9\xfa\xfb\xfc\xfd\xfe\xff" => 0,
+    b"\xff\x00" => 1,
+    "" => 2,
+};
+
+static EMPTY: TrieHard<'static, u8> = trie_hard! {};
+
+static REPEATED: TrieHard<'static, u8> = trie_hard! {
+    "and" => 0,
+    "ant" => 1,
+    "and" => 2,
+};
+
+const NUMBERS: TrieHard<'static, u32> = trie_hard! {
+    "one" => 1,
+    "two" => 2,
+    "three" => 3,
+};
+
+fn runtime<'a>(keys: &[&'a [u8]]) -> TrieHard<'a, usize> {
+    TrieHard::new(keys.iter().copied().zip(0..).collect())
+}
+
+#[rstest]
+#[case("and", Some("and"))]
+#[case("do", Some("do"))]
+#[case("dot", Some("dot"))]
+#[case("d", None)]
+#[case("dots", None)]
+fn test_get(#[case] key: &str, #[case] expected: Option<&str>) {
+    assert_eq!(SMALL.get(key), expected);
+}
+
+#[test]
+fn test_matches_runtime() {
+    assert!(matches!(SMALL, TrieHard::U8(_)));
+    assert!(SMALL.iter().map(|(k, _)| k).eq(runtime(&[
+        b"and", b"ant", b"dad", b"do", b"dot"
+    ])
+    .iter()
+    .map(|(k, _)| k)));
+}
+
+#[test]
+fn test_repeated_key() {
+    assert_eq!(REPEATED.get("and"), Some(2));
+    assert_eq!(REPEATED.get("ant"), Some(1));
+    assert_eq!(
+        format!("{REPEATED:?}"),
+        format!(
+            "{:?}",
+            TrieHard::new(vec![(b"and", 0), (b"ant", 1), (b"and", 2)])
+        )
+    );
+}
+
+#[test]
+fn test_u256() {
+    let TrieHard::U256(_) = &ALL_BYTES else {
+        panic!("expected a U256 trie");
+    };
+
+    let all_bytes = (0..=255).collect::<Vec<u8>>();
+    assert_eq!(ALL_BYTES.get(&all_bytes), Some(0));
+    assert_eq!(ALL_BYTES.get(b"\xff\x00"), Some(1));
+    assert_eq!(ALL_BYTES.get(b""), Some(2));
+    assert_eq!(ALL_BYTES.get(b"\xff"), None);
+    assert_eq!(
+        ALL_BYTES.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+        [2, 0, 1]
+    );
+}
+
+#[test]
+fn test_empty() {
+    assert_eq!(EMPTY.iter().count(), 0);
+    assert_eq!(EMPTY.get("a"), None);
+}
+
+#[test]
+fn test_clone_is_mutable() {
+    let mut trie = SMALL.clone();
+    trie.insert("dots", "dots");
+    assert_eq!(trie.remove("and"), Some("and"));
+
+    assert_eq!(trie.get("dots"), Some("dots"));
+    assert_eq!(trie.get("and"), None);
+    assert_eq!(SMALL.get("and"), Some("and"));
+}
+
+#[test]
+fn test_const_is_mutable() {
+    let mut trie = NUMBERS;
+    *trie.get_mut("one").unwrap() = 10;
+    assert_eq!(trie.insert("four", 4), None);
+    assert_eq!(trie.remove("two"), Some(2));
+
+    assert_eq!(
+        trie.iter().collect::<Vec<_>>(),
+        [(&b"four"[..], 4), (b"one", 10), (b"three", 3)]
+    );
+    let original = NUMBERS;
+    assert_eq!(original.get("one"), Some(1));
+    assert_eq!(original.get("two"), Some(2));
+}